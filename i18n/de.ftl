@@ -0,0 +1,34 @@
+# German messages of fedora-update-notifier
+
+installed-ready = Installierte Aktualisierungen{ $location } warten auf Rückmeldung
+installed-ready-list = Installierte Aktualisierungen{ $location } warten auf Rückmeldung:
+installed-ready-aliases = Installierte Aktualisierungen{ $location } warten auf Rückmeldung: { $aliases } ({ $url })
+installed-ready-category = Installierte Aktualisierungen{ $location } warten auf Rückmeldung: { $category } ({ $count })
+interesting-available = Aktualisierungen für interessante Pakete{ $location } können getestet werden.
+interesting-available-list = Aktualisierungen für interessante Pakete{ $location } können getestet werden:
+interesting-available-aliases = Aktualisierungen für interessante Pakete{ $location } können getestet werden: { $aliases } ({ $url })
+interesting-available-category = Aktualisierungen für interessante Pakete{ $location } können getestet werden: { $category } ({ $count })
+installed-none = Keine Aktualisierungen für installierte Pakete{ $location } warten auf Rückmeldung.
+interesting-none = Keine Aktualisierungen für interessante Pakete{ $location } verfügbar.
+feedback-url = Adresse für Rückmeldungen: { $url }
+install-relevant = Die relevanten Aktualisierungen installieren mit:
+install-with = Installieren mit:
+submitter = Eingereicht von: { $submitter }
+status = Status: { $status }
+system = System: { $system }
+builds = Builds:
+notes = Anmerkungen:
+karma = Karma:
+bugs = Fehler:
+test-cases = Testfälle:
+koji-build = Neuer Build in koji abgeschlossen: { $nvr }
+koji-builds = Neue Builds in koji abgeschlossen:
+category-security = Sicherheit
+category-desktop = Desktop
+category-tools = Kommandozeilenwerkzeuge
+category-libraries = Bibliotheken
+packages-and-more = { $packages } und { $count } weitere
+more-updates = { $count } weitere Aktualisierungen warten auf Rückmeldung
+held-back = { $count } Benachrichtigungen wurden zurückgehalten
+reboot-needed = Vor dem Testen neu starten, der Kernel oder grundlegende Bibliotheken wurden aktualisiert.
+restart-services = Diese Dienste vor dem Testen neu starten:
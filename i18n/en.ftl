@@ -0,0 +1,35 @@
+# English messages of fedora-update-notifier
+# placeholders are named, and messages that are missing from translations fall back to these
+
+installed-ready = Installed updates{ $location } are ready for feedback
+installed-ready-list = Installed updates{ $location } are ready for feedback:
+installed-ready-aliases = Installed updates{ $location } are ready for feedback: { $aliases } ({ $url })
+installed-ready-category = Installed updates{ $location } are ready for feedback: { $category } ({ $count })
+interesting-available = Updates for interesting packages{ $location } are available for testing.
+interesting-available-list = Updates for interesting packages{ $location } are available for testing:
+interesting-available-aliases = Updates for interesting packages{ $location } are available for testing: { $aliases } ({ $url })
+interesting-available-category = Updates for interesting packages{ $location } are available for testing: { $category } ({ $count })
+installed-none = No updates for installed packages{ $location } are waiting for feedback.
+interesting-none = No updates for interesting packages{ $location } are available.
+feedback-url = Feedback URL: { $url }
+install-relevant = Install the relevant updates with:
+install-with = Install with:
+submitter = Submitter: { $submitter }
+status = Status: { $status }
+system = System: { $system }
+builds = Builds:
+notes = Notes:
+karma = Karma:
+bugs = Bugs:
+test-cases = Test cases:
+koji-build = New build completed in koji: { $nvr }
+koji-builds = New builds completed in koji:
+category-security = Security
+category-desktop = Desktop
+category-tools = Command-line tools
+category-libraries = Libraries
+packages-and-more = { $packages } and { $count } more
+more-updates = { $count } more updates need feedback
+held-back = { $count } notifications were held back
+reboot-needed = Reboot the system before testing, the kernel or core libraries were updated.
+restart-services = Restart these services before testing:
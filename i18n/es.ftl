@@ -0,0 +1,34 @@
+# Spanish messages of fedora-update-notifier
+
+installed-ready = Actualizaciones instaladas{ $location } esperan comentarios
+installed-ready-list = Actualizaciones instaladas{ $location } esperan comentarios:
+installed-ready-aliases = Actualizaciones instaladas{ $location } esperan comentarios: { $aliases } ({ $url })
+installed-ready-category = Actualizaciones instaladas{ $location } esperan comentarios: { $category } ({ $count })
+interesting-available = Hay actualizaciones de paquetes de interés{ $location } disponibles para probar.
+interesting-available-list = Hay actualizaciones de paquetes de interés{ $location } disponibles para probar:
+interesting-available-aliases = Hay actualizaciones de paquetes de interés{ $location } disponibles para probar: { $aliases } ({ $url })
+interesting-available-category = Hay actualizaciones de paquetes de interés{ $location } disponibles para probar: { $category } ({ $count })
+installed-none = Ninguna actualización de paquetes instalados{ $location } espera comentarios.
+interesting-none = No hay actualizaciones de paquetes de interés{ $location } disponibles.
+feedback-url = URL para comentarios: { $url }
+install-relevant = Instale las actualizaciones relevantes con:
+install-with = Instalar con:
+submitter = Enviada por: { $submitter }
+status = Estado: { $status }
+system = Sistema: { $system }
+builds = Compilaciones:
+notes = Notas:
+karma = Karma:
+bugs = Errores:
+test-cases = Casos de prueba:
+koji-build = Nueva compilación terminada en koji: { $nvr }
+koji-builds = Nuevas compilaciones terminadas en koji:
+category-security = Seguridad
+category-desktop = Escritorio
+category-tools = Herramientas de línea de órdenes
+category-libraries = Bibliotecas
+packages-and-more = { $packages } y { $count } más
+more-updates = { $count } actualizaciones más esperan comentarios
+held-back = { $count } notificaciones fueron retenidas
+reboot-needed = Reinicie el sistema antes de probar, se actualizaron el kernel o bibliotecas básicas.
+restart-services = Reinicie estos servicios antes de probar:
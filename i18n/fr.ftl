@@ -0,0 +1,34 @@
+# French messages of fedora-update-notifier
+
+installed-ready = Des mises à jour installées{ $location } attendent vos retours
+installed-ready-list = Des mises à jour installées{ $location } attendent vos retours :
+installed-ready-aliases = Des mises à jour installées{ $location } attendent vos retours : { $aliases } ({ $url })
+installed-ready-category = Des mises à jour installées{ $location } attendent vos retours : { $category } ({ $count })
+interesting-available = Des mises à jour de paquets intéressants{ $location } sont disponibles pour des tests.
+interesting-available-list = Des mises à jour de paquets intéressants{ $location } sont disponibles pour des tests :
+interesting-available-aliases = Des mises à jour de paquets intéressants{ $location } sont disponibles pour des tests : { $aliases } ({ $url })
+interesting-available-category = Des mises à jour de paquets intéressants{ $location } sont disponibles pour des tests : { $category } ({ $count })
+installed-none = Aucune mise à jour de paquets installés{ $location } n'attend de retours.
+interesting-none = Aucune mise à jour de paquets intéressants{ $location } n'est disponible.
+feedback-url = Adresse pour les retours : { $url }
+install-relevant = Installez les mises à jour concernées avec :
+install-with = Installer avec :
+submitter = Proposée par : { $submitter }
+status = État : { $status }
+system = Système : { $system }
+builds = Builds :
+notes = Notes :
+karma = Karma :
+bugs = Bogues :
+test-cases = Cas de test :
+koji-build = Nouveau build terminé dans koji : { $nvr }
+koji-builds = Nouveaux builds terminés dans koji :
+category-security = Sécurité
+category-desktop = Bureau
+category-tools = Outils en ligne de commande
+category-libraries = Bibliothèques
+packages-and-more = { $packages } et { $count } de plus
+more-updates = { $count } autres mises à jour attendent vos retours
+held-back = { $count } notifications ont été retenues
+reboot-needed = Redémarrez le système avant de tester, le noyau ou des bibliothèques de base ont été mis à jour.
+restart-services = Redémarrez ces services avant de tester :
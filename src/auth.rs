@@ -0,0 +1,306 @@
+use std::io::{stdin, stdout, Write};
+use std::process::Command;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::http;
+
+const OIDC_PROVIDER: &str = "https://id.fedoraproject.org/openidc";
+const OIDC_CLIENT_ID: &str = "bodhi-client";
+const OIDC_SCOPES: &str = "openid email profile";
+const OIDC_REDIRECT_URI: &str = "urn:ietf:wg:oauth:2.0:oob";
+
+const KEYRING_SERVICE: &str = "fedora-update-notifier";
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    device_authorization_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+}
+
+pub struct Authenticator {
+    client: reqwest::Client,
+    username: String,
+}
+
+fn keyring_entry(username: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, username).map_err(|error| format!("Failed to access keyring: {}", error))
+}
+
+fn load_refresh_token(username: &str) -> Result<Option<String>, String> {
+    match keyring_entry(username)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(error) => Err(format!("Failed to read token from keyring: {}", error)),
+    }
+}
+
+fn store_refresh_token(username: &str, token: &str) -> Result<(), String> {
+    keyring_entry(username)?
+        .set_password(token)
+        .map_err(|error| format!("Failed to store token in keyring: {}", error))
+}
+
+// PKCE code verifier and S256 code challenge
+fn pkce() -> (String, String) {
+    let verifier: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect();
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    (verifier, challenge)
+}
+
+pub fn prompt(message: &str) -> Result<String, String> {
+    print!("{}", message);
+    let _ = stdout().flush();
+
+    let mut line = String::new();
+    match stdin().read_line(&mut line) {
+        Ok(_) => Ok(line.trim().to_owned()),
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+impl Authenticator {
+    pub fn new(username: &str) -> Result<Self, String> {
+        let client = match http::client_builder().build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Authenticator {
+            client,
+            username: username.to_owned(),
+        })
+    }
+
+    async fn discover(&self) -> Result<Discovery, String> {
+        let url = format!("{}/.well-known/openid-configuration", OIDC_PROVIDER);
+
+        let response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                return Err(format!("Failed to query OpenID Connect provider: {}", error));
+            },
+        };
+
+        match response.json().await {
+            Ok(discovery) => Ok(discovery),
+            Err(error) => Err(format!("Failed to query OpenID Connect provider: {}", error)),
+        }
+    }
+
+    async fn token_request(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<TokenResponse, String> {
+        let response = match self.client.post(endpoint).form(params).send().await {
+            Ok(response) => response,
+            Err(error) => {
+                return Err(format!("Failed to request token: {}", error));
+            },
+        };
+
+        let success = response.status().is_success();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(error) => {
+                return Err(format!("Failed to request token: {}", error));
+            },
+        };
+
+        if success {
+            serde_json::from_str(&body).map_err(|error| format!("Failed to parse token: {}", error))
+        } else {
+            match serde_json::from_str::<TokenError>(&body) {
+                Ok(error) => Err(error.error),
+                Err(_) => Err(format!("Failed to request token: {}", body)),
+            }
+        }
+    }
+
+    async fn device_flow(&self, discovery: &Discovery, endpoint: &str) -> Result<TokenResponse, String> {
+        let response = match self
+            .client
+            .post(endpoint)
+            .form(&[("client_id", OIDC_CLIENT_ID), ("scope", OIDC_SCOPES)])
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(error) => {
+                return Err(format!("Failed to start device authorization: {}", error));
+            },
+        };
+
+        let authorization: DeviceAuthorization = match response.json().await {
+            Ok(authorization) => authorization,
+            Err(error) => {
+                return Err(format!("Failed to start device authorization: {}", error));
+            },
+        };
+
+        match &authorization.verification_uri_complete {
+            Some(uri) => println!("Visit {} to log in.", uri),
+            None => println!(
+                "Visit {} and enter the code {} to log in.",
+                &authorization.verification_uri, &authorization.user_code
+            ),
+        }
+
+        let mut interval = Duration::from_secs(authorization.interval.unwrap_or(5));
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let result = self
+                .token_request(
+                    &discovery.token_endpoint,
+                    &[
+                        ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                        ("device_code", authorization.device_code.as_str()),
+                        ("client_id", OIDC_CLIENT_ID),
+                    ],
+                )
+                .await;
+
+            match result {
+                Ok(tokens) => return Ok(tokens),
+                Err(error) if error == "authorization_pending" => {},
+                Err(error) if error == "slow_down" => interval += Duration::from_secs(5),
+                Err(error) => return Err(format!("Failed to log in: {}", error)),
+            }
+        }
+    }
+
+    async fn browser_flow(&self, discovery: &Discovery) -> Result<TokenResponse, String> {
+        let (verifier, challenge) = pkce();
+
+        let url = match reqwest::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", OIDC_CLIENT_ID),
+                ("scope", OIDC_SCOPES),
+                ("redirect_uri", OIDC_REDIRECT_URI),
+                ("code_challenge", challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        ) {
+            Ok(url) => url,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        println!("Visit this URL to log in (it should open in your browser):");
+        println!("{}", url);
+        let _ = Command::new("xdg-open").arg(url.as_str()).status();
+
+        let code = prompt("Enter the authorization code: ")?;
+
+        self.token_request(
+            &discovery.token_endpoint,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code.as_str()),
+                ("redirect_uri", OIDC_REDIRECT_URI),
+                ("client_id", OIDC_CLIENT_ID),
+                ("code_verifier", verifier.as_str()),
+            ],
+        )
+        .await
+        .map_err(|error| format!("Failed to log in: {}", error))
+    }
+
+    pub async fn login(&self) -> Result<(), String> {
+        let discovery = self.discover().await?;
+
+        let tokens = match &discovery.device_authorization_endpoint {
+            Some(endpoint) => self.device_flow(&discovery, endpoint).await?,
+            None => self.browser_flow(&discovery).await?,
+        };
+
+        match &tokens.refresh_token {
+            Some(refresh_token) => store_refresh_token(&self.username, refresh_token)?,
+            None => {
+                return Err(String::from("No refresh token was issued, unable to stay logged in."));
+            },
+        }
+
+        println!("Logged in as {}.", &self.username);
+        Ok(())
+    }
+
+    pub fn logout(&self) -> Result<(), String> {
+        match keyring_entry(&self.username)?.delete_password() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(error) => Err(format!("Failed to remove token from keyring: {}", error)),
+        }
+    }
+
+    // exchanges the stored refresh token for a new access token
+    pub async fn access_token(&self) -> Result<String, String> {
+        let refresh_token = match load_refresh_token(&self.username)? {
+            Some(token) => token,
+            None => {
+                return Err(String::from(
+                    "Not logged in, run \"fedora-update-notifier login\" first.",
+                ));
+            },
+        };
+
+        let discovery = self.discover().await?;
+
+        let tokens = self
+            .token_request(
+                &discovery.token_endpoint,
+                &[
+                    ("grant_type", "refresh_token"),
+                    ("refresh_token", refresh_token.as_str()),
+                    ("client_id", OIDC_CLIENT_ID),
+                ],
+            )
+            .await
+            .map_err(|error| format!("Failed to refresh login, try logging in again: {}", error))?;
+
+        // refresh tokens can be rotated by the provider
+        if let Some(refresh_token) = &tokens.refresh_token {
+            store_refresh_token(&self.username, refresh_token)?;
+        }
+
+        Ok(tokens.access_token)
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs::{create_dir_all, remove_file, write};
+use std::path::PathBuf;
+
+const ENTRY_NAME: &str = "fedora-update-notifier.desktop";
+
+// seconds to wait after login, so the check doesn't slow down starting the session
+pub const DEFAULT_DELAY: u64 = 120;
+
+fn autostart_dir() -> Result<PathBuf, String> {
+    match dirs::config_dir() {
+        Some(path) => Ok(path.join("autostart")),
+        None => Err(String::from("Unable to determine $XDG_CONFIG_HOME.")),
+    }
+}
+
+// quoting rules for arguments of the Exec key in desktop entries
+fn quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            },
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// only GNOME supports X-GNOME-Autostart-Delay, so the delay is implemented with sleep instead
+fn desktop_entry(exec: &str, delay: u64) -> String {
+    let command = format!("sleep {} && exec '{}' --wait", delay, exec.replace('\'', "'\\''"));
+
+    format!(
+        r#"[Desktop Entry]
+Type=Application
+Name=Fedora Update Notifier
+Comment=Check for installed fedora updates awaiting feedback
+Exec=sh -c {}
+Terminal=false
+NoDisplay=true
+X-GNOME-Autostart-enabled=true
+"#,
+        quote(&command)
+    )
+}
+
+pub fn install_autostart(delay: u64) -> Result<(), String> {
+    let exec = match std::env::current_exe() {
+        Ok(path) => path,
+        Err(error) => {
+            return Err(format!("Unable to determine path of the executable: {}", error));
+        },
+    };
+
+    let dir = autostart_dir()?;
+    if let Err(error) = create_dir_all(&dir) {
+        return Err(format!("Unable to create {}: {}", dir.display(), error));
+    }
+
+    let path = dir.join(ENTRY_NAME);
+    if let Err(error) = write(&path, desktop_entry(&exec.to_string_lossy(), delay)) {
+        return Err(format!("Unable to write {}: {}", path.display(), error));
+    }
+
+    println!("Installed {} (runs {} seconds after login)", path.display(), delay);
+
+    Ok(())
+}
+
+pub fn uninstall_autostart() -> Result<(), String> {
+    let path = autostart_dir()?.join(ENTRY_NAME);
+
+    if path.exists() {
+        if let Err(error) = remove_file(&path) {
+            return Err(format!("Unable to remove {}: {}", path.display(), error));
+        }
+        println!("Removed {}", path.display());
+    }
+
+    Ok(())
+}
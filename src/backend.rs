@@ -0,0 +1,542 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+use tracing::{debug, warn};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use crate::cache::Cache;
+use crate::dnf::{dnf, glob_matches, DnfError};
+use crate::nvr::{self, NEVR};
+use crate::subprocess;
+
+const DNF_DAEMON: &str = "org.rpm.dnf.v0";
+const DNF_DAEMON_PATH: &str = "/org/rpm/dnf/v0";
+const DNF_DAEMON_SESSION_MANAGER: &str = "org.rpm.dnf.v0.SessionManager";
+const DNF_DAEMON_RPM: &str = "org.rpm.dnf.v0.rpm.Rpm";
+
+// the rpm database is in /usr/lib/sysimage/rpm since Fedora 36, and in /var/lib/rpm before that; changes to the sqlite
+// database can stay in its write-ahead log for a while
+const RPMDB_FILES: &[&str] = &[
+    "/usr/lib/sysimage/rpm/rpmdb.sqlite",
+    "/usr/lib/sysimage/rpm/rpmdb.sqlite-wal",
+    "/var/lib/rpm/rpmdb.sqlite",
+    "/var/lib/rpm/rpmdb.sqlite-wal",
+    "/var/lib/rpm/Packages",
+];
+
+// installed binary packages are mapped to the source packages they were built from
+const RPM_QUERYFORMAT: &str = "%{NAME} %{EPOCH} %{SOURCERPM}\\n";
+const DNF_QUERYFORMAT: &str = "%{name} %{epoch} %{sourcerpm}\n";
+const RPM_SOURCE_QUERYFORMAT: &str = "%{NAME} %{SOURCERPM}\\n";
+
+pub trait PackageBackend: Send {
+    fn name(&self) -> &str;
+
+    // returns the source packages of all installed packages
+    fn installed(&self) -> Result<Vec<NEVR>, String>;
+}
+
+// a single unexpected line (i.e. from a bootstrap package) shouldn't break everything
+fn parse_packages(lines: &[String]) -> Result<Vec<NEVR>, String> {
+    let mut packages: Vec<NEVR> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        match NEVR::from_package(line.trim()) {
+            Ok(nevr) => packages.push(nevr),
+            Err(error) => {
+                warn!("Skipping installed package: {}", error);
+                errors.push(error);
+            },
+        }
+    }
+
+    if packages.is_empty() && !errors.is_empty() {
+        return Err(format!("Failed to parse any installed packages: {}", errors.join("; ")));
+    }
+
+    Ok(nvr::dedup(packages))
+}
+
+// parses the output of rpm queries with RPM_QUERYFORMAT
+fn parse_output(output: &str) -> Result<Vec<NEVR>, String> {
+    // gpg-pubkey pseudo-packages have no source package
+    let lines: Vec<String> = output
+        .lines()
+        .filter(|line| line.ends_with(".src.rpm"))
+        .map(String::from)
+        .collect();
+
+    parse_packages(&lines)
+}
+
+pub struct DnfDaemonBackend;
+
+impl PackageBackend for DnfDaemonBackend {
+    fn name(&self) -> &str {
+        "dnf5daemon"
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        match query_dnfdaemon() {
+            Ok(packages) => parse_packages(&packages),
+            Err(error) => Err(format!("{}", error)),
+        }
+    }
+}
+
+pub struct DnfBackend;
+
+impl PackageBackend for DnfBackend {
+    fn name(&self) -> &str {
+        "dnf"
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        // the --cacheonly query fails on systems without populated cache
+        let packages = match query_dnf(true) {
+            Ok(packages) => packages,
+            // dnf already waited for the lock, so a second query would only wait again
+            Err(DnfError::Locked) => return Err(DnfError::Locked.into()),
+            Err(_) => query_dnf(false)?,
+        };
+
+        parse_packages(&packages)
+    }
+}
+
+fn rpm(root: Option<&Path>, queryformat: &str) -> Result<String, String> {
+    let mut command = Command::new("rpm");
+    if let Some(root) = root {
+        command.arg("--root").arg(root);
+    }
+    command.args(&["--query", "--all", "--queryformat", queryformat]);
+
+    let output = match subprocess::output(&mut command) {
+        Ok(output) => output,
+        Err(error) => {
+            return Err(format!("Failed to run rpm: {}", error));
+        },
+    };
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to query rpm: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    match String::from_utf8(output.stdout) {
+        Ok(result) => Ok(result),
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+fn query_rpm(root: Option<&Path>) -> Result<Vec<NEVR>, String> {
+    parse_output(&rpm(root, RPM_QUERYFORMAT)?)
+}
+
+// source packages of installed binary packages whose names match any of the patterns
+pub fn matching_sources(patterns: &[String]) -> Result<HashSet<String>, String> {
+    let mut sources = HashSet::new();
+
+    for line in rpm(None, RPM_SOURCE_QUERYFORMAT)?
+        .lines()
+        .filter(|line| line.ends_with(".src.rpm"))
+    {
+        match nvr::parse_source(line) {
+            Ok((name, source)) => {
+                if patterns.iter().any(|pattern| glob_matches(pattern, name)) {
+                    sources.insert(source.to_owned());
+                }
+            },
+            Err(error) => warn!("Skipping installed package: {}", error),
+        }
+    }
+
+    Ok(sources)
+}
+
+// reads the rpm database directly, which works without dnf and doesn't need the transaction lock
+pub struct RpmBackend;
+
+impl PackageBackend for RpmBackend {
+    fn name(&self) -> &str {
+        "rpm"
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        query_rpm(None)
+    }
+}
+
+// reads the rpm database of a chroot that isn't running, i.e. of mock
+pub struct ChrootBackend {
+    name: String,
+    root: PathBuf,
+}
+
+impl ChrootBackend {
+    pub fn new(root: &Path) -> Self {
+        ChrootBackend {
+            name: format!("rpm ({})", root.display()),
+            root: root.to_owned(),
+        }
+    }
+}
+
+impl PackageBackend for ChrootBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        query_rpm(Some(&self.root))
+    }
+}
+
+// queries rpm through another command, i.e. "podman exec" in a container
+pub struct RemoteBackend {
+    name: String,
+    command: Vec<String>,
+    // arguments are interpreted by a shell on the other side
+    shell: bool,
+}
+
+impl RemoteBackend {
+    pub fn podman(container: &str) -> Self {
+        RemoteBackend {
+            name: format!("podman ({})", container),
+            command: vec![String::from("podman"), String::from("exec"), container.to_owned()],
+            shell: false,
+        }
+    }
+
+    pub fn ssh(name: &str, destination: &str, port: Option<u16>) -> Self {
+        // never prompt for passwords, this usually runs non-interactively
+        let mut command = vec![String::from("ssh"), String::from("-o"), String::from("BatchMode=yes")];
+
+        if let Some(port) = port {
+            command.push(String::from("-p"));
+            command.push(port.to_string());
+        }
+
+        command.push(destination.to_owned());
+
+        RemoteBackend {
+            name: format!("ssh ({})", name),
+            command,
+            shell: true,
+        }
+    }
+
+    pub fn exec(&self, args: &[&str]) -> Result<String, String> {
+        let mut command = Command::new(&self.command[0]);
+        command.args(&self.command[1..]);
+
+        for arg in args {
+            if self.shell {
+                command.arg(format!("'{}'", arg.replace('\'', "'\\''")));
+            } else {
+                command.arg(arg);
+            }
+        }
+
+        let output = match subprocess::output(&mut command) {
+            Ok(output) => output,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to run command with {}: {}",
+                &self.name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        match String::from_utf8(output.stdout) {
+            Ok(result) => Ok(result),
+            Err(error) => Err(format!("{}", error)),
+        }
+    }
+}
+
+impl PackageBackend for RemoteBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        let output = self.exec(&["rpm", "--query", "--all", "--queryformat", RPM_QUERYFORMAT])?;
+        parse_output(&output)
+    }
+}
+
+// queries a podman container, and stops it again afterwards if it was only started for that
+pub struct ContainerBackend {
+    remote: RemoteBackend,
+    container: String,
+    stop: bool,
+}
+
+impl ContainerBackend {
+    pub fn new(container: &str, stop: bool) -> Self {
+        ContainerBackend {
+            remote: RemoteBackend::podman(container),
+            container: container.to_owned(),
+            stop,
+        }
+    }
+
+    pub fn exec(&self, args: &[&str]) -> Result<String, String> {
+        self.remote.exec(args)
+    }
+
+    pub fn stop(&self) {
+        if !self.stop {
+            return;
+        }
+
+        match subprocess::output(Command::new("podman").args(["stop", &self.container])) {
+            Ok(output) if output.status.success() => {},
+            Ok(output) => warn!(
+                "Failed to stop container {}: {}",
+                &self.container,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(error) => warn!("Failed to stop container {}: {}", &self.container, error),
+        }
+    }
+}
+
+impl PackageBackend for ContainerBackend {
+    fn name(&self) -> &str {
+        self.remote.name()
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        let packages = self.remote.installed();
+        self.stop();
+        packages
+    }
+}
+
+// changes whenever packages are installed, upgraded, or removed
+fn rpmdb_fingerprint() -> Option<String> {
+    let files: Vec<String> = RPMDB_FILES
+        .iter()
+        .filter_map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+            Some(format!(
+                "{}:{}.{}:{}",
+                path,
+                modified.as_secs(),
+                modified.subsec_nanos(),
+                metadata.len()
+            ))
+        })
+        .collect();
+
+    if files.is_empty() {
+        None
+    } else {
+        Some(files.join(";"))
+    }
+}
+
+fn format_cached(packages: &[NEVR]) -> String {
+    packages
+        .iter()
+        .map(|nevr| format!("{} {} {} {}\n", &nevr.n, nevr.e, &nevr.v, &nevr.r))
+        .collect()
+}
+
+fn parse_cached(packages: &str) -> Option<Vec<NEVR>> {
+    packages
+        .lines()
+        .map(|line| match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [n, e, v, r] => Some(NEVR {
+                n: (*n).to_owned(),
+                e: e.parse().ok()?,
+                v: (*v).to_owned(),
+                r: (*r).to_owned(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// skips querying installed packages (the slowest local step) if the rpm database didn't change since the last run
+pub struct CachedBackend {
+    inner: Box<dyn PackageBackend>,
+    read_only: bool,
+}
+
+impl CachedBackend {
+    pub fn new(inner: Box<dyn PackageBackend>, read_only: bool) -> Self {
+        CachedBackend { inner, read_only }
+    }
+}
+
+impl PackageBackend for CachedBackend {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        let fingerprint = match rpmdb_fingerprint() {
+            Some(fingerprint) => fingerprint,
+            None => return self.inner.installed(),
+        };
+
+        let cache = match if self.read_only {
+            Cache::open_read_only()
+        } else {
+            Cache::open()
+        } {
+            Ok(cache) => cache,
+            Err(error) => {
+                warn!("Failed to open cache of installed packages: {}", error);
+                return self.inner.installed();
+            },
+        };
+
+        if let Some(packages) = cache.installed_packages(&fingerprint).as_deref().and_then(parse_cached) {
+            debug!(
+                "The rpm database didn't change since the last run, using {} cached packages",
+                packages.len()
+            );
+            return Ok(packages);
+        }
+
+        let packages = self.inner.installed()?;
+        if let Err(error) = cache.store_installed_packages(&fingerprint, &format_cached(&packages)) {
+            warn!("Failed to cache installed packages: {}", error);
+        }
+
+        Ok(packages)
+    }
+}
+
+// tries all backends in order and returns the result of the first one that succeeds
+pub struct FallbackBackend {
+    backends: Vec<Box<dyn PackageBackend>>,
+}
+
+impl FallbackBackend {
+    pub fn new(backends: Vec<Box<dyn PackageBackend>>) -> Self {
+        FallbackBackend { backends }
+    }
+}
+
+impl Default for FallbackBackend {
+    fn default() -> Self {
+        FallbackBackend::new(vec![
+            Box::new(DnfDaemonBackend),
+            Box::new(DnfBackend),
+            Box::new(RpmBackend),
+        ])
+    }
+}
+
+impl PackageBackend for FallbackBackend {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    fn installed(&self) -> Result<Vec<NEVR>, String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        for backend in &self.backends {
+            let _span = tracing::info_span!("installed", backend = backend.name()).entered();
+
+            match backend.installed() {
+                Ok(packages) => {
+                    debug!("Found {} installed source packages", packages.len());
+                    return Ok(packages);
+                },
+                Err(error) => {
+                    warn!("Failed to query installed packages with {}: {}", backend.name(), error);
+                    errors.push(error);
+                },
+            }
+        }
+
+        Err(format!("Failed to query installed packages: {}", errors.join("; ")))
+    }
+}
+
+fn query_dnfdaemon() -> Result<Vec<String>, zbus::Error> {
+    let connection = Connection::system()?;
+
+    let manager = Proxy::new(&connection, DNF_DAEMON, DNF_DAEMON_PATH, DNF_DAEMON_SESSION_MANAGER)?;
+
+    let options: HashMap<&str, Value> = HashMap::new();
+    let session: OwnedObjectPath = manager.call("open_session", &(options,))?;
+
+    let result = list_packages(&connection, &session);
+
+    // always try to clean up the session, even if listing packages failed
+    let _closed: Result<bool, zbus::Error> = manager.call("close_session", &(&session,));
+
+    result
+}
+
+// returns installed packages in the same format as DNF_QUERYFORMAT
+fn list_packages(connection: &Connection, session: &OwnedObjectPath) -> Result<Vec<String>, zbus::Error> {
+    let rpm = Proxy::new(connection, DNF_DAEMON, session.as_str(), DNF_DAEMON_RPM)?;
+
+    let mut options: HashMap<&str, Value> = HashMap::new();
+    options.insert("package_attrs", Value::from(vec!["name", "epoch", "sourcerpm"]));
+    options.insert("scope", Value::from("installed"));
+
+    let packages: Vec<HashMap<String, OwnedValue>> = rpm.call("list", &(options,))?;
+
+    let mut lines: Vec<String> = Vec::new();
+    for package in packages {
+        if let (Some(name), Some(epoch), Some(sourcerpm)) =
+            (package.get("name"), package.get("epoch"), package.get("sourcerpm"))
+        {
+            let name = String::try_from(name.clone())?;
+            let epoch = String::try_from(epoch.clone())?;
+            let sourcerpm = String::try_from(sourcerpm.clone())?;
+
+            // gpg-pubkey pseudo-packages have no source package
+            if !sourcerpm.is_empty() {
+                lines.push(format!("{} {} {}", name, epoch, sourcerpm));
+            }
+        }
+    }
+
+    lines.sort();
+    lines.dedup();
+
+    Ok(lines)
+}
+
+fn query_dnf(cacheonly: bool) -> Result<Vec<String>, DnfError> {
+    let mut args = vec!["repoquery"];
+
+    if cacheonly {
+        args.push("--cacheonly");
+    }
+
+    args.extend(&["--installed", "--queryformat", DNF_QUERYFORMAT]);
+
+    let installed = dnf(&args)?;
+
+    // gpg-pubkey pseudo-packages have no source package
+    Ok(installed
+        .lines()
+        .filter(|line| line.ends_with(".src.rpm"))
+        .map(String::from)
+        .collect())
+}
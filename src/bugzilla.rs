@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+
+use crate::http;
+use crate::retry::{retry, RetryPolicy};
+
+const BUGZILLA_URL: &str = "https://bugzilla.redhat.com";
+
+#[derive(Debug, Deserialize)]
+struct BugList {
+    bugs: Vec<BugInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BugInfo {
+    id: u32,
+    #[serde(default)]
+    creator: String,
+    #[serde(default)]
+    cc: Vec<String>,
+}
+
+pub struct Bugzilla {
+    client: reqwest::Client,
+    email: String,
+    api_key: Option<String>,
+    policy: RetryPolicy,
+}
+
+impl Bugzilla {
+    pub fn new(email: &str, api_key: Option<&str>, policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Bugzilla {
+            client,
+            email: email.to_owned(),
+            api_key: api_key.map(String::from),
+            policy,
+        })
+    }
+
+    async fn fetch(&self, ids: &str) -> Result<BugList, reqwest::Error> {
+        let mut request = self
+            .client
+            .get(format!("{}/rest/bug", BUGZILLA_URL))
+            .query(&[("id", ids), ("include_fields", "id,creator,cc")]);
+
+        // e-mail addresses are only visible to logged-in users
+        if let Some(api_key) = &self.api_key {
+            request = request.query(&[("api_key", api_key)]);
+        }
+
+        request.send().await?.error_for_status()?.json().await
+    }
+
+    // returns the IDs of bugs that were reported by the user or that the user is CC'd on
+    pub async fn my_bugs(&self, ids: &[u32]) -> Result<HashSet<u32>, String> {
+        if ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        let ids = ids.join(",");
+
+        let bugs = retry(self.policy, "Querying bugzilla for bugs", || self.fetch(&ids)).await?;
+
+        Ok(bugs
+            .bugs
+            .into_iter()
+            .filter(|bug| bug.creator == self.email || bug.cc.contains(&self.email))
+            .map(|bug| bug.id)
+            .collect())
+    }
+}
@@ -0,0 +1,1037 @@
+use std::collections::HashSet;
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+use bodhi::data::FedoraRelease;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::feedback::Feedback;
+use crate::nvr::InstallState;
+
+// updates are fetched again if they were modified within this margin before the last run
+const CLOCK_SKEW_MARGIN: i64 = 10 * 60;
+
+// cached responses for queries that weren't repeated for this long are removed
+const MAX_RESPONSE_AGE: i64 = 7 * 24 * 60 * 60;
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS runs (
+    release TEXT PRIMARY KEY,
+    last_full INTEGER NOT NULL,
+    last_run INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS updates (
+    alias TEXT PRIMARY KEY,
+    release TEXT NOT NULL,
+    date_modified TEXT,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS builds (
+    alias TEXT NOT NULL,
+    nvr TEXT NOT NULL,
+    PRIMARY KEY (alias, nvr)
+);
+CREATE TABLE IF NOT EXISTS evaluations (
+    alias TEXT PRIMARY KEY,
+    date_modified TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    commented INTEGER NOT NULL,
+    states TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS installs (
+    alias TEXT PRIMARY KEY,
+    first_seen INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS install_boots (
+    alias TEXT NOT NULL,
+    boot_id TEXT NOT NULL,
+    PRIMARY KEY (alias, boot_id)
+);
+CREATE TABLE IF NOT EXISTS install_builds (
+    alias TEXT PRIMARY KEY,
+    builds TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS alerts (
+    alias TEXT NOT NULL,
+    key TEXT NOT NULL,
+    PRIMARY KEY (alias, key)
+);
+CREATE TABLE IF NOT EXISTS responses (
+    url TEXT PRIMARY KEY,
+    etag TEXT,
+    last_modified TEXT,
+    body TEXT NOT NULL,
+    fetched INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS dismissed (
+    alias TEXT PRIMARY KEY,
+    until INTEGER
+);
+CREATE TABLE IF NOT EXISTS deferred (
+    summary TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    body TEXT NOT NULL,
+    urgent INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS notifications (
+    sent INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS queued_feedback (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alias TEXT NOT NULL,
+    text TEXT NOT NULL,
+    karma INTEGER NOT NULL,
+    testcases TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS build_checks (
+    release TEXT PRIMARY KEY,
+    last_check INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS comments (
+    alias TEXT PRIMARY KEY,
+    date_modified TEXT NOT NULL,
+    json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS installed (
+    fingerprint TEXT PRIMARY KEY,
+    packages TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    time INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    alias TEXT NOT NULL,
+    karma INTEGER
+);
+"#;
+
+#[derive(Debug)]
+pub struct UpdateCache {
+    // timestamp of the last full refresh
+    pub last_full: i64,
+    // timestamp of the last successful (incremental or full) run
+    pub last_run: i64,
+    // raw JSON of updates that were in testing at the time of the last run
+    pub updates: Vec<Value>,
+}
+
+impl UpdateCache {
+    pub fn new(updates: Vec<Value>) -> Self {
+        let now = Utc::now().timestamp();
+
+        UpdateCache {
+            last_full: now,
+            last_run: now,
+            updates,
+        }
+    }
+
+    pub fn is_stale(&self, max_age: i64) -> bool {
+        Utc::now().timestamp() - self.last_full > max_age
+    }
+
+    // date filter for bodhi queries
+    pub fn since(&self) -> String {
+        let since = DateTime::from_timestamp(self.last_run - CLOCK_SKEW_MARGIN, 0).unwrap_or_default();
+        since.format("%Y-%m-%dT%H:%M:%S").to_string()
+    }
+}
+
+// cached result of matching an update against installed packages
+#[derive(Debug)]
+pub struct CachedEvaluation {
+    pub commented: bool,
+    pub states: Vec<InstallState>,
+}
+
+// HTTP response with the validators for sending conditional requests
+#[derive(Debug)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+// notification that was held back during quiet hours or while notifications were muted
+#[derive(Debug)]
+pub struct DeferredNotification {
+    pub summary: String,
+    pub url: String,
+    pub body: String,
+    pub urgent: bool,
+}
+
+// feedback that couldn't be submitted because bodhi was not reachable
+#[derive(Debug)]
+pub struct QueuedFeedback {
+    pub id: i64,
+    pub alias: String,
+    pub feedback: Feedback,
+}
+
+// when builds of an update were first seen installed, and on how many boots
+#[derive(Clone, Copy, Debug)]
+pub struct InstallRecord {
+    pub first_seen: i64,
+    pub boots: u32,
+}
+
+// something that happened to an update ("notified", "installed", or "feedback")
+#[derive(Debug, Serialize)]
+pub struct HistoryEntry {
+    pub time: i64,
+    pub event: String,
+    pub alias: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub karma: Option<i32>,
+}
+
+// numbers of distinct updates per month, by the month when it first happened
+#[derive(Debug, Serialize)]
+pub struct MonthlyStats {
+    pub month: String,
+    pub notified: u32,
+    pub tested: u32,
+    pub commented: u32,
+    pub karma: u32,
+}
+
+impl InstallRecord {
+    pub fn days(&self) -> i64 {
+        (Utc::now().timestamp() - self.first_seen) / (24 * 60 * 60)
+    }
+}
+
+pub fn boot_id() -> Option<String> {
+    std::fs::read_to_string("/proc/sys/kernel/random/boot_id")
+        .ok()
+        .map(|id| id.trim().to_owned())
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|path| path.join("fedora-update-notifier"))
+}
+
+pub struct Cache {
+    connection: Connection,
+    // nothing is written to a read-only cache
+    read_only: bool,
+}
+
+impl Cache {
+    pub fn open() -> Result<Self, String> {
+        let dir = match cache_dir() {
+            Some(dir) => dir,
+            None => {
+                return Err(String::from("Unable to determine $XDG_CACHE_HOME."));
+            },
+        };
+
+        if let Err(error) = create_dir_all(&dir) {
+            return Err(format!("Unable to create {}: {}", dir.display(), error));
+        }
+
+        let connection = match Connection::open(dir.join("cache.sqlite")) {
+            Ok(connection) => connection,
+            Err(error) => {
+                return Err(format!("Unable to open cache: {}", error));
+            },
+        };
+
+        if let Err(error) = connection.execute_batch(SCHEMA) {
+            return Err(format!("Unable to initialize cache: {}", error));
+        }
+
+        Ok(Cache {
+            connection,
+            read_only: false,
+        })
+    }
+
+    // opens the cache without creating or changing anything on disk
+    pub fn open_read_only() -> Result<Self, String> {
+        let path = match cache_dir() {
+            Some(dir) => dir.join("cache.sqlite"),
+            None => {
+                return Err(String::from("Unable to determine $XDG_CACHE_HOME."));
+            },
+        };
+
+        let connection = if path.exists() {
+            Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        } else {
+            // there is nothing cached yet, which is the same as an empty cache
+            Connection::open_in_memory().and_then(|connection| {
+                connection.execute_batch(SCHEMA)?;
+                Ok(connection)
+            })
+        };
+
+        match connection {
+            Ok(connection) => Ok(Cache {
+                connection,
+                read_only: true,
+            }),
+            Err(error) => Err(format!("Unable to open cache: {}", error)),
+        }
+    }
+
+    pub fn load_updates(&self, release: &FedoraRelease) -> Result<Option<UpdateCache>, String> {
+        let release = release.to_string();
+
+        let run: Option<(i64, i64)> = self
+            .connection
+            .query_row(
+                "SELECT last_full, last_run FROM runs WHERE release = ?1",
+                params![release],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let (last_full, last_run) = match run {
+            Some(run) => run,
+            None => return Ok(None),
+        };
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT json FROM updates WHERE release = ?1")
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let rows = statement
+            .query_map(params![release], |row| row.get::<_, String>(0))
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let mut updates: Vec<Value> = Vec::new();
+        for row in rows {
+            let json = row.map_err(|error| format!("Unable to read cache: {}", error))?;
+            match serde_json::from_str(&json) {
+                Ok(update) => updates.push(update),
+                Err(error) => {
+                    return Err(format!("Unable to parse cached update: {}", error));
+                },
+            }
+        }
+
+        Ok(Some(UpdateCache {
+            last_full,
+            last_run,
+            updates,
+        }))
+    }
+
+    // aliases of all cached updates (of any release), i.e. for shell completions
+    pub fn aliases(&self) -> Result<Vec<String>, String> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT alias FROM updates ORDER BY alias")
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let rows = statement
+            .query_map(params![], |row| row.get::<_, String>(0))
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        rows.collect::<Result<Vec<String>, _>>()
+            .map_err(|error| format!("Unable to read cache: {}", error))
+    }
+
+    pub fn store_updates(&mut self, release: &FedoraRelease, cache: &UpdateCache) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let release = release.to_string();
+
+        let write = |connection: &mut Connection| -> Result<(), rusqlite::Error> {
+            let transaction = connection.transaction()?;
+
+            transaction.execute(
+                "DELETE FROM builds WHERE alias IN (SELECT alias FROM updates WHERE release = ?1)",
+                params![release],
+            )?;
+            transaction.execute("DELETE FROM updates WHERE release = ?1", params![release])?;
+
+            for update in &cache.updates {
+                let alias = update["alias"].as_str().unwrap_or_default();
+
+                transaction.execute(
+                    "INSERT OR REPLACE INTO updates (alias, release, date_modified, json) VALUES (?1, ?2, ?3, ?4)",
+                    params![alias, release, update["date_modified"].as_str(), update.to_string()],
+                )?;
+
+                if let Some(builds) = update["builds"].as_array() {
+                    for build in builds {
+                        if let Some(nvr) = build["nvr"].as_str() {
+                            transaction.execute(
+                                "INSERT OR REPLACE INTO builds (alias, nvr) VALUES (?1, ?2)",
+                                params![alias, nvr],
+                            )?;
+                        }
+                    }
+                }
+            }
+
+            transaction.execute(
+                "INSERT OR REPLACE INTO runs (release, last_full, last_run) VALUES (?1, ?2, ?3)",
+                params![release, cache.last_full, cache.last_run],
+            )?;
+
+            transaction.commit()
+        };
+
+        write(&mut self.connection).map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    pub fn evaluation(&self, alias: &str, date_modified: &str, fingerprint: &str) -> Option<CachedEvaluation> {
+        let row: Option<(bool, String)> = self
+            .connection
+            .query_row(
+                "SELECT commented, states FROM evaluations WHERE alias = ?1 AND date_modified = ?2 AND fingerprint = ?3",
+                params![alias, date_modified, fingerprint],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()
+            .flatten();
+
+        let (commented, states) = row?;
+
+        Some(CachedEvaluation {
+            commented,
+            states: serde_json::from_str(&states).ok()?,
+        })
+    }
+
+    // records an alert, and returns whether it is new
+    pub fn is_new_alert(&self, alias: &str, key: &str) -> Result<bool, String> {
+        match self.connection.query_row(
+            "SELECT COUNT(*) = 0 FROM alerts WHERE alias = ?1 AND key = ?2",
+            params![alias, key],
+            |row| row.get(0),
+        ) {
+            Ok(new) => Ok(new),
+            Err(error) => Err(format!("Unable to read cache: {}", error)),
+        }
+    }
+
+    // alerts are only recorded after they were sent, so they are sent again if that failed
+    pub fn record_alert(&self, alias: &str, key: &str) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT OR IGNORE INTO alerts (alias, key) VALUES (?1, ?2)",
+            params![alias, key],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // holds back a notification, newer notifications with the same summary replace older ones
+    pub fn defer_notification(&self, notification: &DeferredNotification) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT OR REPLACE INTO deferred (summary, url, body, urgent) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                notification.summary,
+                notification.url,
+                notification.body,
+                notification.urgent
+            ],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    pub fn has_deferred(&self) -> Result<bool, String> {
+        match self
+            .connection
+            .query_row("SELECT COUNT(*) FROM deferred", params![], |row| row.get::<_, i64>(0))
+        {
+            Ok(count) => Ok(count > 0),
+            Err(error) => Err(format!("Unable to read cache: {}", error)),
+        }
+    }
+
+    // returns and removes all notifications that were held back
+    pub fn take_deferred(&self) -> Result<Vec<DeferredNotification>, String> {
+        let read = || -> Result<Vec<DeferredNotification>, rusqlite::Error> {
+            let mut statement = self
+                .connection
+                .prepare("SELECT summary, url, body, urgent FROM deferred")?;
+            let rows = statement.query_map(params![], |row| {
+                Ok(DeferredNotification {
+                    summary: row.get(0)?,
+                    url: row.get(1)?,
+                    body: row.get(2)?,
+                    urgent: row.get(3)?,
+                })
+            })?;
+            rows.collect()
+        };
+
+        let notifications = read().map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        if !self.read_only {
+            if let Err(error) = self.connection.execute("DELETE FROM deferred", params![]) {
+                return Err(format!("Unable to write cache: {}", error));
+            }
+        }
+
+        Ok(notifications)
+    }
+
+    // remembers when a notification was sent, for limiting the number of notifications per day
+    pub fn record_notification(&self, sent: i64) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let record = || -> Result<(), rusqlite::Error> {
+            self.connection
+                .execute("INSERT INTO notifications (sent) VALUES (?1)", params![sent])?;
+            // only the last day is needed
+            self.connection.execute(
+                "DELETE FROM notifications WHERE sent < ?1",
+                params![sent - 24 * 60 * 60],
+            )?;
+            Ok(())
+        };
+
+        record().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    pub fn notifications_since(&self, since: i64) -> Result<u32, String> {
+        match self.connection.query_row(
+            "SELECT COUNT(*) FROM notifications WHERE sent >= ?1",
+            params![since],
+            |row| row.get(0),
+        ) {
+            Ok(count) => Ok(count),
+            Err(error) => Err(format!("Unable to read cache: {}", error)),
+        }
+    }
+
+    // only the first notification about an update is kept
+    pub fn record_notified(&self, alias: &str) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT INTO history (time, event, alias) SELECT ?1, 'notified', ?2 \
+             WHERE NOT EXISTS (SELECT 1 FROM history WHERE event = 'notified' AND alias = ?2)",
+            params![Utc::now().timestamp(), alias],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    pub fn record_feedback(&self, alias: &str, karma: i32) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT INTO history (time, event, alias, karma) VALUES (?1, 'feedback', ?2, ?3)",
+            params![Utc::now().timestamp(), alias, karma],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // recorded events and installs, newest first
+    pub fn history(&self, limit: u32) -> Result<Vec<HistoryEntry>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT time, event, alias, karma FROM history \
+                 UNION ALL SELECT first_seen, 'installed', alias, NULL FROM installs \
+                 ORDER BY time DESC LIMIT ?1",
+            )
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let rows = statement
+            .query_map(params![limit], |row| {
+                Ok(HistoryEntry {
+                    time: row.get(0)?,
+                    event: row.get(1)?,
+                    alias: row.get(2)?,
+                    karma: row.get(3)?,
+                })
+            })
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        rows.collect::<Result<Vec<HistoryEntry>, _>>()
+            .map_err(|error| format!("Unable to read cache: {}", error))
+    }
+
+    pub fn monthly_stats(&self) -> Result<Vec<MonthlyStats>, String> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT month, SUM(event = 'notified'), SUM(event = 'installed'), SUM(event = 'feedback'), SUM(karma) \
+                 FROM ( \
+                     SELECT strftime('%Y-%m', MIN(time), 'unixepoch', 'localtime') AS month, event, \
+                         MAX(IFNULL(karma, 0) != 0) AS karma \
+                     FROM ( \
+                         SELECT time, event, alias, karma FROM history \
+                         UNION ALL SELECT first_seen, 'installed', alias, NULL FROM installs \
+                     ) \
+                     GROUP BY event, alias \
+                 ) \
+                 GROUP BY month ORDER BY month",
+            )
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        let rows = statement
+            .query_map(params![], |row| {
+                Ok(MonthlyStats {
+                    month: row.get(0)?,
+                    notified: row.get(1)?,
+                    tested: row.get(2)?,
+                    commented: row.get(3)?,
+                    karma: row.get(4)?,
+                })
+            })
+            .map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        rows.collect::<Result<Vec<MonthlyStats>, _>>()
+            .map_err(|error| format!("Unable to read cache: {}", error))
+    }
+
+    pub fn queue_feedback(&self, alias: &str, feedback: &Feedback) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let testcases = match serde_json::to_string(&feedback.testcases) {
+            Ok(testcases) => testcases,
+            Err(error) => return Err(format!("Unable to write cache: {}", error)),
+        };
+
+        match self.connection.execute(
+            "INSERT INTO queued_feedback (alias, text, karma, testcases) VALUES (?1, ?2, ?3, ?4)",
+            params![alias, feedback.text, feedback.karma, testcases],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // queued feedback, in the order it was given
+    pub fn queued_feedback(&self) -> Result<Vec<QueuedFeedback>, String> {
+        let read = || -> Result<Vec<(i64, String, String, i32, String)>, rusqlite::Error> {
+            let mut statement = self
+                .connection
+                .prepare("SELECT id, alias, text, karma, testcases FROM queued_feedback ORDER BY id")?;
+            let rows = statement.query_map(params![], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+            })?;
+            rows.collect()
+        };
+
+        let rows = read().map_err(|error| format!("Unable to read cache: {}", error))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, alias, text, karma, testcases)| QueuedFeedback {
+                id,
+                alias,
+                feedback: Feedback {
+                    text,
+                    karma,
+                    testcases: serde_json::from_str(&testcases).unwrap_or_default(),
+                },
+            })
+            .collect())
+    }
+
+    pub fn remove_queued_feedback(&self, id: i64) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self
+            .connection
+            .execute("DELETE FROM queued_feedback WHERE id = ?1", params![id])
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // hides an update until the given timestamp, or forever
+    pub fn dismiss(&self, alias: &str, until: Option<i64>) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT OR REPLACE INTO dismissed (alias, until) VALUES (?1, ?2)",
+            params![alias, until],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // updates that were ignored, or snoozed until a later time
+    pub fn is_dismissed(&self, alias: &str) -> bool {
+        self.connection
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM dismissed WHERE alias = ?1 AND (until IS NULL OR until > ?2)",
+                params![alias, Utc::now().timestamp()],
+                |row| row.get(0),
+            )
+            .unwrap_or(false)
+    }
+
+    // timestamp of the last check for completed koji builds
+    pub fn last_build_check(&self, release: &FedoraRelease) -> Option<i64> {
+        self.connection
+            .query_row(
+                "SELECT last_check FROM build_checks WHERE release = ?1",
+                params![release.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    pub fn store_build_check(&self, release: &FedoraRelease, last_check: i64) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "INSERT OR REPLACE INTO build_checks (release, last_check) VALUES (?1, ?2)",
+            params![release.to_string(), last_check],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // installed packages from the last run, if the rpm database still has the same fingerprint
+    pub fn installed_packages(&self, fingerprint: &str) -> Option<String> {
+        self.connection
+            .query_row(
+                "SELECT packages FROM installed WHERE fingerprint = ?1",
+                params![fingerprint],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    pub fn store_installed_packages(&self, fingerprint: &str, packages: &str) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        // only the latest state of the rpm database is useful
+        let write = || -> Result<(), rusqlite::Error> {
+            self.connection.execute("DELETE FROM installed", params![])?;
+            self.connection.execute(
+                "INSERT INTO installed (fingerprint, packages) VALUES (?1, ?2)",
+                params![fingerprint, packages],
+            )?;
+            Ok(())
+        };
+
+        write().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    // complete list of comments of an update, if it wasn't modified since
+    pub fn comments(&self, alias: &str, date_modified: &str) -> Option<Vec<Value>> {
+        let json: String = self
+            .connection
+            .query_row(
+                "SELECT json FROM comments WHERE alias = ?1 AND date_modified = ?2",
+                params![alias, date_modified],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()
+            .flatten()?;
+
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn store_comments(&self, alias: &str, date_modified: &str, comments: &[Value]) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let json = match serde_json::to_string(comments) {
+            Ok(json) => json,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        match self.connection.execute(
+            "INSERT OR REPLACE INTO comments (alias, date_modified, json) VALUES (?1, ?2, ?3)",
+            params![alias, date_modified, json],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // ETag and Last-Modified of a cached response, the body is only needed if it didn't change
+    pub fn response_validators(&self, url: &str) -> Option<(Option<String>, Option<String>)> {
+        self.connection
+            .query_row(
+                "SELECT etag, last_modified FROM responses WHERE url = ?1",
+                params![url],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    pub fn response_body(&self, url: &str) -> Option<String> {
+        self.connection
+            .query_row("SELECT body FROM responses WHERE url = ?1", params![url], |row| {
+                row.get(0)
+            })
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    pub fn store_response(&self, url: &str, response: &CachedResponse) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let now = Utc::now().timestamp();
+
+        let write = || -> Result<(), rusqlite::Error> {
+            self.connection.execute(
+                "INSERT OR REPLACE INTO responses (url, etag, last_modified, body, fetched) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![url, response.etag, response.last_modified, response.body, now],
+            )?;
+            // queries with date filters are never repeated
+            self.connection.execute(
+                "DELETE FROM responses WHERE fetched < ?1",
+                params![now - MAX_RESPONSE_AGE],
+            )?;
+            Ok(())
+        };
+
+        write().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    // marks a cached response as still valid
+    pub fn touch_response(&self, url: &str) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self.connection.execute(
+            "UPDATE responses SET fetched = ?2 WHERE url = ?1",
+            params![url, Utc::now().timestamp()],
+        ) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // drops a cached response whose body can't be used anymore
+    pub fn remove_response(&self, url: &str) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        match self
+            .connection
+            .execute("DELETE FROM responses WHERE url = ?1", params![url])
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to write cache: {}", error)),
+        }
+    }
+
+    // the installed builds the record belongs to
+    fn install_builds(&self, alias: &str) -> Result<Option<String>, rusqlite::Error> {
+        self.connection
+            .query_row(
+                "SELECT builds FROM install_builds WHERE alias = ?1",
+                params![alias],
+                |row| row.get(0),
+            )
+            .optional()
+    }
+
+    // records are started over if other builds are installed now (i.e. after a respin)
+    pub fn record_install(&self, alias: &str, builds: &str, boot_id: Option<&str>) -> Result<InstallRecord, String> {
+        if self.read_only {
+            return self.install(alias, builds, boot_id);
+        }
+
+        let record = || -> Result<InstallRecord, rusqlite::Error> {
+            if self.install_builds(alias)?.as_deref() != Some(builds) {
+                self.connection
+                    .execute("DELETE FROM installs WHERE alias = ?1", params![alias])?;
+                self.connection
+                    .execute("DELETE FROM install_boots WHERE alias = ?1", params![alias])?;
+                self.connection.execute(
+                    "INSERT OR REPLACE INTO install_builds (alias, builds) VALUES (?1, ?2)",
+                    params![alias, builds],
+                )?;
+            }
+
+            self.connection.execute(
+                "INSERT OR IGNORE INTO installs (alias, first_seen) VALUES (?1, ?2)",
+                params![alias, Utc::now().timestamp()],
+            )?;
+
+            if let Some(boot_id) = boot_id {
+                self.connection.execute(
+                    "INSERT OR IGNORE INTO install_boots (alias, boot_id) VALUES (?1, ?2)",
+                    params![alias, boot_id],
+                )?;
+            }
+
+            let first_seen = self.connection.query_row(
+                "SELECT first_seen FROM installs WHERE alias = ?1",
+                params![alias],
+                |row| row.get(0),
+            )?;
+            let boots = self.connection.query_row(
+                "SELECT COUNT(*) FROM install_boots WHERE alias = ?1",
+                params![alias],
+                |row| row.get(0),
+            )?;
+
+            Ok(InstallRecord { first_seen, boots })
+        };
+
+        record().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    // what record_install would return, without recording anything
+    fn install(&self, alias: &str, builds: &str, boot_id: Option<&str>) -> Result<InstallRecord, String> {
+        let query = || -> Result<InstallRecord, rusqlite::Error> {
+            if self.install_builds(alias)?.as_deref() != Some(builds) {
+                return Ok(InstallRecord {
+                    first_seen: Utc::now().timestamp(),
+                    boots: u32::from(boot_id.is_some()),
+                });
+            }
+
+            let first_seen: Option<i64> = self
+                .connection
+                .query_row(
+                    "SELECT first_seen FROM installs WHERE alias = ?1",
+                    params![alias],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let boots: u32 = self.connection.query_row(
+                "SELECT COUNT(*) FROM install_boots WHERE alias = ?1",
+                params![alias],
+                |row| row.get(0),
+            )?;
+            // the current boot would be counted if it wasn't already
+            let new_boot: u32 = match boot_id {
+                Some(boot_id) => self.connection.query_row(
+                    "SELECT COUNT(*) = 0 FROM install_boots WHERE alias = ?1 AND boot_id = ?2",
+                    params![alias, boot_id],
+                    |row| row.get(0),
+                )?,
+                None => 0,
+            };
+
+            Ok(InstallRecord {
+                first_seen: first_seen.unwrap_or_else(|| Utc::now().timestamp()),
+                boots: boots + new_boot,
+            })
+        };
+
+        query().map_err(|error| format!("Unable to read cache: {}", error))
+    }
+
+    // evaluations are stored as (alias, date_modified, evaluation)
+    pub fn store_evaluations(
+        &self,
+        fingerprint: &str,
+        evaluations: &[(String, String, CachedEvaluation)],
+    ) -> Result<(), String> {
+        if self.read_only || evaluations.is_empty() {
+            return Ok(());
+        }
+
+        let mut rows = Vec::new();
+        for (alias, date_modified, evaluation) in evaluations {
+            match serde_json::to_string(&evaluation.states) {
+                Ok(states) => rows.push((alias, date_modified, evaluation.commented, states)),
+                Err(error) => {
+                    return Err(format!("{}", error));
+                },
+            }
+        }
+
+        // one transaction instead of one per update
+        let write = || -> Result<(), rusqlite::Error> {
+            let transaction = self.connection.unchecked_transaction()?;
+
+            for (alias, date_modified, commented, states) in &rows {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO evaluations (alias, date_modified, fingerprint, commented, states) \
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![alias, date_modified, fingerprint, commented, states],
+                )?;
+            }
+
+            transaction.commit()
+        };
+
+        write().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+
+    // removes evaluations and comments of updates that are not in testing anymore
+    pub fn prune_updates(&self, aliases: &HashSet<&str>) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        let prune = || -> Result<(), rusqlite::Error> {
+            let transaction = self.connection.unchecked_transaction()?;
+
+            for table in &["evaluations", "comments"] {
+                let stored: Vec<String> = {
+                    let mut statement = transaction.prepare(&format!("SELECT alias FROM {}", table))?;
+                    let rows = statement.query_map(params![], |row| row.get(0))?;
+                    rows.collect::<Result<_, _>>()?
+                };
+
+                for alias in stored.iter().filter(|alias| !aliases.contains(alias.as_str())) {
+                    transaction.execute(&format!("DELETE FROM {} WHERE alias = ?1", table), params![alias])?;
+                }
+            }
+
+            transaction.commit()
+        };
+
+        prune().map_err(|error| format!("Unable to write cache: {}", error))
+    }
+}
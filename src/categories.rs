@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use tracing::debug;
+
+use crate::dnf;
+use crate::output::UpdateSummary;
+
+// comps groups whose packages are desktop applications or command-line tools,
+// everything else is usually only pulled in as a dependency
+const DESKTOP_GROUPS: &[&str] = &[
+    "workstation-product",
+    "gnome-desktop",
+    "kde-desktop",
+    "xfce-desktop",
+    "multimedia",
+    "firefox",
+    "libreoffice",
+    "fonts",
+];
+const TOOLS_GROUPS: &[&str] = &[
+    "core",
+    "standard",
+    "admin-tools",
+    "system-tools",
+    "development-tools",
+    "c-development",
+];
+
+// categories are sorted by how important it is to test updates in them
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    Security,
+    Desktop,
+    Tools,
+    Libraries,
+}
+
+impl Category {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::Security => "category-security",
+            Category::Desktop => "category-desktop",
+            Category::Tools => "category-tools",
+            Category::Libraries => "category-libraries",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Categories {
+    desktop: HashSet<String>,
+    tools: HashSet<String>,
+}
+
+fn group_packages(groups: &[&str]) -> HashSet<String> {
+    let mut packages = HashSet::new();
+
+    for group in groups {
+        // not all groups are available on all releases (or on EPEL)
+        match dnf::group_packages(group) {
+            Ok(names) => packages.extend(names),
+            Err(error) => debug!("Skipped comps group {}: {}", group, error),
+        }
+    }
+
+    packages
+}
+
+impl Categories {
+    pub fn load() -> Self {
+        Categories {
+            desktop: group_packages(DESKTOP_GROUPS),
+            tools: group_packages(TOOLS_GROUPS),
+        }
+    }
+
+    pub fn category(&self, update: &UpdateSummary) -> Category {
+        if update.update_type == "security" {
+            Category::Security
+        } else if update.packages.iter().any(|package| self.desktop.contains(package)) {
+            Category::Desktop
+        } else if update.packages.iter().any(|package| self.tools.contains(package)) {
+            Category::Tools
+        } else {
+            Category::Libraries
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::cmp::Ordering;
+
+use bodhi::data::FedoraRelease;
+use serde::{Deserialize, Serialize};
+
+use crate::http;
+use crate::nvr::NEVR;
+use crate::retry::{retry, RetryPolicy};
+use crate::version::evrcmp;
+
+const MDAPI_URL: &str = "https://mdapi.fedoraproject.org";
+
+#[derive(Debug, Deserialize)]
+struct ChangelogList {
+    changelogs: Vec<ChangelogEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourcePackage {
+    #[serde(rename = "co-packages", default)]
+    co_packages: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ChangelogEntry {
+    pub author: String,
+    #[serde(rename(deserialize = "changelog"))]
+    pub text: String,
+    pub date: i64,
+}
+
+impl ChangelogEntry {
+    // changelog entries usually end with the version, i.e. "Jane Doe <jane@example.com> - 1:2.3-4"
+    fn evr(&self) -> Option<(u32, &str, &str)> {
+        let (_, evr) = self.author.rsplit_once(" - ")?;
+        let evr = evr.trim();
+
+        let (e, vr) = match evr.split_once(':') {
+            Some((e, vr)) => (e.parse().ok()?, vr),
+            None => (0, evr),
+        };
+
+        match vr.rsplit_once('-') {
+            Some((v, r)) => Some((e, v, r)),
+            None => Some((e, vr, "")),
+        }
+    }
+
+    fn is_newer_than(&self, installed: &NEVR) -> bool {
+        match self.evr() {
+            // changelog entries without release only compare against the installed version
+            Some((e, v, "")) => evrcmp((e, v, ""), (installed.e, &installed.v, "")) == Ordering::Greater,
+            Some(evr) => evrcmp(evr, installed.evr()) == Ordering::Greater,
+            None => true,
+        }
+    }
+}
+
+pub struct Mdapi {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl Mdapi {
+    pub fn new(policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Mdapi { client, policy })
+    }
+
+    async fn fetch<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Option<T>, reqwest::Error> {
+        let response = self.client.get(url).send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    async fn changelog(&self, branch: &str, package: &str) -> Result<Option<ChangelogList>, String> {
+        let url = format!("{}/{}/changelog/{}", MDAPI_URL, branch, package);
+        retry(self.policy, &format!("Querying changelog of {}", package), || {
+            self.fetch(&url)
+        })
+        .await
+    }
+
+    // returns the changelog entries of a package in updates-testing that are newer than the installed version
+    pub async fn newer_entries(
+        &self,
+        release: &FedoraRelease,
+        installed: &NEVR,
+    ) -> Result<Vec<ChangelogEntry>, String> {
+        let branch = match release.to_string().strip_prefix("EPEL-") {
+            Some(major) => format!("epel{}-testing", major),
+            None => format!("{}-updates-testing", release.to_string().to_lowercase()),
+        };
+
+        let mut list = self.changelog(&branch, &installed.n).await?;
+
+        // changelogs are looked up by binary package, which doesn't always share the source package name
+        if list.is_none() {
+            let url = format!("{}/{}/srcpkg/{}", MDAPI_URL, branch, &installed.n);
+            let source: Option<SourcePackage> =
+                retry(self.policy, &format!("Querying packages of {}", &installed.n), || {
+                    self.fetch(&url)
+                })
+                .await?;
+
+            if let Some(package) = source.and_then(|source| source.co_packages.into_iter().next()) {
+                list = self.changelog(&branch, &package).await?;
+            }
+        }
+
+        let entries = match list {
+            Some(list) => list.changelogs,
+            None => {
+                return Err(format!("No changelog found for {} in {}", &installed.n, branch));
+            },
+        };
+
+        // entries are sorted from newest to oldest
+        Ok(entries
+            .into_iter()
+            .take_while(|entry| entry.is_newer_than(installed))
+            .collect())
+    }
+}
@@ -0,0 +1,52 @@
+use std::io::Write;
+
+use clap::{App, Shell};
+
+const BIN_NAME: &str = "fedora-update-notifier";
+
+// update aliases are completed with the updates from the local cache for subcommands that take them,
+// which isn't supported by the generated zsh completions
+const BASH_ALIASES: &str = r#"
+_fedora-update-notifier_aliases() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+
+    case "${prev}" in
+        show|install|stage|comment|open)
+            COMPREPLY=($(compgen -W "$(fedora-update-notifier complete-aliases 2>/dev/null)" -- "${cur}"))
+            return 0
+            ;;
+    esac
+
+    _fedora-update-notifier "$@"
+}
+
+complete -F _fedora-update-notifier_aliases -o bashdefault -o default fedora-update-notifier
+"#;
+
+const FISH_ALIASES: &str = r#"
+complete -c fedora-update-notifier -n "__fish_seen_subcommand_from show install stage comment open" \
+    -f -a "(fedora-update-notifier complete-aliases 2>/dev/null)"
+"#;
+
+pub fn print(mut app: App, shell: &str) -> Result<(), String> {
+    let (shell, aliases) = match shell {
+        "bash" => (Shell::Bash, Some(BASH_ALIASES)),
+        "zsh" => (Shell::Zsh, None),
+        "fish" => (Shell::Fish, Some(FISH_ALIASES)),
+        _ => {
+            return Err(format!("Unsupported shell: {}", shell));
+        },
+    };
+
+    let mut stdout = std::io::stdout();
+    app.gen_completions_to(BIN_NAME, shell, &mut stdout);
+
+    if let Some(aliases) = aliases {
+        if let Err(error) = stdout.write_all(aliases.as_bytes()) {
+            return Err(format!("Failed to print completions: {}", error));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::convert::TryFrom;
+use std::io::ErrorKind;
+use std::process::Command;
+
+use bodhi::data::FedoraRelease;
+use serde_json::Value;
+
+use crate::nvr::{dedup, NEVR};
+use crate::subprocess;
+
+const REGISTRY: &str = "registry.fedoraproject.org/";
+
+// container updates are published for separate releases, i.e. F39C
+pub fn container_release(release: &FedoraRelease) -> Option<FedoraRelease> {
+    FedoraRelease::try_from(format!("{}C", release).as_str()).ok()
+}
+
+// container updates have aliases like "FEDORA-CONTAINER-2023-0123456789"
+pub fn is_container(alias: &str) -> bool {
+    alias.starts_with("FEDORA-CONTAINER-")
+}
+
+fn podman(args: &[&str]) -> Result<Option<Value>, String> {
+    match subprocess::output(Command::new("podman").args(args)) {
+        Ok(output) if output.status.success() => match serde_json::from_slice(&output.stdout) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => Err(format!("Failed to parse podman output: {}", error)),
+        },
+        Ok(_) => Err(format!("Failed to run podman {}", args.join(" "))),
+        // systems without podman don't have any images
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(format!("Failed to run podman: {}", error)),
+    }
+}
+
+// images built in koji carry the component name, version, and release of their build as labels
+fn image_build(image: &Value) -> Option<NEVR> {
+    let labels = match &image["Labels"] {
+        Value::Object(_) => &image["Labels"],
+        _ => &image["Config"]["Labels"],
+    };
+
+    Some(NEVR {
+        n: labels["com.redhat.component"].as_str()?.to_owned(),
+        e: 0,
+        v: labels["version"].as_str()?.to_owned(),
+        r: labels["release"].as_str()?.to_owned(),
+    })
+}
+
+// returns the builds of locally pulled images from the fedora registry
+pub fn pulled_images() -> Result<Vec<NEVR>, String> {
+    let images = match podman(&["images", "--format", "json"])? {
+        Some(Value::Array(images)) => images,
+        _ => return Ok(Vec::new()),
+    };
+
+    let ids: Vec<&str> = images
+        .iter()
+        .filter(|image| match image["Names"].as_array() {
+            Some(names) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| name.starts_with(REGISTRY)),
+            None => false,
+        })
+        .filter_map(|image| image["Id"].as_str())
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = vec!["image", "inspect", "--format", "json"];
+    args.extend(ids);
+
+    match podman(&args)? {
+        Some(Value::Array(images)) => Ok(dedup(images.iter().filter_map(image_build).collect())),
+        _ => Ok(Vec::new()),
+    }
+}
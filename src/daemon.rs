@@ -0,0 +1,243 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+use zbus::{dbus_interface, ConnectionBuilder, SignalContext};
+
+use crate::cache::Cache;
+use crate::journal;
+use crate::quiet;
+use crate::tray;
+
+pub const BUS_NAME: &str = "org.ironthree.FedoraUpdateNotifier";
+const OBJECT_PATH: &str = "/org/ironthree/FedoraUpdateNotifier";
+
+// hours between checks, same as the default for the systemd timer
+pub const DEFAULT_INTERVAL: u64 = 6;
+
+// how often to check if the user came back to a locked or idle session
+const AWAY_POLL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct PendingUpdate {
+    alias: String,
+    url: String,
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+// the parts of the JSON output that are exposed over DBus
+#[derive(Debug, Deserialize)]
+struct CheckOutput {
+    #[serde(default)]
+    feedback_url: Option<String>,
+    #[serde(default)]
+    feedback: Vec<PendingUpdate>,
+}
+
+type Pending = Arc<Mutex<Vec<PendingUpdate>>>;
+
+// the list is only replaced as a whole, so it is still consistent if another thread panicked
+fn lock(pending: &Pending) -> MutexGuard<'_, Vec<PendingUpdate>> {
+    pending.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Service {
+    pending: Pending,
+    refresh: Arc<Notify>,
+}
+
+#[dbus_interface(name = "org.ironthree.FedoraUpdateNotifier")]
+impl Service {
+    // starts a check right away, UpdatesChanged is emitted when it finished (if anything changed)
+    fn refresh(&self) {
+        self.refresh.notify_one();
+    }
+
+    // alias, bodhi URL, and packages of installed updates that are ready for feedback
+    fn get_pending_feedback(&self) -> Vec<(String, String, Vec<String>)> {
+        lock(&self.pending)
+            .iter()
+            .map(|update| (update.alias.clone(), update.url.clone(), update.packages.clone()))
+            .collect()
+    }
+
+    // hides an update for some time, like snoozing it in the terminal interface
+    async fn snooze(
+        &self,
+        alias: String,
+        secs: u64,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> zbus::fdo::Result<()> {
+        let until = chrono::Utc::now().timestamp() + secs as i64;
+        if let Err(error) = Cache::open().and_then(|cache| cache.dismiss(&alias, Some(until))) {
+            return Err(zbus::fdo::Error::Failed(error));
+        }
+
+        let changed = {
+            let mut pending = lock(&self.pending);
+            let before = pending.len();
+            pending.retain(|update| update.alias != alias);
+            pending.len() != before
+        };
+
+        if changed {
+            Self::updates_changed(&ctxt).await?;
+        }
+        Ok(())
+    }
+
+    #[dbus_interface(signal)]
+    async fn updates_changed(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+}
+
+// checks run in a separate process with the settings from the configuration file, like runs started by the timer,
+// so they also send notifications (unless they are paused); None means that bodhi was not reachable
+fn check(profile: Option<&str>, notify: bool) -> Result<Option<CheckOutput>, String> {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(error) => {
+            return Err(format!("Failed to determine path of the executable: {}", error));
+        },
+    };
+
+    let mut command = Command::new(exe);
+    command.args(["--output", "json", "--wait"]);
+    if let Some(profile) = profile {
+        command.args(["--profile", profile]);
+    }
+    if !notify {
+        command.arg("--no-notify");
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(error) => {
+            return Err(format!("Failed to check for updates: {}", error));
+        },
+    };
+
+    match output.status.code() {
+        Some(
+            journal::EXIT_NOTHING
+            | journal::EXIT_FEEDBACK
+            | journal::EXIT_PENDING
+            | journal::EXIT_FEEDBACK_PENDING
+            // the results are complete, only the notifications were printed instead
+            | journal::EXIT_UNSENT,
+        ) => {},
+        Some(journal::EXIT_OFFLINE) => return Ok(None),
+        _ => {
+            return Err(format!(
+                "Failed to check for updates: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        },
+    }
+
+    match serde_json::from_slice::<CheckOutput>(&output.stdout) {
+        Ok(result) => Ok(Some(result)),
+        // i.e. if the check was postponed on a metered connection
+        Err(error) => Err(format!("Unexpected output of check: {}", error)),
+    }
+}
+
+async fn connect(service: Service) -> zbus::Result<zbus::Connection> {
+    ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, service)?
+        .build()
+        .await
+}
+
+// checks for updates periodically (or when asked to over DBus), and keeps the results for desktop integrations
+pub async fn run(interval: Duration, profile: Option<&str>, with_tray: bool) -> Result<(), String> {
+    let pending: Pending = Arc::new(Mutex::new(Vec::new()));
+    let refresh = Arc::new(Notify::new());
+    let paused = Arc::new(AtomicBool::new(false));
+
+    let service = Service {
+        pending: pending.clone(),
+        refresh: refresh.clone(),
+    };
+
+    let connection = match connect(service).await {
+        Ok(connection) => connection,
+        Err(error) => {
+            return Err(format!("Failed to register {} on the session bus: {}", BUS_NAME, error));
+        },
+    };
+
+    let tray = if with_tray {
+        Some(tray::spawn(refresh.clone(), paused.clone()))
+    } else {
+        None
+    };
+
+    journal::ready();
+
+    let mut away = false;
+
+    loop {
+        journal::status("Checking for updates");
+
+        let notify = !paused.load(Ordering::Relaxed);
+        let profile = profile.map(str::to_owned);
+
+        match tokio::task::spawn_blocking(move || check(profile.as_deref(), notify)).await {
+            Ok(Ok(Some(result))) => {
+                if let Some(tray) = &tray {
+                    tray.update(result.feedback.len(), result.feedback_url);
+                }
+
+                let feedback = result.feedback;
+                let changed = {
+                    let mut pending = lock(&pending);
+                    let changed = *pending != feedback;
+                    *pending = feedback;
+                    changed
+                };
+
+                if changed {
+                    let signal = match SignalContext::new(&connection, OBJECT_PATH) {
+                        Ok(ctxt) => Service::updates_changed(&ctxt).await,
+                        Err(error) => Err(error),
+                    };
+                    if let Err(error) = signal {
+                        warn!("Failed to emit UpdatesChanged signal: {}", error);
+                    }
+                }
+            },
+            Ok(Ok(None)) => debug!("Bodhi is not reachable, keeping the results of the last check"),
+            Ok(Err(error)) => warn!("{}", error),
+            Err(error) => warn!("Failed to check for updates: {}", error),
+        }
+
+        journal::status("Waiting for the next check");
+
+        let next = tokio::time::Instant::now() + interval;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(next) => break,
+                _ = refresh.notified() => {
+                    debug!("Check requested");
+                    break;
+                },
+                _ = tokio::time::sleep(AWAY_POLL) => {
+                    let was_away = away;
+                    away = tokio::task::spawn_blocking(quiet::is_away).await.unwrap_or(false);
+
+                    // notifications that were held back while the user was away are delivered by the next check
+                    if was_away && !away && Cache::open().and_then(|cache| cache.has_deferred()).unwrap_or(false) {
+                        debug!("Session is active again, delivering notifications that were held back");
+                        break;
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::backend;
+use crate::subprocess;
+
+const DNF_CONF: &str = "/etc/dnf/dnf.conf";
+const VERSIONLOCK_LIST: &str = "/etc/dnf/plugins/versionlock.list";
+const VERSIONLOCK_TOML: &str = "/etc/dnf/versionlock.toml";
+
+pub const TESTING_REPO: &str = "updates-testing";
+pub const EPEL_TESTING_REPO: &str = "epel-testing";
+pub const MODULAR_TESTING_REPO: &str = "updates-testing-modular";
+pub const EPEL_MODULAR_TESTING_REPO: &str = "epel-testing-modular";
+
+#[derive(Debug, Deserialize)]
+struct VersionLock {
+    #[serde(default)]
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockedPackage {
+    name: String,
+}
+
+// seconds to wait for other transactions (i.e. PackageKit installing updates) to release the lock
+pub const DEFAULT_LOCK_WAIT: u64 = 600;
+
+const LOCK_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_LOCK_BACKOFF: Duration = Duration::from_secs(60);
+
+// dnf4, dnf5, and rpm report the lock differently
+const LOCK_MESSAGES: &[&str] = &[
+    "Failed to obtain the transaction lock",
+    "Failed to obtain the lock",
+    "Waiting for process with pid",
+    "can't create transaction lock",
+];
+
+static LOCK_WAIT: OnceLock<Duration> = OnceLock::new();
+
+pub fn set_lock_wait(lock_wait: Duration) {
+    let _ = LOCK_WAIT.set(lock_wait);
+}
+
+fn lock_wait() -> Duration {
+    LOCK_WAIT
+        .get()
+        .copied()
+        .unwrap_or(Duration::from_secs(DEFAULT_LOCK_WAIT))
+}
+
+#[derive(Debug)]
+pub enum DnfError {
+    // another transaction holds the lock of the rpm database
+    Locked,
+    Failed(String),
+}
+
+impl Display for DnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnfError::Locked => write!(f, "The rpm database is locked by another transaction."),
+            DnfError::Failed(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<DnfError> for String {
+    fn from(error: DnfError) -> Self {
+        error.to_string()
+    }
+}
+
+fn run_dnf(args: &[&str]) -> Result<String, DnfError> {
+    debug!("Running dnf {}", args.join(" "));
+
+    let output = match subprocess::output(Command::new("dnf").arg("--quiet").args(args)) {
+        Ok(output) => output,
+        Err(error) => {
+            return Err(DnfError::Failed(format!("{}", error)));
+        },
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if LOCK_MESSAGES.iter().any(|message| stderr.contains(message)) {
+            return Err(DnfError::Locked);
+        }
+
+        return match stderr.trim() {
+            "" => Err(DnfError::Failed(String::from("Failed to query dnf."))),
+            stderr => Err(DnfError::Failed(format!("Failed to query dnf: {}", stderr))),
+        };
+    }
+
+    match String::from_utf8(output.stdout) {
+        Ok(result) => Ok(result),
+        Err(error) => Err(DnfError::Failed(format!("{}", error))),
+    }
+}
+
+// runs dnf with the given arguments and returns its output, waiting while the rpm database is locked
+pub fn dnf(args: &[&str]) -> Result<String, DnfError> {
+    let deadline = Instant::now() + lock_wait();
+    let mut backoff = LOCK_BACKOFF;
+
+    loop {
+        match run_dnf(args) {
+            Err(DnfError::Locked) if Instant::now() + backoff < deadline => {
+                info!(
+                    "The rpm database is locked by another transaction, trying again in {}s",
+                    backoff.as_secs()
+                );
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_LOCK_BACKOFF);
+            },
+            result => return result,
+        }
+    }
+}
+
+fn lines(output: &str) -> Vec<String> {
+    let mut lines: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+
+    lines.sort();
+    lines.dedup();
+    lines
+}
+
+// parses package names from "dnf group info" output, for both dnf4 and dnf5:
+//
+//  Mandatory Packages:          Mandatory packages   : foo
+//    foo                                             : bar
+//    bar
+fn parse_group_info(output: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+
+    for line in output.lines() {
+        let value = match line.split_once(':') {
+            Some((key, value)) => {
+                let key = key.trim();
+                if !key.is_empty() {
+                    in_packages = key.to_lowercase().ends_with("packages");
+                }
+                value
+            },
+            None => line,
+        };
+
+        if !in_packages {
+            continue;
+        }
+
+        // dnf4 marks packages with their installation status
+        let value = value.trim().trim_start_matches(&['=', '+', '-'][..]);
+
+        if let Some(package) = value.split_whitespace().next() {
+            packages.push(package.to_owned());
+        }
+    }
+
+    packages
+}
+
+// returns the names of the source packages that the given binary packages were built from
+pub fn source_names(packages: &[String]) -> Result<Vec<String>, String> {
+    if packages.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = vec!["repoquery", "--queryformat", "%{source_name}\n"];
+    args.extend(packages.iter().map(String::as_str));
+
+    Ok(lines(&dnf(&args)?))
+}
+
+// returns the names of the source packages of all packages in a comps group
+pub fn group_packages(group: &str) -> Result<Vec<String>, String> {
+    let packages = parse_group_info(&dnf(&["group", "info", group])?);
+
+    if packages.is_empty() {
+        return Err(format!("No packages found in group: {}", group));
+    }
+
+    source_names(&packages)
+}
+
+// returns the names of the source packages of all packages that were explicitly installed by the user
+pub fn userinstalled() -> Result<Vec<String>, String> {
+    Ok(lines(&dnf(&[
+        "repoquery",
+        "--installed",
+        "--userinstalled",
+        "--queryformat",
+        "%{source_name}\n",
+    ])?))
+}
+
+fn is_root() -> bool {
+    // /proc/self is owned by the effective user ID of the current process
+    std::fs::metadata("/proc/self").map(|m| m.uid() == 0).unwrap_or(false)
+}
+
+fn is_graphical() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_some()
+}
+
+// EPEL and modular updates are published to their own testing repositories,
+// their aliases look like "FEDORA-EPEL-2023-0123456789" or "FEDORA-MODULAR-2023-0123456789"
+pub fn testing_repo(alias: &str) -> &'static str {
+    if alias.starts_with("FEDORA-EPEL-MODULAR-") {
+        EPEL_MODULAR_TESTING_REPO
+    } else if alias.starts_with("FEDORA-EPEL-") {
+        EPEL_TESTING_REPO
+    } else if alias.starts_with("FEDORA-MODULAR-") {
+        MODULAR_TESTING_REPO
+    } else {
+        TESTING_REPO
+    }
+}
+
+pub fn is_modular(alias: &str) -> bool {
+    alias.contains("-MODULAR-")
+}
+
+// installs an update from updates-testing, asking for elevated privileges if necessary
+pub fn upgrade_advisory(alias: &str, assume_yes: bool) -> Result<(), String> {
+    let mut command = if is_root() {
+        Command::new("dnf")
+    } else if is_graphical() && Path::new("/usr/bin/pkexec").exists() {
+        let mut command = Command::new("pkexec");
+        command.arg("dnf");
+        command
+    } else {
+        let mut command = Command::new("sudo");
+        command.arg("dnf");
+        command
+    };
+
+    command
+        .arg("upgrade")
+        .arg(format!("--enablerepo={}", testing_repo(alias)))
+        .arg(format!("--advisory={}", alias));
+
+    if assume_yes {
+        command.arg("--assumeyes");
+    }
+
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(format!("Failed to install update: {}", alias)),
+        Err(error) => Err(format!("Failed to run dnf: {}", error)),
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RestartHints {
+    // the kernel or core libraries were updated since the last boot
+    pub reboot: bool,
+    // services that are still running with files from older versions
+    pub services: Vec<String>,
+}
+
+// "dnf needs-restarting --reboothint" exits with status 1 if a reboot is needed
+pub fn needs_restarting() -> Result<RestartHints, String> {
+    let reboot = match subprocess::output(Command::new("dnf").args(&["--quiet", "needs-restarting", "--reboothint"])) {
+        Ok(output) => output.status.code() == Some(1),
+        Err(error) => {
+            return Err(format!("Failed to run dnf: {}", error));
+        },
+    };
+
+    let services = lines(&dnf(&["needs-restarting", "--services"])?);
+
+    Ok(RestartHints { reboot, services })
+}
+
+// returns PackageKit package IDs ("name;version;arch;repo") of the packages that an update would upgrade
+pub fn advisory_package_ids(alias: &str) -> Result<Vec<String>, String> {
+    let repo = testing_repo(alias);
+    let output = dnf(&[
+        "repoquery",
+        &format!("--enablerepo={}", repo),
+        &format!("--advisory={}", alias),
+        "--upgrades",
+        "--queryformat",
+        "%{name} %{epoch} %{version} %{release} %{arch}\n",
+    ])?;
+
+    let mut ids = Vec::new();
+    for line in lines(&output) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        let (name, epoch, version, release, arch) = match fields.as_slice() {
+            [name, epoch, version, release, arch] => (name, epoch, version, release, arch),
+            _ => {
+                return Err(format!("Unexpected output from dnf: {}", line));
+            },
+        };
+
+        let evr = match *epoch {
+            "0" | "(none)" => format!("{}-{}", version, release),
+            _ => format!("{}:{}-{}", epoch, version, release),
+        };
+
+        ids.push(format!("{};{};{};{}", name, evr, arch, repo));
+    }
+
+    if ids.is_empty() {
+        return Err(format!("No packages to upgrade for update: {}", alias));
+    }
+
+    Ok(ids)
+}
+
+// returns the repositories that the packages built from each installed source package were installed from
+pub fn installed_repos() -> Result<HashMap<String, HashSet<String>>, String> {
+    let output = dnf(&[
+        "repoquery",
+        "--installed",
+        "--queryformat",
+        "%{source_name} %{from_repo}\n",
+    ])?;
+
+    let mut repos: HashMap<String, HashSet<String>> = HashMap::new();
+    for line in lines(&output) {
+        if let Some((source_name, repo)) = line.split_once(' ') {
+            repos
+                .entry(source_name.to_owned())
+                .or_default()
+                .insert(repo.trim().to_owned());
+        }
+    }
+
+    Ok(repos)
+}
+
+// matches package names against dnf-style globs with "*" and "?" wildcards
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first().copied(), name.first().copied()) {
+            (None, None) => true,
+            (Some('*'), _) => matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..])),
+            (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+            (Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+// "exclude=" and "excludepkgs=" in the [main] section of dnf.conf
+fn excludes(conf: &str) -> Vec<String> {
+    let mut excludes = Vec::new();
+    let mut main = false;
+
+    for line in conf.lines().map(str::trim) {
+        if line.starts_with('[') {
+            main = line == "[main]";
+            continue;
+        }
+
+        if !main {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            if matches!(key.trim(), "exclude" | "excludepkgs") {
+                excludes.extend(
+                    value
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|pattern| !pattern.is_empty())
+                        .map(String::from),
+                );
+            }
+        }
+    }
+
+    excludes
+}
+
+// strips version, release, and architecture from "name-[epoch:]version-release.arch" patterns
+fn locked_name(pattern: &str) -> &str {
+    let mut parts = pattern.rsplitn(3, '-');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(_), Some(name)) => name,
+        _ => pattern,
+    }
+}
+
+// returns name patterns of binary packages that are excluded in dnf.conf or locked with the versionlock plugin
+fn locked_packages() -> Vec<String> {
+    let mut patterns = Vec::new();
+
+    if let Ok(conf) = std::fs::read_to_string(DNF_CONF) {
+        patterns.extend(excludes(&conf));
+    }
+
+    // dnf4 versionlock plugin, excluded packages are prefixed with "!"
+    if let Ok(list) = std::fs::read_to_string(VERSIONLOCK_LIST) {
+        patterns.extend(
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| locked_name(line.trim_start_matches('!')).to_owned()),
+        );
+    }
+
+    // dnf5 versionlock
+    if let Ok(toml) = std::fs::read_to_string(VERSIONLOCK_TOML) {
+        match toml::from_str::<VersionLock>(&toml) {
+            Ok(lock) => patterns.extend(lock.packages.into_iter().map(|package| package.name)),
+            Err(error) => warn!("Failed to parse {}: {}", VERSIONLOCK_TOML, error),
+        }
+    }
+
+    patterns
+}
+
+// names of source packages that dnf will not upgrade, because some of their installed binary packages are locked
+pub fn locked_sources() -> Result<HashSet<String>, String> {
+    let patterns = locked_packages();
+    if patterns.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    backend::matching_sources(&patterns)
+}
@@ -0,0 +1,187 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::http;
+use crate::notifier::has_notification_daemon;
+use crate::source::bodhi_url;
+use crate::systemd;
+
+// dnf metadata older than this probably means that "dnf makecache" isn't running
+const MAX_CACHE_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+const DNF_CACHE_DIRS: &[&str] = &["/var/cache/dnf", "/var/cache/libdnf5"];
+
+enum Check {
+    Ok(String),
+    Warning(String, &'static str),
+    Failure(String, &'static str),
+}
+
+impl Check {
+    fn print(&self) {
+        match self {
+            Check::Ok(message) => println!("[ OK ] {}", message),
+            Check::Warning(message, hint) => {
+                println!("[WARN] {}", message);
+                println!("       {}", hint);
+            },
+            Check::Failure(message, hint) => {
+                println!("[FAIL] {}", message);
+                println!("       {}", hint);
+            },
+        }
+    }
+}
+
+fn check_config(profile: Option<&str>) -> Vec<Check> {
+    match crate::get_config(profile) {
+        Ok(config) => vec![
+            Check::Ok(String::from("Configuration file is valid")),
+            Check::Ok(format!("FAS username: {}", config.fas.username)),
+        ],
+        Err(error) => vec![Check::Failure(
+            error,
+            "Create it with \"fedora-update-notifier init\", or see \"fedora-update-notifier --help\" for the format.",
+        )],
+    }
+}
+
+fn check_command(program: &str, hint: &'static str) -> Check {
+    match Command::new(program).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            let version = version.lines().next().unwrap_or_default().trim();
+            Check::Ok(format!("{} is available ({})", program, version))
+        },
+        Ok(_) => Check::Failure(format!("{} --version failed", program), hint),
+        Err(error) => Check::Failure(format!("{} is not available: {}", program, error), hint),
+    }
+}
+
+fn check_release() -> Check {
+    match crate::get_release() {
+        Ok(release) => Check::Ok(format!("Fedora release: {}", release)),
+        Err(error) => Check::Failure(
+            format!("Unable to determine Fedora release: {}", error),
+            "Only Fedora and EPEL-enabled Enterprise Linux systems are supported.",
+        ),
+    }
+}
+
+// returns the modification time of the newest repository metadata in a directory tree
+fn newest_metadata(dir: &Path) -> Option<SystemTime> {
+    let mut newest: Option<SystemTime> = None;
+
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+
+        let modified = if path.is_dir() {
+            newest_metadata(&path)
+        } else if path.extension().map(|ext| ext == "solv").unwrap_or(false) {
+            entry.metadata().and_then(|metadata| metadata.modified()).ok()
+        } else {
+            None
+        };
+
+        newest = newest.max(modified);
+    }
+
+    newest
+}
+
+fn check_dnf_cache() -> Check {
+    let newest = DNF_CACHE_DIRS
+        .iter()
+        .filter_map(|dir| newest_metadata(Path::new(dir)))
+        .max();
+
+    let age = match newest.map(|newest| newest.elapsed()) {
+        Some(Ok(age)) => age,
+        _ => {
+            return Check::Warning(
+                String::from("No dnf metadata cache found"),
+                "Run \"sudo dnf makecache\" so installed packages can be queried without network access.",
+            );
+        },
+    };
+
+    let days = age.as_secs() / (24 * 60 * 60);
+    if age > MAX_CACHE_AGE {
+        Check::Warning(
+            format!("dnf metadata cache is {} days old", days),
+            "Run \"sudo dnf makecache\", or enable \"dnf-makecache.timer\".",
+        )
+    } else {
+        Check::Ok(format!("dnf metadata cache is {} days old", days))
+    }
+}
+
+async fn check_bodhi() -> Check {
+    let client = match http::client_builder().timeout(Duration::from_secs(30)).build() {
+        Ok(client) => client,
+        Err(error) => {
+            return Check::Failure(format!("{}", error), "This is a bug.");
+        },
+    };
+
+    let hint = "Check your network connection and proxy settings, or https://status.fedoraproject.org.";
+
+    match client
+        .get(format!("{}/releases/", bodhi_url()))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(_) => Check::Ok(format!("bodhi is reachable ({})", bodhi_url())),
+        Err(error) => Check::Failure(format!("bodhi is not reachable: {}", error), hint),
+    }
+}
+
+fn check_notifications() -> Check {
+    if has_notification_daemon() {
+        Check::Ok(String::from("Desktop notification daemon is available"))
+    } else {
+        Check::Warning(
+            String::from("No desktop notification daemon is available"),
+            "Notifications will be printed to the terminal, or use a different notifier (i.e. \"matrix\" or \"webhook\").",
+        )
+    }
+}
+
+fn check_service() -> Option<Check> {
+    match systemd::is_service_stale()? {
+        true => Some(Check::Warning(
+            String::from("The systemd user service was installed by an older version"),
+            "Run \"fedora-update-notifier install-service\" again (with the same --on-calendar schedule) to update it.",
+        )),
+        false => Some(Check::Ok(String::from("The systemd user service is up-to-date"))),
+    }
+}
+
+// checks everything that needs to work for fedora-update-notifier, and prints actionable results
+pub async fn doctor(profile: Option<&str>) -> Result<(), String> {
+    let mut checks = check_config(profile);
+    checks.push(check_command("rpm", "Install rpm, only Fedora systems are supported."));
+    checks.push(check_release());
+    checks.push(check_command("dnf", "Install dnf to query installed packages."));
+    checks.push(check_dnf_cache());
+    checks.push(check_bodhi().await);
+    checks.push(check_notifications());
+    checks.extend(check_service());
+
+    for check in &checks {
+        check.print();
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|check| matches!(check, Check::Failure(..)))
+        .count();
+
+    match failures {
+        0 => Ok(()),
+        1 => Err(String::from("1 check failed.")),
+        _ => Err(format!("{} checks failed.", failures)),
+    }
+}
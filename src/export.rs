@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use crate::output::{Summary, UpdateSummary};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Html,
+    // this is actually an Atom feed, but "rss" is what most people look for
+    Rss,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format {
+            "html" => Ok(ExportFormat::Html),
+            "rss" => Ok(ExportFormat::Rss),
+            _ => Err(format!("Unknown report format: {}", format)),
+        }
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn html_update(html: &mut String, update: &UpdateSummary) {
+    html.push_str(&format!(
+        "<li><a href=\"{}\">{}</a> ({})\n",
+        escape(&update.url),
+        escape(&update.alias),
+        escape(&update.karma_info())
+    ));
+
+    html.push_str("<ul>\n");
+    for build in &update.builds {
+        html.push_str(&format!("<li>{}</li>\n", escape(build)));
+    }
+    for bug in &update.bugs {
+        html.push_str(&format!(
+            "<li><a href=\"https://bugzilla.redhat.com/show_bug.cgi?id={}\">rhbz#{}</a>: {}</li>\n",
+            bug.id,
+            bug.id,
+            escape(&bug.title)
+        ));
+    }
+    html.push_str("</ul>\n");
+
+    if !update.notes.trim().is_empty() {
+        html.push_str(&format!("<pre>{}</pre>\n", escape(update.notes.trim())));
+    }
+
+    html.push_str("</li>\n");
+}
+
+fn html_section(html: &mut String, title: &str, url: Option<&str>, updates: &[UpdateSummary]) {
+    if updates.is_empty() {
+        return;
+    }
+
+    html.push_str(&format!("<h2>{}</h2>\n", escape(title)));
+    if let Some(url) = url {
+        html.push_str(&format!("<p><a href=\"{}\">Open in bodhi</a></p>\n", escape(url)));
+    }
+
+    html.push_str("<ul>\n");
+    for update in updates {
+        html_update(html, update);
+    }
+    html.push_str("</ul>\n");
+}
+
+// static page with the same contents as the terminal output
+fn html(summary: &Summary) -> String {
+    let title = format!("Updates in testing for {}", summary.release);
+    let generated = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC");
+
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&title)));
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!(
+        "<h1>{}</h1>\n<p>Generated on {}.</p>\n",
+        escape(&title),
+        generated
+    ));
+
+    if summary.feedback.is_empty() && summary.pending.is_empty() {
+        html.push_str("<p>There are no updates that need feedback.</p>\n");
+    }
+
+    html_section(
+        &mut html,
+        "Installed updates that are ready for feedback",
+        summary.feedback_url.as_deref(),
+        &summary.feedback,
+    );
+    html_section(
+        &mut html,
+        "Updates for interesting packages",
+        summary.interesting_url.as_deref(),
+        &summary.pending,
+    );
+
+    for target in &summary.targets {
+        html_section(
+            &mut html,
+            &format!("Installed updates{} that are ready for feedback", target.location()),
+            target.summary.feedback_url.as_deref(),
+            &target.summary.feedback,
+        );
+        html_section(
+            &mut html,
+            &format!("Updates for interesting packages{}", target.location()),
+            target.summary.interesting_url.as_deref(),
+            &target.summary.pending,
+        );
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// updates on other systems get their own entries, the system name is appended to the ID to keep it unique
+fn atom_entry(atom: &mut String, update: &UpdateSummary, kind: &str, system: Option<&str>, updated: &str) {
+    let id = match system {
+        Some(system) => format!("{}#{}", update.url, system),
+        None => update.url.clone(),
+    };
+
+    atom.push_str("<entry>\n");
+    atom.push_str(&format!("<id>{}</id>\n", escape(&id)));
+    atom.push_str(&format!(
+        "<title>{} ({}): {}</title>\n",
+        escape(&update.alias),
+        escape(kind),
+        escape(&update.packages.join(", "))
+    ));
+    atom.push_str(&format!("<link href=\"{}\"/>\n", escape(&update.url)));
+    atom.push_str(&format!("<updated>{}</updated>\n", updated));
+    atom.push_str(&format!(
+        "<author><name>{}</name></author>\n",
+        escape(&update.submitter)
+    ));
+    atom.push_str(&format!(
+        "<summary>{}</summary>\n",
+        escape(&format!("{}\n\n{}", update.karma_info(), update.notes.trim()))
+    ));
+    atom.push_str("</entry>\n");
+}
+
+// Atom feed with one entry for every update, which are identified by their bodhi URL
+fn atom(summary: &Summary) -> String {
+    let updated = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let mut atom = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    atom.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    atom.push_str(&format!(
+        "<id>urn:fedora-update-notifier:{}</id>\n",
+        escape(&summary.release.to_lowercase())
+    ));
+    atom.push_str(&format!(
+        "<title>Updates in testing for {}</title>\n",
+        escape(&summary.release)
+    ));
+    atom.push_str(&format!("<updated>{}</updated>\n", updated));
+    atom.push_str("<generator>fedora-update-notifier</generator>\n");
+
+    for update in &summary.feedback {
+        atom_entry(&mut atom, update, "ready for feedback", None, &updated);
+    }
+    for update in &summary.pending {
+        atom_entry(&mut atom, update, "interesting", None, &updated);
+    }
+    for target in &summary.targets {
+        let system = Some(target.source.name.as_str());
+
+        let kind = format!("ready for feedback{}", target.location());
+        for update in &target.summary.feedback {
+            atom_entry(&mut atom, update, &kind, system, &updated);
+        }
+        let kind = format!("interesting{}", target.location());
+        for update in &target.summary.pending {
+            atom_entry(&mut atom, update, &kind, system, &updated);
+        }
+    }
+
+    atom.push_str("</feed>\n");
+    atom
+}
+
+pub fn write(summary: &Summary, format: ExportFormat, path: &Path) -> Result<(), String> {
+    let contents = match format {
+        ExportFormat::Html => html(summary),
+        ExportFormat::Rss => atom(summary),
+    };
+
+    match std::fs::write(path, contents) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(format!("Failed to write report to {}: {}", path.display(), error)),
+    }
+}
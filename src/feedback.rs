@@ -0,0 +1,294 @@
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::auth::Authenticator;
+use crate::cache::Cache;
+use crate::http;
+use crate::notifier::{Message, Notifier};
+use crate::report::Evaluation;
+use crate::retry::{retry_after, retry_errors, send, RetryPolicy, Transient, DEFAULT_TIMEOUT};
+use crate::source::bodhi_url;
+
+const DEFAULT_TEMPLATE: &str =
+    "Installed and running for {days} days on Fedora {release} ({desktop}, {arch}, kernel {kernel}) with no issues.";
+
+#[derive(Debug, Serialize)]
+struct TestcaseFeedback {
+    testcase_name: String,
+    karma: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct NewComment<'a> {
+    update: &'a str,
+    text: &'a str,
+    karma: i32,
+    testcase_feedback: Vec<TestcaseFeedback>,
+    csrf_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CsrfToken {
+    csrf_token: String,
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    // bodhi or the network are down, submitting the same feedback again later can work
+    Unavailable(String),
+    Rejected(String),
+}
+
+impl Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitError::Unavailable(error) | SubmitError::Rejected(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<SubmitError> for String {
+    fn from(error: SubmitError) -> Self {
+        error.to_string()
+    }
+}
+
+impl SubmitError {
+    fn from_request(context: &str, error: impl Transient + Display) -> Self {
+        let message = format!("{}: {}", context, error);
+        if error.is_transient() {
+            SubmitError::Unavailable(message)
+        } else {
+            SubmitError::Rejected(message)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Feedback {
+    pub text: String,
+    pub karma: i32,
+    pub testcases: Vec<(String, i32)>,
+}
+
+fn default_template() -> String {
+    String::from(DEFAULT_TEMPLATE)
+}
+
+// automatic comments for updates that were installed for the whole soak period
+#[derive(Clone, Debug, Deserialize)]
+pub struct AutoCommentConfig {
+    #[serde(default = "default_template")]
+    pub template: String,
+    #[serde(default)]
+    pub karma: i32,
+}
+
+// system information that can be included in automatic comments
+#[derive(Debug)]
+pub struct SystemInfo {
+    release: String,
+    desktop: String,
+    arch: &'static str,
+    kernel: String,
+}
+
+impl SystemInfo {
+    pub fn collect(release: &str) -> Self {
+        SystemInfo {
+            release: release.trim_start_matches('F').to_owned(),
+            desktop: std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_else(|_| String::from("no desktop")),
+            arch: std::env::consts::ARCH,
+            kernel: std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                .map(|kernel| kernel.trim().to_owned())
+                .unwrap_or_else(|_| String::from("unknown")),
+        }
+    }
+}
+
+impl AutoCommentConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.karma {
+            -1..=1 => Ok(()),
+            _ => Err(format!(
+                "Invalid karma for automatic comments (expected 1, 0, or -1): {}",
+                self.karma
+            )),
+        }
+    }
+
+    // replaces {alias}, {days}, {boots}, {release}, {desktop}, {arch}, and {kernel} in the template
+    pub fn feedback(&self, system: &SystemInfo, evaluation: &Evaluation) -> Feedback {
+        let (days, boots) = match &evaluation.install {
+            Some(install) => (install.days(), install.boots),
+            None => (0, 0),
+        };
+
+        let text = self
+            .template
+            .replace("{alias}", &evaluation.update.alias)
+            .replace("{days}", &days.to_string())
+            .replace("{boots}", &boots.to_string())
+            .replace("{release}", &system.release)
+            .replace("{desktop}", &system.desktop)
+            .replace("{arch}", system.arch)
+            .replace("{kernel}", &system.kernel);
+
+        Feedback {
+            text,
+            karma: self.karma,
+            testcases: Vec::new(),
+        }
+    }
+}
+
+pub fn parse_karma(karma: &str) -> Result<i32, String> {
+    match karma {
+        "+1" | "1" => Ok(1),
+        "0" => Ok(0),
+        "-1" => Ok(-1),
+        _ => Err(format!("Invalid karma (expected +1, 0, or -1): {}", karma)),
+    }
+}
+
+// parses test case feedback in the "NAME=KARMA" format
+pub fn parse_testcase(testcase: &str) -> Result<(String, i32), String> {
+    let mut name_karma: Vec<&str> = testcase.rsplitn(2, '=').collect();
+
+    if name_karma.len() != 2 {
+        return Err(format!(
+            "Invalid test case feedback (expected NAME=KARMA): {}",
+            testcase
+        ));
+    }
+
+    // rsplitn returns things in reverse order
+    let karma = name_karma.remove(0);
+    let name = name_karma.remove(0);
+
+    Ok((name.to_owned(), parse_karma(karma)?))
+}
+
+pub fn client() -> Result<reqwest::Client, String> {
+    match http::client_builder()
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
+        .build()
+    {
+        Ok(client) => Ok(client),
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+// all authenticated requests that change data in bodhi need a CSRF token
+pub async fn csrf_token(client: &reqwest::Client, access_token: &str) -> Result<String, SubmitError> {
+    let request = || send(client.get(format!("{}/csrf", bodhi_url())).bearer_auth(access_token));
+
+    match retry_errors(RetryPolicy::default(), "Getting a CSRF token", request).await {
+        Ok(response) => match response.json::<CsrfToken>().await {
+            Ok(csrf) => Ok(csrf.csrf_token),
+            Err(error) => Err(SubmitError::from_request("Failed to get CSRF token from bodhi", error)),
+        },
+        Err(error) => Err(SubmitError::from_request("Failed to get CSRF token from bodhi", error)),
+    }
+}
+
+pub async fn submit(access_token: &str, alias: &str, feedback: &Feedback) -> Result<(), SubmitError> {
+    let client = client().map_err(SubmitError::Rejected)?;
+    let csrf_token = csrf_token(&client, access_token).await?;
+
+    let comment = NewComment {
+        update: alias,
+        text: &feedback.text,
+        karma: feedback.karma,
+        testcase_feedback: feedback
+            .testcases
+            .iter()
+            .map(|(name, karma)| TestcaseFeedback {
+                testcase_name: name.clone(),
+                karma: *karma,
+            })
+            .collect(),
+        csrf_token,
+    };
+
+    match client
+        .post(format!("{}/comments/", bodhi_url()))
+        .bearer_auth(access_token)
+        .json(&comment)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => {
+            if let Err(error) = Cache::open().and_then(|cache| cache.record_feedback(alias, feedback.karma)) {
+                warn!("Failed to record feedback for {}: {}", alias, error);
+            }
+            Ok(())
+        },
+        // queued feedback is submitted again by the next run
+        Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            Err(SubmitError::Unavailable(format!(
+                "Failed to submit feedback: bodhi asked us to slow down (retry after {}s)",
+                retry_after(&response).as_secs()
+            )))
+        },
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let message = format!("Failed to submit feedback ({}): {}", status, body);
+            if status.is_server_error() {
+                Err(SubmitError::Unavailable(message))
+            } else {
+                Err(SubmitError::Rejected(message))
+            }
+        },
+        Err(error) => Err(SubmitError::from_request("Failed to submit feedback", error)),
+    }
+}
+
+// stores feedback that couldn't be submitted, it is submitted again by the next run
+pub fn queue(alias: &str, feedback: &Feedback) -> Result<(), String> {
+    Cache::open()?.queue_feedback(alias, feedback)
+}
+
+// submits queued feedback, and sends a notification once it was submitted (or rejected by bodhi)
+pub async fn submit_queued(username: &str, notifier: &dyn Notifier) -> Result<(), String> {
+    let queued = Cache::open()?.queued_feedback()?;
+    if queued.is_empty() {
+        return Ok(());
+    }
+
+    let access_token = Authenticator::new(username)?.access_token().await?;
+
+    for queued in queued {
+        let alias = &queued.alias;
+        let (summary, urgent) = match submit(&access_token, alias, &queued.feedback).await {
+            Ok(()) => (format!("Submitted queued feedback for {}", alias), false),
+            Err(SubmitError::Unavailable(error)) => {
+                warn!("Failed to submit queued feedback for {}: {}", alias, error);
+                continue;
+            },
+            Err(SubmitError::Rejected(error)) => (
+                format!("Failed to submit queued feedback for {}: {}", alias, error),
+                true,
+            ),
+        };
+
+        // the cache is only opened while it is accessed, the connection can't be held across await points
+        Cache::open()?.remove_queued_feedback(queued.id)?;
+
+        notifier
+            .notify(&Message {
+                summary: &summary,
+                url: &format!("{}/updates/{}", bodhi_url(), alias),
+                updates: &[],
+                urgent,
+                custom_body: None,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use tracing::warn;
+
+// proxy and additional CA certificates for all HTTP clients, i.e. for corporate networks
+#[derive(Default)]
+struct Settings {
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+// bundles can contain multiple PEM certificates
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>, String> {
+    let contents = match read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            return Err(format!("Failed to read CA bundle {}: {}", path.display(), error));
+        },
+    };
+
+    let mut certificates = Vec::new();
+    for block in contents.split_inclusive("-----END CERTIFICATE-----") {
+        if !block.contains("-----BEGIN CERTIFICATE-----") {
+            continue;
+        }
+
+        match Certificate::from_pem(block.as_bytes()) {
+            Ok(certificate) => certificates.push(certificate),
+            Err(error) => {
+                return Err(format!("Invalid certificate in {}: {}", path.display(), error));
+            },
+        }
+    }
+
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+
+    Ok(certificates)
+}
+
+// reqwest honors $https_proxy and $http_proxy, but not $all_proxy (which is used i.e. for SOCKS proxies)
+fn all_proxy() -> Option<String> {
+    let configured = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+    if ["https_proxy", "HTTPS_PROXY", "http_proxy", "HTTP_PROXY"]
+        .iter()
+        .any(|name| configured(name).is_some())
+    {
+        return None;
+    }
+
+    configured("all_proxy").or_else(|| configured("ALL_PROXY"))
+}
+
+// this needs to be called before any clients are built
+pub fn configure(proxy: Option<&str>, ca_bundle: Option<&Path>) -> Result<(), String> {
+    let proxy = match (proxy, all_proxy()) {
+        (Some(url), _) => match Proxy::all(url) {
+            Ok(proxy) => Some(proxy),
+            Err(error) => {
+                return Err(format!("Invalid proxy URL {}: {}", url, error));
+            },
+        },
+        // the environment can contain proxies for other programs, i.e. socks4://, which reqwest doesn't support
+        (None, Some(url)) => match Proxy::all(&url) {
+            Ok(proxy) => Some(proxy),
+            Err(error) => {
+                warn!("Ignoring unsupported proxy from $all_proxy ({}): {}", url, error);
+                None
+            },
+        },
+        (None, None) => None,
+    };
+
+    let certificates = match ca_bundle {
+        Some(path) => read_certificates(path)?,
+        None => Vec::new(),
+    };
+
+    if SETTINGS.set(Settings { proxy, certificates }).is_err() {
+        warn!("HTTP settings were already configured");
+    }
+
+    Ok(())
+}
+
+// proxies from the environment are used unless a proxy is configured
+pub fn client_builder() -> ClientBuilder {
+    let mut builder =
+        reqwest::Client::builder().user_agent(concat!("fedora-update-notifier/", env!("CARGO_PKG_VERSION")));
+
+    if let Some(settings) = SETTINGS.get() {
+        if let Some(proxy) = &settings.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &settings.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+    }
+
+    builder
+}
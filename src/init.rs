@@ -0,0 +1,193 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::process::Command;
+
+use toml::value::{Table, Value};
+
+use crate::auth::prompt;
+use crate::dnf;
+
+const FEDORA_REALM: &str = "@FEDORAPROJECT.ORG";
+
+// the FAS username is written to ~/.fedora.upn by older versions of fedora-packager-setup
+fn username_from_upn() -> Option<String> {
+    let contents = read_to_string(dirs::home_dir()?.join(".fedora.upn")).ok()?;
+    let username = contents.trim();
+
+    if username.is_empty() {
+        None
+    } else {
+        Some(username.to_owned())
+    }
+}
+
+// fedpkg can be configured with an explicit "user" setting
+fn username_from_fedpkg() -> Option<String> {
+    let contents = read_to_string(dirs::config_dir()?.join("rpkg/fedpkg.conf")).ok()?;
+
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        if key.trim() == "user" && !value.trim().is_empty() {
+            Some(value.trim().to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+// principals for the Fedora realm are "USERNAME@FEDORAPROJECT.ORG"
+fn username_from_kerberos() -> Option<String> {
+    let output = Command::new("klist").output().ok()?;
+    let output = String::from_utf8(output.stdout).ok()?;
+
+    output.lines().find_map(|line| {
+        let principal = line.strip_prefix("Default principal:")?.trim();
+        principal.strip_suffix(FEDORA_REALM).map(String::from)
+    })
+}
+
+fn detect_username() -> Option<(String, &'static str)> {
+    if let Some(username) = username_from_upn() {
+        return Some((username, "~/.fedora.upn"));
+    }
+    if let Some(username) = username_from_fedpkg() {
+        return Some((username, "fedpkg configuration"));
+    }
+    if let Some(username) = username_from_kerberos() {
+        return Some((username, "Kerberos principal"));
+    }
+    None
+}
+
+fn confirm(message: &str, default: bool) -> Result<bool, String> {
+    let choices = if default { "[Y/n]" } else { "[y/N]" };
+    let answer = prompt(&format!("{} {} ", message, choices))?;
+
+    match answer.to_lowercase().as_str() {
+        "" => Ok(default),
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err(format!("Invalid answer: {}", answer)),
+    }
+}
+
+fn ask_username() -> Result<String, String> {
+    let detected = detect_username();
+
+    let username = match &detected {
+        Some((username, source)) => {
+            let answer = prompt(&format!("FAS username (found in {}) [{}]: ", source, username))?;
+            if answer.is_empty() {
+                username.clone()
+            } else {
+                answer
+            }
+        },
+        None => prompt("FAS username: ")?,
+    };
+
+    if username.is_empty() {
+        return Err(String::from("A FAS username is required."));
+    }
+
+    Ok(username)
+}
+
+fn ask_interests() -> Result<Vec<String>, String> {
+    match dnf::userinstalled() {
+        Ok(mut packages) => {
+            packages.sort();
+            packages.dedup();
+
+            let message = format!(
+                "Add the {} explicitly installed (source) packages to the list of interesting packages?",
+                packages.len()
+            );
+            if !packages.is_empty() && confirm(&message, true)? {
+                return Ok(packages);
+            }
+        },
+        Err(error) => {
+            println!("Unable to query explicitly installed packages: {}", error);
+        },
+    }
+
+    let answer = prompt("Interesting packages (separated by commas, can be edited later): ")?;
+
+    Ok(answer
+        .split(',')
+        .map(str::trim)
+        .filter(|package| !package.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+// creates ~/.config/fedora.toml interactively, keeping other sections of an existing file
+pub fn init() -> Result<(), String> {
+    let path = crate::config_path()?;
+
+    let mut config = match read_to_string(&path) {
+        Ok(contents) => {
+            let config: Table = match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(error) => {
+                    return Err(format!("Unable to parse existing {}: {}", path.display(), error));
+                },
+            };
+
+            let message = format!(
+                "{} already exists. Update it (comments will not be preserved)?",
+                path.display()
+            );
+            if !confirm(&message, false)? {
+                return Ok(());
+            }
+
+            config
+        },
+        Err(_) => Table::new(),
+    };
+
+    let username = ask_username()?;
+    let interests = ask_interests()?;
+
+    let mut fas = match config.remove("FAS") {
+        Some(Value::Table(table)) => table,
+        _ => Table::new(),
+    };
+    fas.insert(String::from("username"), Value::String(username));
+    config.insert(String::from("FAS"), Value::Table(fas));
+
+    let mut settings = match config.remove("fedora-update-notifier") {
+        Some(Value::Table(table)) => table,
+        _ => Table::new(),
+    };
+    settings.insert(
+        String::from("interests"),
+        Value::Array(interests.into_iter().map(Value::String).collect()),
+    );
+    config.insert(String::from("fedora-update-notifier"), Value::Table(settings));
+
+    let contents = match toml::to_string(&Value::Table(config)) {
+        Ok(contents) => contents,
+        Err(error) => {
+            return Err(format!("{}", error));
+        },
+    };
+
+    if let Some(dir) = path.parent() {
+        if let Err(error) = create_dir_all(dir) {
+            return Err(format!("Unable to create {}: {}", dir.display(), error));
+        }
+    }
+
+    if let Err(error) = write(&path, contents) {
+        return Err(format!("Unable to write {}: {}", path.display(), error));
+    }
+
+    println!("Configuration written to {}.", path.display());
+    println!(
+        "Check it with \"fedora-update-notifier doctor\", see \"fedora-update-notifier --help\" for more settings."
+    );
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use serde::Deserialize;
+
+use crate::dnf;
+use crate::pagure::Pagure;
+use crate::retry::RetryPolicy;
+
+// interests are either a plain list of packages, or a table with different sources
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum InterestsConfig {
+    Packages(Vec<String>),
+    Sources(Interests),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Auto {
+    // packages that were explicitly installed by the user
+    UserInstalled,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Interests {
+    #[serde(default)]
+    pub packages: Vec<String>,
+    // FAS users or "@group"s whose packages are interesting
+    #[serde(default)]
+    pub maintainers: Vec<String>,
+    // comps groups whose packages are interesting
+    #[serde(default)]
+    pub groups: Vec<String>,
+    #[serde(default)]
+    pub auto: Option<Auto>,
+}
+
+// interests can also be given on the command line only
+impl Default for InterestsConfig {
+    fn default() -> Self {
+        InterestsConfig::Packages(Vec::new())
+    }
+}
+
+impl From<InterestsConfig> for Interests {
+    fn from(config: InterestsConfig) -> Self {
+        match config {
+            InterestsConfig::Packages(packages) => Interests {
+                packages,
+                ..Default::default()
+            },
+            InterestsConfig::Sources(interests) => interests,
+        }
+    }
+}
+
+impl Interests {
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.maintainers.is_empty() && self.groups.is_empty() && self.auto.is_none()
+    }
+
+    // expands all sources of interests into a list of source package names
+    pub async fn expand(&self, policy: RetryPolicy) -> Result<Vec<String>, String> {
+        let mut packages = self.packages.clone();
+
+        if !self.maintainers.is_empty() {
+            let pagure = Pagure::new(policy)?;
+            for maintainer in &self.maintainers {
+                packages.extend(pagure.packages(maintainer).await?);
+            }
+        }
+
+        if !self.groups.is_empty() || self.auto.is_some() {
+            let groups = self.groups.clone();
+            let auto = self.auto;
+
+            let local = tokio::task::spawn_blocking(move || -> Result<Vec<String>, String> {
+                let mut local = Vec::new();
+                for group in &groups {
+                    local.extend(dnf::group_packages(group)?);
+                }
+                if auto == Some(Auto::UserInstalled) {
+                    local.extend(dnf::userinstalled()?);
+                }
+                Ok(local)
+            })
+            .await;
+
+            match local {
+                Ok(local) => packages.extend(local?),
+                Err(error) => {
+                    return Err(format!("{}", error));
+                },
+            }
+        }
+
+        packages.sort_unstable();
+        packages.dedup();
+        Ok(packages)
+    }
+}
@@ -0,0 +1,148 @@
+use sd_notify::NotifyState;
+use tracing_subscriber::EnvFilter;
+
+// exit codes, so timer-driven runs can be told apart in "systemctl status", and scripts can act on the result
+pub const EXIT_NOTHING: i32 = 0;
+pub const EXIT_FEEDBACK: i32 = 2;
+pub const EXIT_PENDING: i32 = 3;
+pub const EXIT_FEEDBACK_PENDING: i32 = 4;
+pub const EXIT_OFFLINE: i32 = 11;
+pub const EXIT_UNSENT: i32 = 12;
+
+// classes of errors, with values from sysexits.h
+pub const EXIT_USAGE: i32 = 64;
+pub const EXIT_ERROR: i32 = 70;
+pub const EXIT_BUSY: i32 = 75;
+pub const EXIT_CONFIG: i32 = 78;
+
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub feedback: usize,
+    pub pending: usize,
+    // bodhi was not reachable, so nothing was checked
+    pub offline: bool,
+    // notifications could only be printed to the terminal
+    pub unsent: bool,
+}
+
+impl Outcome {
+    pub fn exit_code(&self) -> i32 {
+        if self.offline {
+            return EXIT_OFFLINE;
+        }
+
+        // updates were found (or there would be nothing to send), but nobody was told about them; this takes
+        // precedence, runs with --no-notify or the stdout notifier never fall back and keep the other statuses
+        if self.unsent {
+            return EXIT_UNSENT;
+        }
+
+        match (self.feedback > 0, self.pending > 0) {
+            (true, true) => EXIT_FEEDBACK_PENDING,
+            (true, false) => EXIT_FEEDBACK,
+            (false, true) => EXIT_PENDING,
+            (false, false) => EXIT_NOTHING,
+        }
+    }
+}
+
+// errors that aren't classified otherwise are reported with EXIT_ERROR
+#[derive(Debug)]
+pub struct Failure {
+    pub message: String,
+    pub exit_code: i32,
+}
+
+impl Failure {
+    // invalid command-line arguments
+    pub fn usage(message: String) -> Self {
+        Failure {
+            message,
+            exit_code: EXIT_USAGE,
+        }
+    }
+
+    // another instance is running, trying again later can work
+    pub fn busy(message: String) -> Self {
+        Failure {
+            message,
+            exit_code: EXIT_BUSY,
+        }
+    }
+
+    // missing or invalid settings
+    pub fn config(message: String) -> Self {
+        Failure {
+            message,
+            exit_code: EXIT_CONFIG,
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Failure {
+            message,
+            exit_code: EXIT_ERROR,
+        }
+    }
+}
+
+// stdout / stderr are connected to the journal if this is set by systemd
+pub fn is_journal() -> bool {
+    std::env::var_os("JOURNAL_STREAM").is_some()
+}
+
+// log messages go to stderr, with verbosity from -v / -q or overridden with RUST_LOG
+pub fn init_logging(verbosity: i64) {
+    let level = match verbosity {
+        v if v < 0 => "error",
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::new(format!("warn,fedora_update_notifier={}", level)),
+    };
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(false);
+
+    // the journal has its own timestamps
+    if is_journal() {
+        subscriber.with_ansi(false).without_time().init();
+    } else {
+        subscriber.init();
+    }
+}
+
+// these are no-ops if not running under systemd
+pub fn ready() {
+    let _ = sd_notify::notify(false, &[NotifyState::Ready]);
+}
+
+pub fn status(message: &str) {
+    let _ = sd_notify::notify(false, &[NotifyState::Status(message)]);
+}
+
+// messages are prefixed with syslog priorities, which are picked up by journald
+pub fn info(message: &str) {
+    if is_journal() {
+        println!("<6>{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+pub fn error(message: &str) {
+    if is_journal() {
+        eprintln!("<3>{}", message);
+    } else {
+        eprintln!("Error: {}", message);
+    }
+}
@@ -0,0 +1,170 @@
+use bodhi::data::FedoraRelease;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::http;
+use crate::retry::{retry, RetryPolicy};
+
+// koji itself only has an XML-RPC API, but all build state changes are also published on the message bus,
+// and archived by datagrepper, which can be queried with plain JSON
+const DATAGREPPER_URL: &str = "https://apps.fedoraproject.org/datagrepper/v2/search";
+const BUILD_TOPIC: &str = "org.fedoraproject.prod.buildsys.build.state.change";
+const KOJI_URL: &str = "https://koji.fedoraproject.org/koji";
+
+const ROWS_PER_PAGE: u32 = 100;
+const CONCURRENT_PAGES: usize = 4;
+
+// datagrepper combines values of the same filter with OR, but different filters with AND, so packages and users are
+// queried separately, with several packages per query
+const PACKAGES_PER_QUERY: usize = 25;
+
+// build states are numbered, see koji.BUILD_STATES
+const BUILD_COMPLETE: i64 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct CompletedBuild {
+    pub nvr: String,
+    pub owner: String,
+    pub url: String,
+}
+
+pub struct Koji {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+// i.e. ".fc39" for F39 and ".el9" for EPEL-9, which is the suffix of the release of all builds for it
+fn dist_tag(release: &FedoraRelease) -> String {
+    let release = release.to_string();
+    match release.strip_prefix("EPEL-") {
+        Some(major) => format!(".el{}", major),
+        None => format!(".fc{}", release.trim_start_matches('F')),
+    }
+}
+
+impl Koji {
+    pub fn new(policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Koji { client, policy })
+    }
+
+    async fn fetch_page(&self, since: i64, filter: &[(&str, &str)], page: u32) -> Result<Value, reqwest::Error> {
+        debug!("Querying datagrepper for builds since {} (page {})", since, page);
+
+        self.client
+            .get(DATAGREPPER_URL)
+            .query(&[("topic", BUILD_TOPIC), ("order", "asc")])
+            .query(filter)
+            .query(&[("start", since)])
+            .query(&[("page", page), ("rows_per_page", ROWS_PER_PAGE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    async fn messages(&self, since: i64, filter: &[(&str, &str)]) -> Result<Vec<Value>, String> {
+        let first = retry(self.policy, "Querying datagrepper (page 1)", || {
+            self.fetch_page(since, filter, 1)
+        })
+        .await?;
+        let pages = first["pages"].as_u64().unwrap_or(1) as u32;
+
+        let rest: Vec<Result<Value, String>> = stream::iter(2..=pages)
+            .map(|page| async move {
+                let what = format!("Querying datagrepper (page {} of {})", page, pages);
+                retry(self.policy, &what, || self.fetch_page(since, filter, page)).await
+            })
+            .buffered(CONCURRENT_PAGES)
+            .collect()
+            .await;
+
+        let mut messages: Vec<Value> = Vec::new();
+        for page in std::iter::once(Ok(first)).chain(rest) {
+            let mut page = page?;
+            match page.get_mut("raw_messages").map(Value::take) {
+                Some(Value::Array(items)) => messages.extend(items),
+                _ => {
+                    return Err(String::from(
+                        "Unexpected response from datagrepper: missing \"raw_messages\"",
+                    ));
+                },
+            }
+        }
+
+        Ok(messages)
+    }
+
+    // builds for the given release that completed since a point in time, either of one of the given packages,
+    // or built by the given user
+    pub async fn completed_builds(
+        &self,
+        release: &FedoraRelease,
+        since: i64,
+        packages: &[String],
+        owner: &str,
+    ) -> Result<Vec<CompletedBuild>, String> {
+        let dist_tag = dist_tag(release);
+        let mut builds: Vec<CompletedBuild> = Vec::new();
+
+        let mut filters: Vec<Vec<(&str, &str)>> = vec![vec![("user", owner)]];
+        for chunk in packages.chunks(PACKAGES_PER_QUERY) {
+            filters.push(chunk.iter().map(|package| ("package", package.as_str())).collect());
+        }
+
+        let mut messages: Vec<Value> = Vec::new();
+        for filter in &filters {
+            messages.extend(self.messages(since, filter).await?);
+        }
+
+        for message in messages {
+            let body = &message["body"];
+
+            let (name, version, release) = match (&body["name"], &body["version"], &body["release"]) {
+                (Value::String(name), Value::String(version), Value::String(release)) => (name, version, release),
+                _ => continue,
+            };
+            let owner_name = body["owner"].as_str().unwrap_or_default();
+
+            // builds on secondary architecture instances are mirrors of primary builds
+            let primary = body["instance"]
+                .as_str()
+                .map(|instance| instance == "primary")
+                .unwrap_or(true);
+            let complete = body["new"].as_i64() == Some(BUILD_COMPLETE);
+            let watched = owner_name == owner || packages.contains(name);
+
+            if !primary || !complete || !watched || !release.ends_with(&dist_tag) {
+                continue;
+            }
+
+            let nvr = format!("{}-{}-{}", name, version, release);
+            // builds of watched packages by the given user are part of both queries
+            if builds.iter().any(|build| build.nvr == nvr) {
+                continue;
+            }
+
+            let url = match body["build_id"].as_u64() {
+                Some(id) => format!("{}/buildinfo?buildID={}", KOJI_URL, id),
+                None => format!("{}/search?match=exact&type=build&terms={}", KOJI_URL, nvr),
+            };
+
+            builds.push(CompletedBuild {
+                nvr,
+                owner: owner_name.to_owned(),
+                url,
+            });
+        }
+
+        Ok(builds)
+    }
+}
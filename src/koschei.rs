@@ -0,0 +1,77 @@
+use bodhi::data::FedoraRelease;
+use serde::Deserialize;
+
+use crate::http;
+use crate::retry::{retry, RetryPolicy};
+
+const KOSCHEI_URL: &str = "https://koschei.fedoraproject.org";
+
+// keeps query URLs reasonably short
+const PACKAGES_PER_QUERY: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct PackageState {
+    name: String,
+    state: String,
+}
+
+// koschei collections are named like dist-git branches, i.e. "f39" or "epel9"
+fn collection(release: &FedoraRelease) -> String {
+    release.to_string().to_lowercase().replace('-', "")
+}
+
+pub fn package_url(release: &FedoraRelease, package: &str) -> String {
+    format!("{}/package/{}?collection={}", KOSCHEI_URL, package, collection(release))
+}
+
+pub struct Koschei {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl Koschei {
+    pub fn new(policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Koschei { client, policy })
+    }
+
+    async fn fetch(&self, collection: &str, packages: &[String]) -> Result<Vec<PackageState>, reqwest::Error> {
+        let mut query: Vec<(&str, &str)> = vec![("collection", collection)];
+        query.extend(packages.iter().map(|package| ("name", package.as_str())));
+
+        self.client
+            .get(format!("{}/api/v1/packages", KOSCHEI_URL))
+            .query(&query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    // returns the (source) packages whose scratch rebuilds in koschei are currently failing,
+    // packages that are not tracked by koschei are ignored
+    pub async fn failing_packages(&self, release: &FedoraRelease, packages: &[String]) -> Result<Vec<String>, String> {
+        let collection = collection(release);
+        let mut failing: Vec<String> = Vec::new();
+
+        for chunk in packages.chunks(PACKAGES_PER_QUERY) {
+            let states = retry(self.policy, "Querying koschei", || self.fetch(&collection, chunk)).await?;
+
+            failing.extend(
+                states
+                    .into_iter()
+                    .filter(|package| package.state == "failing")
+                    .map(|package| package.name),
+            );
+        }
+
+        Ok(failing)
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+// translations are fluent resources in the i18n directory, which are compiled into the binary;
+// messages that are missing from a translation fall back to the English resource
+const RESOURCES: &[(&str, &str)] = &[
+    ("en", include_str!("../i18n/en.ftl")),
+    ("de", include_str!("../i18n/de.ftl")),
+    ("es", include_str!("../i18n/es.ftl")),
+    ("fr", include_str!("../i18n/fr.ftl")),
+];
+
+type Bundle = FluentBundle<FluentResource>;
+
+// the bundle for the language from the environment (if there is a translation), followed by the English one
+static BUNDLES: OnceLock<Vec<Bundle>> = OnceLock::new();
+
+fn load(language: &str) -> Option<Bundle> {
+    let (_, source) = RESOURCES.iter().find(|(id, _)| *id == language)?;
+    let id: LanguageIdentifier = language.parse().ok()?;
+
+    let resource = match FluentResource::try_new(source.to_string()) {
+        Ok(resource) => resource,
+        Err((_, errors)) => {
+            warn!("Invalid translations for \"{}\": {:?}", language, errors);
+            return None;
+        },
+    };
+
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // unicode isolation marks around placeholders would show up in terminals and notifications
+    bundle.set_use_isolating(false);
+
+    if let Err(errors) = bundle.add_resource(resource) {
+        warn!("Invalid translations for \"{}\": {:?}", language, errors);
+        return None;
+    }
+
+    Some(bundle)
+}
+
+fn bundles() -> &'static [Bundle] {
+    BUNDLES.get_or_init(|| {
+        let language = language_from_env().filter(|language| language != "en");
+
+        language
+            .as_deref()
+            .into_iter()
+            .chain(std::iter::once("en"))
+            .filter_map(load)
+            .collect()
+    })
+}
+
+fn translate(bundles: &[Bundle], id: &str, args: Option<&FluentArgs>) -> String {
+    for bundle in bundles {
+        let pattern = match bundle.get_message(id).and_then(|message| message.value()) {
+            Some(pattern) => pattern,
+            None => continue,
+        };
+
+        let mut errors = Vec::new();
+        let text = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            warn!("Failed to format message \"{}\": {:?}", id, errors);
+        }
+
+        return text.into_owned();
+    }
+
+    id.to_owned()
+}
+
+// i.e. "de" for "de_AT.UTF-8", checking the same variables as gettext (in the same order)
+fn language_from_env() -> Option<String> {
+    for variable in ["LANGUAGE", "LC_ALL", "LC_MESSAGES", "LANG"] {
+        let value = match std::env::var(variable) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+
+        // LANGUAGE can contain a list of languages, only the first one is used
+        let locale = value.split(':').next().unwrap_or_default();
+        let language = locale.split(['_', '.', '@']).next().unwrap_or_default();
+
+        if !language.is_empty() {
+            return Some(language.to_lowercase());
+        }
+    }
+
+    None
+}
+
+// translates a message
+pub fn tr(id: &str) -> String {
+    translate(bundles(), id, None)
+}
+
+// translates a message and replaces its named placeholders
+pub fn trf(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.to_string());
+    }
+
+    translate(bundles(), id, Some(&fluent_args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // message IDs and the names of their placeholders, one message per line
+    fn messages(source: &str) -> Vec<(&str, Vec<&str>)> {
+        source
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, text)| {
+                let mut names: Vec<&str> = text
+                    .split("{ $")
+                    .skip(1)
+                    .filter_map(|part| part.split(' ').next())
+                    .collect();
+                names.sort_unstable();
+                (id, names)
+            })
+            .collect()
+    }
+
+    fn args(values: &[(&'static str, &'static str)]) -> FluentArgs<'static> {
+        let mut args = FluentArgs::new();
+        for (name, value) in values {
+            args.set(*name, *value);
+        }
+        args
+    }
+
+    #[test]
+    fn substitution() {
+        let bundles: Vec<Bundle> = load("en").into_iter().collect();
+
+        assert_eq!(
+            translate(&bundles, "feedback-url", Some(&args(&[("url", "https://example.com")]))),
+            "Feedback URL: https://example.com"
+        );
+        assert_eq!(
+            translate(
+                &bundles,
+                "packages-and-more",
+                Some(&args(&[("packages", "a, b"), ("count", "3")]))
+            ),
+            "a, b and 3 more"
+        );
+        assert_eq!(translate(&bundles, "builds", None), "Builds:");
+        assert_eq!(translate(&bundles, "missing", None), "missing");
+    }
+
+    #[test]
+    fn fallback() {
+        let bundles: Vec<Bundle> = vec![load("de"), load("en")].into_iter().flatten().collect();
+
+        assert_eq!(bundles.len(), 2);
+        assert_eq!(translate(&bundles, "notes", None), "Anmerkungen:");
+    }
+
+    #[test]
+    fn catalogs_are_complete() {
+        let expected = messages(RESOURCES[0].1);
+
+        for (language, source) in RESOURCES {
+            assert!(load(language).is_some(), "{}", language);
+            assert_eq!(messages(source), expected, "{}", language);
+        }
+    }
+}
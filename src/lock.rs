@@ -0,0 +1,56 @@
+use std::fs::{create_dir_all, File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+
+use tracing::info;
+
+// the lock is held until this is dropped, or until the process exits
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+fn lock_path() -> Result<PathBuf, String> {
+    // the runtime directory is not available outside of user sessions
+    match dirs::runtime_dir().or_else(dirs::cache_dir) {
+        Some(path) => Ok(path.join("fedora-update-notifier").join("lock")),
+        None => Err(String::from("Unable to determine $XDG_RUNTIME_DIR.")),
+    }
+}
+
+// makes sure that only one instance at a time writes state or sends notifications
+pub fn acquire(wait: bool) -> Result<InstanceLock, String> {
+    let path = lock_path()?;
+
+    if let Some(dir) = path.parent() {
+        if let Err(error) = create_dir_all(dir) {
+            return Err(format!("Unable to create {}: {}", dir.display(), error));
+        }
+    }
+
+    let file = match OpenOptions::new().create(true).write(true).truncate(false).open(&path) {
+        Ok(file) => file,
+        Err(error) => {
+            return Err(format!("Unable to open {}: {}", path.display(), error));
+        },
+    };
+
+    match file.try_lock() {
+        Ok(()) => {},
+        Err(TryLockError::WouldBlock) if wait => {
+            info!("Waiting for another instance to finish");
+            if let Err(error) = file.lock() {
+                return Err(format!("Unable to lock {}: {}", path.display(), error));
+            }
+        },
+        Err(TryLockError::WouldBlock) => {
+            return Err(String::from(
+                "Another instance is already running (use --wait to wait for it to finish).",
+            ));
+        },
+        Err(TryLockError::Error(error)) => {
+            return Err(format!("Unable to lock {}: {}", path.display(), error));
+        },
+    }
+
+    Ok(InstanceLock { _file: file })
+}
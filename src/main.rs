@@ -1,119 +1,372 @@
-use std::cmp::PartialEq;
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fs::read_to_string;
+use std::path::PathBuf;
 use std::process::Command;
-use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use bodhi::data::*;
-use bodhi::BodhiClientBuilder;
-
-use notify_rust::Notification;
 
 use serde::Deserialize;
+use tracing::{debug, info, info_span, warn, Instrument};
 
-#[derive(Debug, PartialEq)]
-#[allow(clippy::upper_case_acronyms)]
-struct NVR<'a> {
-    n: &'a str,
-    v: &'a str,
-    r: &'a str,
-}
+mod auth;
+use auth::Authenticator;
+
+mod autostart;
+
+mod backend;
+use backend::{CachedBackend, FallbackBackend, PackageBackend};
+
+mod bugzilla;
+use bugzilla::Bugzilla;
+
+mod cache;
+use cache::Cache;
+
+mod categories;
+use categories::Categories;
+
+mod changelog;
+use changelog::Mdapi;
+
+mod completions;
+
+mod containers;
+
+mod daemon;
+
+mod dnf;
+
+mod doctor;
+
+mod export;
+use export::ExportFormat;
+
+mod feedback;
+use feedback::{AutoCommentConfig, Feedback, SubmitError, SystemInfo};
+
+mod http;
+
+mod init;
+
+mod interests;
+use interests::{Interests, InterestsConfig};
+
+mod journal;
+use journal::{Failure, Outcome};
+
+mod koji;
+use koji::Koji;
+
+mod koschei;
+use koschei::Koschei;
+
+mod l10n;
+
+mod lock;
+
+mod metrics;
+
+mod modules;
+
+mod notifier;
+use notifier::{
+    DeferringNotifier, DryRunNotifier, Message, NotifierConfig, RateLimit, RateLimitingNotifier, TemplateNotifier,
+};
+
+mod nvr;
+use nvr::NEVR;
+
+mod orphans;
+
+mod output;
+use output::{
+    Aggregate, Format, MineSummary, OverrideSummary, PackageChangelog, RebuildSummary, Summary, TargetSummary,
+    UpdateSummary,
+};
+
+mod overrides;
+use overrides::BuildrootOverride;
+
+mod packagekit;
+use packagekit::PackageKit;
+
+mod pagure;
+use pagure::Pagure;
+
+mod portal;
+
+mod power;
+
+mod progress;
+
+mod quiet;
+use quiet::QuietHours;
+
+mod releases;
+
+mod report;
+use report::{evaluate, Alert, Evaluation, Filters, Renotify, Severity, Soak, SortOrder};
+
+mod retry;
+use retry::RetryPolicy;
+
+mod source;
+use source::{BodhiSource, QueryStrategy, UpdateSource};
+
+mod subprocess;
+
+mod systemd;
+
+mod table;
+
+mod target;
+use target::{HostConfig, Source, Target};
+
+mod templates;
+use templates::{TemplateConfig, Templates};
+
+mod tray;
+
+mod tui;
+
+mod updateinfo;
+
+mod version;
+
+// updates of the current user which were pushed to stable are shown for this long with --mine
+const MINE_STABLE_DAYS: i64 = 7;
 
 #[derive(Debug, Deserialize)]
 struct FedoraConfig {
     #[serde(rename(deserialize = "FAS"))]
     fas: FASConfig,
-    #[serde(rename(deserialize = "fedora-update-notifier"))]
+    #[serde(rename(deserialize = "fedora-update-notifier"), default)]
     fedora_update_notifier: FUNConfig,
 }
 
 #[derive(Debug, Deserialize)]
 struct FASConfig {
     username: String,
+    // other accounts whose comments count as well, i.e. of other people using the same machine
+    #[serde(default)]
+    usernames: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct FUNConfig {
-    interests: Vec<String>,
+    #[serde(default)]
+    interests: InterestsConfig,
+    #[serde(default)]
+    bodhi_url: Option<String>,
+    #[serde(default)]
+    proxy: Option<String>,
+    #[serde(default)]
+    ca_bundle: Option<PathBuf>,
+    #[serde(default)]
+    retries: Option<u32>,
+    #[serde(default)]
+    backoff: Option<u64>,
+    #[serde(default)]
+    http_timeout: Option<u64>,
+    #[serde(default)]
+    command_timeout: Option<u64>,
+    #[serde(default)]
+    lock_wait: Option<u64>,
+    #[serde(default)]
+    incremental: Option<bool>,
+    #[serde(default)]
+    cache_installed: Option<bool>,
+    #[serde(default)]
+    refresh: Option<u64>,
+    #[serde(default)]
+    query_strategy: Option<String>,
+    #[serde(default)]
+    skip_stable: Option<bool>,
+    #[serde(default)]
+    skip_failing: Option<bool>,
+    #[serde(default)]
+    critpath_only: Option<bool>,
+    #[serde(default)]
+    types: Option<Vec<String>>,
+    #[serde(default)]
+    min_severity: Option<String>,
+    #[serde(default)]
+    min_age: Option<i64>,
+    #[serde(default)]
+    max_age: Option<i64>,
+    #[serde(default)]
+    renotify: Option<Vec<String>>,
+    #[serde(default)]
+    excluded_authors: Option<Vec<String>>,
+    #[serde(default)]
+    skip_maintained: Option<bool>,
+    #[serde(default)]
+    sort: Option<String>,
+    #[serde(default)]
+    check_repo: Option<bool>,
+    #[serde(default)]
+    cross_check: Option<bool>,
+    #[serde(default)]
+    check_orphans: Option<bool>,
+    #[serde(default)]
+    soak_days: Option<u32>,
+    #[serde(default)]
+    soak_boots: Option<u32>,
+    #[serde(default)]
+    auto_comment: Option<AutoCommentConfig>,
+    #[serde(default)]
+    templates: Option<TemplateConfig>,
+    #[serde(default)]
+    group_notifications: Option<bool>,
+    #[serde(default)]
+    max_notifications_per_run: Option<u32>,
+    #[serde(default)]
+    max_notifications_per_day: Option<u32>,
+    #[serde(default)]
+    notification_interval: Option<u64>,
+    #[serde(default)]
+    quiet_hours: Option<String>,
+    #[serde(default)]
+    skip_metered: Option<bool>,
+    #[serde(default)]
+    min_battery: Option<f64>,
+    #[serde(default)]
+    scan_toolboxes: Option<bool>,
+    #[serde(default)]
+    scan_mock: Option<bool>,
+    #[serde(default)]
+    hosts: Vec<HostConfig>,
+    #[serde(default)]
+    override_warning_days: Option<i64>,
+    #[serde(default)]
+    release_notices: Option<bool>,
+    #[serde(default)]
+    eol_warning_days: Option<i64>,
+    #[serde(default)]
+    my_bugs: Option<bool>,
+    #[serde(default)]
+    bugzilla_email: Option<String>,
+    #[serde(default)]
+    bugzilla_api_key: Option<String>,
+    #[serde(default)]
+    changelog: Option<bool>,
+    #[serde(default)]
+    watch_builds: Option<bool>,
+    #[serde(default)]
+    koschei: Option<bool>,
+    #[serde(default)]
+    metrics_file: Option<PathBuf>,
+    #[serde(default)]
+    notifier: Option<String>,
+    #[serde(flatten)]
+    notifiers: NotifierConfig,
 }
 
-fn parse_nevra(nevra: &str) -> Result<(&str, &str, &str, &str, &str), String> {
-    let mut nevr_a: Vec<&str> = nevra.rsplitn(2, '.').collect();
-
-    if nevr_a.len() != 2 {
-        return Err(format!("Unexpected error when parsing NEVRAs: {}", nevra));
+fn config_path() -> Result<PathBuf, String> {
+    // the home directory is not accessible from a flatpak sandbox, but $XDG_CONFIG_HOME is
+    if portal::is_sandboxed() {
+        return match dirs::config_dir() {
+            Some(path) => Ok(path.join("fedora.toml")),
+            None => Err(String::from("Unable to determine $XDG_CONFIG_HOME.")),
+        };
     }
 
-    // rsplitn returns things in reverse order
-    let a = nevr_a.remove(0);
-    let nevr = nevr_a.remove(0);
-
-    let mut n_ev_r: Vec<&str> = nevr.rsplitn(3, '-').collect();
+    match dirs::home_dir() {
+        Some(path) => Ok(path.join(".config/fedora.toml")),
+        None => Err(String::from("Unable to determine $HOME.")),
+    }
+}
 
-    if n_ev_r.len() != 3 {
-        return Err(format!("Unexpected error when parsing NEVRAs: {}", nevr));
+// settings of profiles and from environment variables replace others, tables (i.e. notifier settings) are merged
+fn merge_settings(settings: &mut toml::value::Table, overrides: toml::value::Table) {
+    for (key, value) in overrides {
+        match (settings.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => merge_settings(existing, value),
+            (_, value) => {
+                settings.insert(key, value);
+            },
+        }
     }
+}
 
-    // rsplitn returns things in reverse order
-    let r = n_ev_r.remove(0);
-    let ev = n_ev_r.remove(0);
-    let n = n_ev_r.remove(0);
+// settings from FUN_* environment variables replace the ones from the configuration file, i.e. FUN_SKIP_STABLE=true,
+// FUN_USERNAME (in the [FAS] section), or FUN_MATRIX__ROOM (in the [fedora-update-notifier.matrix] table)
+const ENV_PREFIX: &str = "FUN_";
 
-    let (e, v) = if ev.contains(':') {
-        let mut e_v: Vec<&str> = ev.split(':').collect();
-        let e = e_v.remove(0);
-        let v = e_v.remove(0);
-        (e, v)
-    } else {
-        ("0", ev)
+// settings that are lists of strings, which are comma-separated in environment variables
+const ENV_LISTS: &[&str] = &["interests", "usernames", "types", "renotify"];
+
+fn env_value(key: &str, value: &str) -> toml::Value {
+    // numbers, booleans, arrays, and inline tables are parsed like in the configuration file
+    let parsed = match toml::from_str::<toml::value::Table>(&format!("value = {}", value)) {
+        Ok(mut table) => table.remove("value"),
+        Err(_) => None,
     };
 
-    Ok((n, e, v, r, a))
+    match parsed {
+        Some(toml::Value::Array(array)) => toml::Value::Array(array),
+        Some(toml::Value::Table(table)) => toml::Value::Table(table),
+        Some(_) | None if ENV_LISTS.contains(&key) => toml::Value::Array(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_owned()))
+                .collect(),
+        ),
+        Some(parsed) => parsed,
+        None => toml::Value::String(value.to_owned()),
+    }
 }
 
-fn parse_filename(nevrax: &str) -> Result<(&str, &str, &str, &str, &str), String> {
-    let mut nevra_x: Vec<&str> = nevrax.rsplitn(2, '.').collect();
+// settings from environment variables, in the same structure as the configuration file
+fn env_settings() -> toml::value::Table {
+    let mut settings = toml::value::Table::new();
 
-    if nevra_x.len() != 2 {
-        return Err(format!("Unexpected error when parsing dnf output: {}", nevrax));
-    }
+    for (variable, value) in std::env::vars() {
+        let name = match variable.strip_prefix(ENV_PREFIX) {
+            Some(name) => name.to_lowercase(),
+            None => continue,
+        };
 
-    // rsplitn returns things in reverse order
-    let _x = nevra_x.remove(0);
-    let nevra = nevra_x.remove(0);
+        // FUN_PROFILE selects the profile instead
+        if name == "profile" {
+            continue;
+        }
 
-    let (n, e, v, r, a) = parse_nevra(nevra)?;
-    Ok((n, e, v, r, a))
-}
+        let section = if name == "username" || name == "usernames" {
+            "FAS"
+        } else {
+            "fedora-update-notifier"
+        };
 
-fn parse_nvr(nvr: &str) -> Result<(&str, &str, &str), String> {
-    let mut n_v_r: Vec<&str> = nvr.rsplitn(3, '-').collect();
+        let mut path: Vec<&str> = name.split("__").collect();
+        let key = path.pop().unwrap_or_default();
 
-    if n_v_r.len() != 3 {
-        return Err(format!("Unexpected error when parsing NEVRAs: {}", nvr));
-    }
+        let mut setting = env_value(key, &value);
+        for part in std::iter::once(key).chain(path.into_iter().rev()) {
+            let mut wrapper = toml::value::Table::new();
+            wrapper.insert(part.to_owned(), setting);
+            setting = toml::Value::Table(wrapper);
+        }
 
-    // rsplitn returns things in reverse order
-    let r = n_v_r.remove(0);
-    let v = n_v_r.remove(0);
-    let n = n_v_r.remove(0);
+        let mut wrapper = toml::value::Table::new();
+        wrapper.insert(section.to_owned(), setting);
+        merge_settings(&mut settings, wrapper);
+    }
 
-    Ok((n, v, r))
+    settings
 }
 
-fn get_config() -> Result<FedoraConfig, String> {
-    let home = match dirs::home_dir() {
-        Some(path) => path,
-        None => {
-            return Err(String::from("Unable to determine $HOME."));
-        },
-    };
-
-    let config_path = home.join(".config/fedora.toml");
+fn get_config(profile: Option<&str>) -> Result<FedoraConfig, String> {
+    let env = env_settings();
 
-    let config_str = match read_to_string(config_path) {
+    let config_str = match read_to_string(config_path()?) {
         Ok(string) => string,
+        // i.e. in containers, where all settings can be set with environment variables
+        Err(_) if !env.is_empty() => String::new(),
         Err(_) => {
             return Err(String::from(
                 "Unable to read configuration file from ~/.config/fedora.toml",
@@ -121,7 +374,7 @@ fn get_config() -> Result<FedoraConfig, String> {
         },
     };
 
-    let config: FedoraConfig = match toml::from_str(&config_str) {
+    let mut config: toml::value::Table = match toml::from_str(&config_str) {
         Ok(config) => config,
         Err(_) => {
             return Err(String::from(
@@ -130,11 +383,39 @@ fn get_config() -> Result<FedoraConfig, String> {
         },
     };
 
-    Ok(config)
+    // profiles are only used if they are selected with --profile
+    let profiles = match config.get_mut("fedora-update-notifier") {
+        Some(toml::Value::Table(settings)) => settings.remove("profile"),
+        _ => None,
+    };
+
+    if let Some(name) = profile {
+        let profile = match profiles {
+            Some(toml::Value::Table(mut profiles)) => profiles.remove(name),
+            _ => None,
+        };
+        match (profile, config.get_mut("fedora-update-notifier")) {
+            (Some(toml::Value::Table(profile)), Some(toml::Value::Table(settings))) => {
+                merge_settings(settings, profile);
+            },
+            _ => {
+                return Err(format!("Profile {} is not defined in ~/.config/fedora.toml", name));
+            },
+        }
+    }
+
+    merge_settings(&mut config, env);
+
+    match toml::Value::Table(config).try_into() {
+        Ok(config) => Ok(config),
+        Err(_) => Err(String::from(
+            "Unable to parse configuration file from ~/.config/fedora.toml",
+        )),
+    }
 }
 
-fn get_release() -> Result<FedoraRelease, String> {
-    let output = match Command::new("rpm").arg("--eval").arg("%{fedora}").output() {
+fn rpm_eval(expression: &str) -> Result<String, String> {
+    let output = match subprocess::output(Command::new("rpm").arg("--eval").arg(expression)) {
         Ok(output) => output,
         Err(error) => {
             return Err(format!("{}", error));
@@ -151,13 +432,30 @@ fn get_release() -> Result<FedoraRelease, String> {
         },
     };
 
-    let release_num = match std::str::from_utf8(&output.stdout) {
-        Ok(result) => result,
-        Err(error) => {
-            return Err(format!("{}", error));
-        },
+    match std::str::from_utf8(&output.stdout) {
+        Ok(result) => Ok(result.trim().to_owned()),
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+fn get_release() -> Result<FedoraRelease, String> {
+    let release_num = rpm_eval("%{fedora}")?;
+
+    // undefined macros are not expanded, which is the case on Enterprise Linux systems
+    if release_num.starts_with('%') {
+        let os_release = match std::fs::read_to_string("/etc/os-release") {
+            Ok(os_release) => os_release,
+            Err(error) => {
+                return Err(format!("Failed to read /etc/os-release: {}", error));
+            },
+        };
+
+        if !target::is_enterprise_linux(&os_release) {
+            return Err(String::from("Neither a Fedora nor an Enterprise Linux system."));
+        }
+
+        return target::epel_release(&rpm_eval("%{rhel}")?);
     }
-    .trim();
 
     let release = format!("F{}", release_num);
 
@@ -171,8 +469,145 @@ fn get_release() -> Result<FedoraRelease, String> {
     Ok(release)
 }
 
+// uses the connectivity state of NetworkManager if it is known, and sends a single request to bodhi otherwise
+async fn is_online(policy: RetryPolicy) -> Result<bool, String> {
+    match power::has_connectivity() {
+        Some(online) => Ok(online),
+        None => Ok(BodhiSource::new(policy)?.is_reachable().await),
+    }
+}
+
+// queries installed packages without blocking the runtime
+async fn query_installed(backend: Box<dyn PackageBackend>) -> Result<Vec<NEVR>, String> {
+    let spinner = progress::spinner("Querying installed packages");
+    let result = tokio::task::spawn_blocking(move || backend.installed()).await;
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(packages) => packages,
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+// names of all packages that updates need to be queried for
+fn package_names(packages: &[NEVR], interests: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = packages
+        .iter()
+        .map(|nevr| nevr.n.clone())
+        .chain(interests.iter().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+// evaluates testing updates against the packages that are installed on another system; updates is None if only
+// updates for packages installed on this system were queried
+async fn scan_target(
+    target: Target,
+    source: &BodhiSource,
+    release: &FedoraRelease,
+    updates: Option<&[Update]>,
+    usernames: &[String],
+    interests: &[String],
+    filters: &Filters,
+) -> Result<Summary, String> {
+    let packages = query_installed(target.backend).await?;
+
+    // other systems can run a different release
+    let other_updates;
+    let updates: &[Update] = match updates {
+        Some(updates) if target.release.to_string() == release.to_string() => updates,
+        Some(_) => {
+            other_updates = source.testing_updates(&target.release).await?;
+            &other_updates
+        },
+        None => {
+            other_updates = source
+                .package_updates(&target.release, &package_names(&packages, interests))
+                .await?;
+            &other_updates
+        },
+    };
+
+    // the cache only applies to packages installed on this system
+    let report = evaluate(&packages, updates, usernames, interests, filters, None)?;
+    Ok(Summary::new(&target.release, &report, interests, &target.source))
+}
+
+// opens the pages of installed updates that are ready for feedback (on any of the checked systems)
+fn open_pages(summary: &Summary, alias: Option<&str>, all: bool) -> Result<(), Failure> {
+    let mut feedback: Vec<&UpdateSummary> = Vec::new();
+    for update in summary
+        .feedback
+        .iter()
+        .chain(summary.targets.iter().flat_map(|t| &t.summary.feedback))
+    {
+        if !feedback.iter().any(|other| other.alias == update.alias) {
+            feedback.push(update);
+        }
+    }
+
+    let urls: Vec<&str> = match alias {
+        // interesting updates can be opened by their alias as well
+        Some(alias) => {
+            let pending = summary
+                .pending
+                .iter()
+                .chain(summary.targets.iter().flat_map(|t| &t.summary.pending));
+            match feedback
+                .iter()
+                .copied()
+                .chain(pending)
+                .find(|update| update.alias == alias)
+            {
+                Some(update) => vec![update.url.as_str()],
+                None => {
+                    return Err(Failure::usage(format!("No relevant update found for {}.", alias)));
+                },
+            }
+        },
+        None if all => feedback.iter().map(|update| update.url.as_str()).collect(),
+        // bodhi lists all of them on one page
+        None => match &summary.feedback_url {
+            Some(feedback_url) if !summary.feedback.is_empty() => vec![feedback_url.as_str()],
+            _ => feedback.iter().map(|update| update.url.as_str()).collect(),
+        },
+    };
+
+    if urls.is_empty() {
+        journal::info("No updates are ready for feedback.");
+        return Ok(());
+    }
+
+    for url in urls {
+        match Command::new("xdg-open").arg(url).status() {
+            Ok(status) if status.success() => {},
+            _ => {
+                return Err(Failure::from(format!("Failed to open {} in the browser.", url)));
+            },
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() -> Result<(), String> {
+async fn main() {
+    match run().await {
+        Ok(outcome) => {
+            journal::status("Finished.");
+            std::process::exit(outcome.exit_code());
+        },
+        Err(failure) => {
+            journal::status(&format!("Failed: {}", failure.message));
+            journal::error(&failure.message);
+            std::process::exit(failure.exit_code);
+        },
+    }
+}
+
+async fn run() -> Result<Outcome, Failure> {
     let app = clap::App::new("fedora-update-notifier")
         .arg(
             clap::Arg::with_name("username")
@@ -181,6 +616,13 @@ async fn main() -> Result<(), String> {
                 .takes_value(true)
                 .help("FAS user name"),
         )
+        .arg(
+            clap::Arg::with_name("profile")
+                .long("profile")
+                .value_name("name")
+                .takes_value(true)
+                .help("use the settings of this profile from the configuration file"),
+        )
         .arg(
             clap::Arg::with_name("interests")
                 .required(false)
@@ -188,298 +630,2041 @@ async fn main() -> Result<(), String> {
                 .multiple(true)
                 .help("interesting packages to check pending updates for"),
         )
+        .arg(
+            clap::Arg::with_name("verbose")
+                .long("verbose")
+                .short("v")
+                .multiple(true)
+                .help(
+                    "print more information about what is happening, i.e. why updates were skipped (repeat for more)",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("quiet")
+                .long("quiet")
+                .short("q")
+                .conflicts_with("verbose")
+                .help("only print errors"),
+        )
+        .arg(
+            clap::Arg::with_name("retries")
+                .long("retries")
+                .value_name("attempts")
+                .takes_value(true)
+                .help("number of attempts for failing bodhi queries (default: 3)"),
+        )
+        .arg(
+            clap::Arg::with_name("backoff")
+                .long("backoff")
+                .value_name("seconds")
+                .takes_value(true)
+                .help("initial delay between retries, doubled after every attempt (default: 2)"),
+        )
+        .arg(
+            clap::Arg::with_name("output")
+                .long("output")
+                .value_name("format")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .help("output format (default: text)"),
+        )
+        .arg(
+            clap::Arg::with_name("skip-stable")
+                .long("skip-stable")
+                .help("skip updates which already have enough karma or are requested for stable"),
+        )
+        .arg(
+            clap::Arg::with_name("skip-failing")
+                .long("skip-failing")
+                .help("skip updates with failing gating tests"),
+        )
+        .arg(
+            clap::Arg::with_name("critpath-only")
+                .long("critpath-only")
+                .help("only show updates for packages on the critical path"),
+        )
+        .arg(
+            clap::Arg::with_name("types")
+                .long("types")
+                .value_name("types")
+                .takes_value(true)
+                .help("only show updates of these types, i.e. \"security,bugfix\""),
+        )
+        .arg(
+            clap::Arg::with_name("renotify")
+                .long("renotify")
+                .value_name("reasons")
+                .takes_value(true)
+                .help("show updates again after commenting on them, i.e. \"no-karma,new-builds\""),
+        )
+        .arg(
+            clap::Arg::with_name("min-severity")
+                .long("min-severity")
+                .value_name("severity")
+                .takes_value(true)
+                .possible_values(&["unspecified", "low", "medium", "high", "urgent"])
+                .help("only show updates with at least this severity"),
+        )
+        .arg(
+            clap::Arg::with_name("min-age")
+                .long("min-age")
+                .value_name("days")
+                .takes_value(true)
+                .help("hide updates which have been in testing for less than this many days"),
+        )
+        .arg(
+            clap::Arg::with_name("max-age")
+                .long("max-age")
+                .value_name("days")
+                .takes_value(true)
+                .help("hide updates which have been in testing for more than this many days"),
+        )
+        .arg(
+            clap::Arg::with_name("sort")
+                .long("sort")
+                .value_name("order")
+                .takes_value(true)
+                .possible_values(&["priority", "age", "needs-karma"])
+                .help("sort updates by need for feedback, days in testing, or missing karma (default: priority)"),
+        )
+        .arg(
+            clap::Arg::with_name("needs-karma")
+                .long("needs-karma")
+                .conflicts_with("sort")
+                .help("only show updates that need more karma, those that will be pushed to stable untested first"),
+        )
+        .arg(
+            clap::Arg::with_name("my-bugs")
+                .long("my-bugs")
+                .help("only show updates which fix bugs that were reported by you or that you are CC'd on"),
+        )
+        .arg(
+            clap::Arg::with_name("notifier")
+                .long("notifier")
+                .value_name("notifier")
+                .takes_value(true)
+                .possible_values(&["desktop", "stdout", "null", "matrix", "webhook"])
+                .help("how to send notifications (default: desktop)"),
+        )
+        .arg(
+            clap::Arg::with_name("group-notifications")
+                .long("group-notifications")
+                .help("send one notification per category (security, desktop, command-line tools, libraries)"),
+        )
+        .arg(
+            clap::Arg::with_name("no-notify")
+                .long("no-notify")
+                .conflicts_with("notifier")
+                .help("don't send any notifications, only print results to the terminal"),
+        )
+        .arg(
+            clap::Arg::with_name("mine")
+                .long("mine")
+                .help("show the status of your own updates instead of updates that need feedback"),
+        )
+        .arg(
+            clap::Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("print which notifications would be sent, but don't send them or write any state"),
+        )
+        .arg(
+            clap::Arg::with_name("details")
+                .long("details")
+                .help("show notes, karma feedback, and install commands for every update"),
+        )
+        .arg(
+            clap::Arg::with_name("changelog")
+                .long("changelog")
+                .help("show changelog entries of interesting updates that are newer than the installed version"),
+        )
+        .arg(
+            clap::Arg::with_name("watch-builds")
+                .long("watch-builds")
+                .help("notify about new builds of interesting packages or your own packages in koji"),
+        )
+        .arg(
+            clap::Arg::with_name("koschei")
+                .long("koschei")
+                .help("flag interesting updates for packages whose rebuilds in koschei are failing"),
+        )
+        .arg(
+            clap::Arg::with_name("allow-metered")
+                .long("allow-metered")
+                .help("query bodhi even if the network connection is metered"),
+        )
+        .arg(
+            clap::Arg::with_name("allow-battery")
+                .long("allow-battery")
+                .help("query bodhi even if the battery level is below min_battery"),
+        )
+        .arg(
+            clap::Arg::with_name("scan-toolboxes")
+                .long("scan-toolboxes")
+                .help("also check packages installed in toolbox and distrobox containers"),
+        )
+        .arg(
+            clap::Arg::with_name("scan-mock")
+                .long("scan-mock")
+                .help("also check packages installed in local mock chroots"),
+        )
+        .arg(
+            clap::Arg::with_name("aggregate")
+                .long("aggregate")
+                .help("group updates by advisory across all checked machines"),
+        )
+        .arg(
+            clap::Arg::with_name("metrics-file")
+                .long("metrics-file")
+                .value_name("path")
+                .takes_value(true)
+                .help("write metrics for the node_exporter textfile collector to this file"),
+        )
+        .arg(
+            clap::Arg::with_name("wait")
+                .long("wait")
+                .help("wait for other running instances to finish instead of failing"),
+        )
+        .arg(
+            clap::Arg::with_name("full-refresh")
+                .long("full-refresh")
+                .help("query all updates from bodhi and all installed packages instead of using cached results"),
+        )
+        .arg(
+            clap::Arg::with_name("query-strategy")
+                .long("query-strategy")
+                .value_name("strategy")
+                .takes_value(true)
+                .possible_values(&["full", "packages"])
+                .help("query all updates, or only updates for installed and interesting packages (default: full)"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("install-service")
+                .about("install and enable a systemd user service and timer")
+                .arg(
+                    clap::Arg::with_name("on-calendar")
+                        .long("on-calendar")
+                        .value_name("spec")
+                        .takes_value(true)
+                        .help("when to run, in systemd.time(7) calendar format (default: every 6 hours)"),
+                )
+                .arg(
+                    clap::Arg::with_name("uninstall")
+                        .long("uninstall")
+                        .conflicts_with("on-calendar")
+                        .help("disable and remove the systemd user service and timer"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("install-autostart")
+                .about("install an autostart entry that runs a check after logging in")
+                .arg(
+                    clap::Arg::with_name("delay")
+                        .long("delay")
+                        .value_name("seconds")
+                        .takes_value(true)
+                        .help("how long to wait after logging in (default: 120 seconds)"),
+                )
+                .arg(
+                    clap::Arg::with_name("uninstall")
+                        .long("uninstall")
+                        .conflicts_with("delay")
+                        .help("remove the autostart entry"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("show")
+                .about("show details about an update")
+                .arg(
+                    clap::Arg::with_name("alias")
+                        .required(true)
+                        .help("update alias, i.e. FEDORA-2021-0123456789"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("install")
+                .about("install an update from updates-testing")
+                .arg(
+                    clap::Arg::with_name("alias")
+                        .required(true)
+                        .help("update alias, i.e. FEDORA-2021-0123456789"),
+                )
+                .arg(
+                    clap::Arg::with_name("yes")
+                        .long("yes")
+                        .short("y")
+                        .help("don't ask for confirmation"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("stage")
+                .about("download updates from updates-testing and install them on the next reboot")
+                .arg(
+                    clap::Arg::with_name("alias")
+                        .required(true)
+                        .multiple(true)
+                        .help("update aliases, i.e. FEDORA-2021-0123456789"),
+                ),
+        )
+        .subcommand(clap::SubCommand::with_name("init").about("create the configuration file interactively"))
+        .subcommand(clap::SubCommand::with_name("doctor").about("check whether everything is set up correctly"))
+        .subcommand(
+            clap::SubCommand::with_name("login").about("log in to bodhi and store the login token in the keyring"),
+        )
+        .subcommand(clap::SubCommand::with_name("logout").about("remove the login token from the keyring"))
+        .subcommand(
+            clap::SubCommand::with_name("daemon")
+                .about("check for updates periodically, and provide a DBus interface for desktop integrations")
+                .arg(
+                    clap::Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("hours")
+                        .takes_value(true)
+                        .help("time between checks (default: 6)"),
+                )
+                .arg(
+                    clap::Arg::with_name("tray")
+                        .long("tray")
+                        .help("show a status icon with the number of updates that are ready for feedback"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("completions")
+                .about("print shell completions")
+                .arg(
+                    clap::Arg::with_name("shell")
+                        .required(true)
+                        .possible_values(&["bash", "zsh", "fish"])
+                        .help("shell to print completions for"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("complete-aliases")
+                .setting(clap::AppSettings::Hidden)
+                .about("print aliases of cached updates, for shell completions"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("list")
+                .about("print updates without sending notifications or writing any state")
+                .arg(
+                    clap::Arg::with_name("installed")
+                        .long("installed")
+                        .conflicts_with_all(&["interesting", "all"])
+                        .help("only list installed updates that are ready for feedback"),
+                )
+                .arg(
+                    clap::Arg::with_name("interesting")
+                        .long("interesting")
+                        .conflicts_with("all")
+                        .help("only list updates for interesting packages"),
+                )
+                .arg(
+                    clap::Arg::with_name("all")
+                        .long("all")
+                        .help("list both installed and interesting updates (default)"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("open")
+                .about("open the bodhi pages of updates that are ready for feedback in the browser")
+                .arg(
+                    clap::Arg::with_name("alias")
+                        .takes_value(true)
+                        .conflicts_with("all")
+                        .help("only open the page of this update"),
+                )
+                .arg(
+                    clap::Arg::with_name("all")
+                        .long("all")
+                        .help("open one page for every update instead of a single page with all of them"),
+                ),
+        )
+        .subcommand(clap::SubCommand::with_name("tui").about("browse updates that need feedback interactively"))
+        .subcommand(
+            clap::SubCommand::with_name("history")
+                .about("show updates that you were notified about, installed, and commented on")
+                .arg(
+                    clap::Arg::with_name("limit")
+                        .long("limit")
+                        .value_name("n")
+                        .takes_value(true)
+                        .help("number of entries to show (default: 50)"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("stats")
+                .about("show how many updates you were notified about, tested, and commented on per month"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("report")
+                .about("write updates that need feedback to a static HTML page or feed")
+                .arg(
+                    clap::Arg::with_name("format")
+                        .long("format")
+                        .value_name("format")
+                        .takes_value(true)
+                        .possible_values(&["html", "rss"])
+                        .help("report format, \"rss\" is an Atom feed (default: html)"),
+                )
+                .arg(
+                    clap::Arg::with_name("out")
+                        .long("out")
+                        .value_name("path")
+                        .takes_value(true)
+                        .required(true)
+                        .help("file to write the report to"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("extend-override")
+                .about("extend the expiration date of a buildroot override")
+                .arg(
+                    clap::Arg::with_name("nvr")
+                        .required(true)
+                        .help("NVR of the build, i.e. foo-1.0-1.fc39"),
+                )
+                .arg(
+                    clap::Arg::with_name("days")
+                        .long("days")
+                        .value_name("days")
+                        .takes_value(true)
+                        .help("number of days from now when the override expires (default: 7)"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("comment")
+                .about("submit feedback for an update")
+                .arg(
+                    clap::Arg::with_name("alias")
+                        .required(true)
+                        .help("update alias, i.e. FEDORA-2021-0123456789"),
+                )
+                .arg(
+                    clap::Arg::with_name("text")
+                        .long("text")
+                        .value_name("text")
+                        .takes_value(true)
+                        .help("comment text"),
+                )
+                .arg(
+                    clap::Arg::with_name("karma")
+                        .long("karma")
+                        .value_name("karma")
+                        .takes_value(true)
+                        .allow_hyphen_values(true)
+                        .possible_values(&["+1", "0", "-1"])
+                        .help("karma for the update (default: 0)"),
+                )
+                .arg(
+                    clap::Arg::with_name("testcase")
+                        .long("testcase")
+                        .value_name("name=karma")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .allow_hyphen_values(true)
+                        .help("feedback for a test case, i.e. \"QA:Testcase_foo=+1\""),
+                ),
+        )
         .about(
             r#"
 If no arguments are specified on the command line, they will be read
-from ~/.config/fedora.toml.
+from ~/.config/fedora.toml. Run "fedora-update-notifier init" to create it.
 
 This config file is expected to be in this format:
 
     [FAS]
     username = "FAS_USERNAME"
+    # optional: updates that other users of this machine (or your other accounts)
+    # commented on don't need feedback either
+    # usernames = ["OTHER_USERNAME"]
 
     [fedora-update-notifier]
     interests = ["package1", "package2"]
+
+    # alternatively: also watch packages of maintainers or "@group"s
+    # or packages in comps groups
+    # interests = { packages = ["package1"], maintainers = ["user", "@group"], groups = ["group"] }
+    # or all packages that were explicitly installed
+    # interests = { auto = "userinstalled" }
+
+    # optional: use another bodhi instance, i.e. staging
+    # bodhi_url = "https://bodhi.stg.fedoraproject.org"
+
+    # optional: send requests through a proxy (instead of $https_proxy or $all_proxy),
+    # and trust additional CA certificates (PEM), i.e. in corporate networks
+    # proxy = "http://proxy.example.com:3128"
+    # ca_bundle = "/etc/pki/ca-trust/source/anchors/corporate.pem"
+
+    # optional: retry failed bodhi queries
+    retries = 3
+    backoff = 2
+
+    # optional: abort requests that take longer than this (in seconds), and stop
+    # dnf and rpm if they take longer than this (i.e. waiting for the rpm database lock)
+    http_timeout = 60
+    command_timeout = 300
+
+    # optional: wait this long (in seconds) for other transactions to release
+    # the lock of the rpm database before giving up
+    lock_wait = 600
+
+    # optional: only query updates which changed since the last run,
+    # and do a full refresh every 24 hours
+    incremental = true
+    refresh = 24
+
+    # optional: only query installed packages if the rpm database changed since the last run
+    cache_installed = true
+
+    # optional: query all updates in testing ("full"), or only updates for installed
+    # and interesting packages ("packages")
+    query_strategy = "packages"
+
+    # optional: skip updates which don't need any more feedback
+    skip_stable = false
+    skip_failing = false
+
+    # optional: only show updates for packages on the critical path
+    critpath_only = false
+
+    # optional: only show updates of some types, or with a minimum severity
+    # (types: "bugfix", "enhancement", "newpackage", "security", "unspecified")
+    # (severities: "unspecified", "low", "medium", "high", "urgent")
+    # types = ["security", "bugfix"]
+    # min_severity = "high"
+
+    # optional: show updates again after commenting on them, if the comments
+    # didn't have any karma, or if builds were changed after the last comment
+    renotify = ["new-builds"]
+
+    # optional: skip updates submitted by these users (in addition to your own),
+    # and updates for packages that you (co-)maintain in dist-git
+    # excluded_authors = ["releng", "packagerbot"]
+    skip_maintained = false
+
+    # optional: only ask for feedback for builds that were installed from updates-testing
+    check_repo = true
+
+    # optional: compare matched updates with the advisories in the local
+    # updates-testing metadata, and warn about discrepancies
+    cross_check = false
+
+    # optional: warn about installed builds from updates-testing that will never
+    # be pushed to stable, because their update was unpushed or obsoleted
+    check_orphans = true
+
+    # optional: only ask for feedback after updates have been installed
+    # for some days, and the system was booted some times
+    soak_days = 0
+    soak_boots = 0
+
+    # optional: only show updates which have been in testing for some days,
+    # and sort updates by days in testing ("priority", "age", or "needs-karma")
+    # min_age = 3
+    # max_age = 21
+    sort = "priority"
+
+    # optional: warn about buildroot overrides which expire within some days (with --mine)
+    override_warning_days = 2
+
+    # optional: notify when the running release reaches its end of life within
+    # some days, or when a new stable release is available
+    release_notices = true
+    eol_warning_days = 30
+
+    # optional: only show updates which fix bugs that you reported or are CC'd on
+    my_bugs = false
+    bugzilla_email = "BUGZILLA_EMAIL"
+    bugzilla_api_key = "BUGZILLA_API_KEY"
+
+    # optional: show changelog entries of interesting updates
+    changelog = false
+
+    # optional: notify about completed koji builds of interesting packages or your own packages
+    watch_builds = false
+
+    # optional: flag interesting updates for packages whose continuous rebuilds in koschei are failing
+    koschei = false
+
+    # optional: send one notification per category of updates (security, desktop,
+    # command-line tools, or libraries, based on comps groups) instead of one for all
+    group_notifications = false
+
+    # optional: send at most some notifications per run (the rest are combined into one)
+    # or per day (the rest are held back until the next day), and wait between notifications
+    # max_notifications_per_run = 3
+    # max_notifications_per_day = 10
+    notification_interval = 1
+
+    # optional: hold back notifications during this time of day, and deliver them later
+    # quiet_hours = "22:00-07:00"
+
+    # optional: postpone checks on metered network connections,
+    # or while running on battery with less than this charge (in percent)
+    skip_metered = true
+    # min_battery = 20
+
+    # optional: write metrics for the node_exporter textfile collector
+    # metrics_file = "/var/lib/node_exporter/textfile_collector/fedora-update-notifier.prom"
+
+    # optional: also check packages installed in local mock chroots
+    # scan_mock = true
+
+    # optional: how to send notifications
+    # ("desktop", "stdout", "null", "matrix", or "webhook")
+    notifier = "desktop"
+
+    # optional: icons and urgencies ("low", "normal", or "critical") of desktop
+    # notifications, by update severity or type, "alert", or "default", and
+    # whether they expire after some seconds (0 for never) or stay until dismissed
+    [fedora-update-notifier.styles]
+    security = { icon = "security-high", urgency = "critical", resident = true }
+    enhancement = { icon = "software-update-available", urgency = "low", timeout = 10 }
+
+    # optional: settings for sending notifications to a matrix room
+    [fedora-update-notifier.matrix]
+    homeserver = "https://matrix.org"
+    access_token = "ACCESS_TOKEN"
+    room = "!ROOM_ID:matrix.org"
+
+    # optional: settings for sending notifications to a webhook
+    # (template is one of "json", "ntfy", or "gotify")
+    [fedora-update-notifier.webhook]
+    url = "https://ntfy.sh/TOPIC"
+    template = "ntfy"
+
+    # optional: also check packages installed on remote hosts over SSH
+    [[fedora-update-notifier.hosts]]
+    host = "user@server.example.com"
+    name = "server"
+    port = 22
+
+    # optional: automatically comment on updates after the soak period
+    # (placeholders: {alias}, {days}, {boots}, {release}, {desktop}, {arch}, {kernel})
+    [fedora-update-notifier.auto_comment]
+    template = "Installed and running for {days} days on Fedora {release} with no issues."
+    karma = 0
+
+    # optional: templates (in jinja2 syntax) for notifications, terminal output, and bodhi URLs
+    [fedora-update-notifier.templates]
+    summary = "{{ summary }}"
+    body = "{% for update in updates %}{{ update.alias }}: {{ update.karma }} karma\n{% endfor %}"
+    feedback_url = "{{ url }}"
+
+    # optional: settings that replace the ones above with --profile laptop
+    [fedora-update-notifier.profile.laptop]
+    interests = ["package1"]
+    notifier = "desktop"
 "#,
         );
 
-    let matches = app.get_matches();
+    let matches = match app.clone().get_matches_safe() {
+        Ok(matches) => matches,
+        // --help and --version are not errors
+        Err(error) if !error.use_stderr() => error.exit(),
+        Err(error) => {
+            eprintln!("{}", error.message);
+            std::process::exit(journal::EXIT_USAGE);
+        },
+    };
 
-    let config = get_config();
+    let verbosity = if matches.is_present("quiet") {
+        -1
+    } else {
+        matches.occurrences_of("verbose") as i64
+    };
+    journal::init_logging(verbosity);
+    progress::init(verbosity < 0);
 
-    let mut username: Option<String> = None;
-    let mut interests: Option<Vec<String>> = None;
+    let profile = match matches.value_of("profile") {
+        Some(profile) => Some(profile.to_owned()),
+        None => std::env::var(format!("{}PROFILE", ENV_PREFIX)).ok(),
+    };
+    let profile = profile.as_deref();
 
-    if let Ok(config) = config {
-        username = Some(config.fas.username);
-        interests = Some(config.fedora_update_notifier.interests)
+    if let Some(matches) = matches.subcommand_matches("completions") {
+        completions::print(app, matches.value_of("shell").unwrap_or_default())?;
+        return Ok(Outcome::default());
     }
 
-    let cli_username = matches.value_of("username");
-    let cli_interests: Option<Vec<&str>> = matches.values_of("interests").map(|values| values.collect());
-
-    if let Some(cli_username) = cli_username {
-        username = Some(cli_username.to_owned());
+    // this is run by the shell while typing, so errors are not printed
+    if matches.subcommand_matches("complete-aliases").is_some() {
+        for alias in Cache::open_read_only()
+            .and_then(|cache| cache.aliases())
+            .unwrap_or_default()
+        {
+            println!("{}", alias);
+        }
+        return Ok(Outcome::default());
     }
 
-    if let Some(cli_interests) = cli_interests {
-        let mut strings: Vec<String> = cli_interests.into_iter().map(|e| e.to_owned()).collect();
+    if let Some(history) = matches.subcommand_matches("history") {
+        let limit = match history.value_of("limit").unwrap_or("50").parse::<u32>() {
+            Ok(limit) => limit,
+            Err(error) => return Err(Failure::usage(format!("Invalid limit: {}", error))),
+        };
 
-        // don't override interests, but append
-        match &mut interests {
-            None => interests = Some(strings),
-            Some(nonempty) => nonempty.append(&mut strings),
+        let entries = Cache::open_read_only()?.history(limit)?;
+        match Format::parse(matches.value_of("output").unwrap_or("text"))? {
+            Format::Text => output::print_history(&entries),
+            Format::Json => output::print_json_history(&entries)?,
         }
+        return Ok(Outcome::default());
     }
 
-    let username = match username {
-        Some(username) => username,
-        None => {
-            return Err(String::from("No FAS username was specified."));
-        },
-    };
+    if matches.subcommand_matches("stats").is_some() {
+        let stats = Cache::open_read_only()?.monthly_stats()?;
+        match Format::parse(matches.value_of("output").unwrap_or("text"))? {
+            Format::Text => output::print_stats(&stats),
+            Format::Json => output::print_json_stats(&stats)?,
+        }
+        return Ok(Outcome::default());
+    }
 
-    let interests = match interests {
-        Some(interests) => interests,
-        None => {
-            return Err(String::from("No interests were specified."));
-        },
+    // the proxy and CA bundle apply to all HTTP requests, including the connectivity check of "doctor"
+    let (proxy, ca_bundle) = match get_config(profile) {
+        Ok(config) => (
+            config.fedora_update_notifier.proxy,
+            config.fedora_update_notifier.ca_bundle,
+        ),
+        Err(_) => (None, None),
     };
+    http::configure(proxy.as_deref(), ca_bundle.as_deref()).map_err(Failure::config)?;
 
-    // query rpm for current release
-    let release = get_release()?;
+    if matches.subcommand_matches("init").is_some() {
+        init::init()?;
+        return Ok(Outcome::default());
+    }
 
-    // query dnf for installed packages
-    let output = match Command::new("dnf")
-        .arg("--quiet")
-        .arg("repoquery")
-        .arg("--cacheonly")
-        .arg("--installed")
-        .arg("--source")
-        .output()
-    {
-        Ok(output) => output,
-        Err(error) => {
-            return Err(format!("{}", error));
-        },
-    };
+    if matches.subcommand_matches("doctor").is_some() {
+        doctor::doctor(profile).await?;
+        return Ok(Outcome::default());
+    }
 
-    match output.status.code() {
-        Some(x) if x != 0 => {
-            return Err(String::from("Failed to query dnf."));
-        },
-        Some(_) => {},
-        None => {
-            return Err(String::from("Failed to query dnf."));
-        },
+    // installing updates doesn't need any configuration
+    if let Some(matches) = matches.subcommand_matches("install") {
+        dnf::upgrade_advisory(matches.value_of("alias").unwrap_or_default(), matches.is_present("yes"))?;
+        return Ok(Outcome::default());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("stage") {
+        let aliases: Vec<&str> = matches.values_of("alias").into_iter().flatten().collect();
+
+        // only one repository can stay enabled until the staged updates are installed
+        let repo = aliases
+            .first()
+            .map(|alias| dnf::testing_repo(alias))
+            .unwrap_or(dnf::TESTING_REPO);
+        if aliases.iter().any(|alias| dnf::testing_repo(alias) != repo) {
+            return Err(Failure::usage(String::from(
+                "Fedora and EPEL updates can't be staged at the same time.",
+            )));
+        }
+
+        let mut package_ids = Vec::new();
+        for alias in aliases {
+            package_ids.extend(dnf::advisory_package_ids(alias)?);
+        }
+
+        PackageKit::new()?.stage(&package_ids, repo)?;
+        println!("The updates will be installed on the next reboot.");
+        return Ok(Outcome::default());
+    }
+
+    // checks are run in separate processes, which take the lock themselves
+    if let Some(matches) = matches.subcommand_matches("daemon") {
+        let interval = match matches.value_of("interval").map(str::parse::<u64>) {
+            Some(Ok(hours)) if hours > 0 => hours,
+            Some(_) => {
+                return Err(Failure::usage(String::from("Invalid interval.")));
+            },
+            None => daemon::DEFAULT_INTERVAL,
+        };
+
+        let interval = Duration::from_secs(interval * 60 * 60);
+        daemon::run(interval, profile, matches.is_present("tray")).await?;
+        return Ok(Outcome::default());
+    }
+
+    // everything below can write state or send notifications
+    let _lock = lock::acquire(matches.is_present("wait")).map_err(Failure::busy)?;
+
+    let dry_run = matches.is_present("dry-run");
+    // listing updates doesn't send notifications or write any state either
+    let list = matches.subcommand_matches("list");
+    let open = matches.subcommand_matches("open");
+    let read_only = dry_run || list.is_some() || open.is_some();
+
+    if !dry_run {
+        if let Err(error) = packagekit::restore_testing_repo() {
+            warn!("{}", error);
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("install-service") {
+        if matches.is_present("uninstall") {
+            systemd::uninstall_service()?;
+        } else {
+            systemd::install_service(matches.value_of("on-calendar").unwrap_or(systemd::DEFAULT_ON_CALENDAR))?;
+        }
+        return Ok(Outcome::default());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("install-autostart") {
+        if matches.is_present("uninstall") {
+            autostart::uninstall_autostart()?;
+        } else {
+            let delay = match matches.value_of("delay").map(str::parse) {
+                Some(Ok(delay)) => delay,
+                Some(Err(_)) => return Err(Failure::usage(String::from("Invalid delay."))),
+                None => autostart::DEFAULT_DELAY,
+            };
+            autostart::install_autostart(delay)?;
+        }
+        return Ok(Outcome::default());
+    }
+
+    // everything can also be specified on the command line without a configuration file, except for profiles
+    let config = match get_config(profile) {
+        Err(error) if profile.is_some() => return Err(Failure::config(error)),
+        config => config,
     };
 
-    let installed = match std::str::from_utf8(&output.stdout) {
-        Ok(result) => result,
-        Err(error) => {
-            return Err(format!("{}", error));
-        },
+    let mut username: Option<String> = None;
+    let mut other_usernames: Vec<String> = Vec::new();
+    let mut interests: Option<Interests> = None;
+    let mut policy = RetryPolicy::default();
+    let mut incremental = true;
+    let mut cache_installed = true;
+    let mut refresh: u64 = 24;
+    let mut strategy = QueryStrategy::Full;
+    // bodhi resets karma when builds are changed, so feedback is needed again
+    let mut filters = Filters {
+        renotify: vec![Renotify::NewBuilds],
+        ..Default::default()
+    };
+    let mut sort = SortOrder::Priority;
+    let mut check_repo = true;
+    let mut skip_maintained = false;
+    let mut cross_check = false;
+    let mut check_orphans = true;
+    let mut scan_toolboxes = false;
+    let mut scan_mock = false;
+    let mut hosts: Vec<HostConfig> = Vec::new();
+    let mut soak = Soak::default();
+    let mut auto_comment: Option<AutoCommentConfig> = None;
+    let mut templates = Templates::new(TemplateConfig::default())?;
+    let mut group_notifications = false;
+    // the desktop notification server replaces notifications that are sent right after each other
+    let mut rate_limit = RateLimit {
+        interval: Duration::from_secs(1),
+        ..Default::default()
     };
+    let mut quiet_hours: Option<QuietHours> = None;
+    let mut skip_metered = true;
+    let mut min_battery: Option<f64> = None;
+    let mut override_warning_days = overrides::DEFAULT_WARNING_DAYS;
+    let mut release_notices = true;
+    let mut eol_warning_days = releases::DEFAULT_EOL_WARNING_DAYS;
+    let mut my_bugs = false;
+    let mut bugzilla_email: Option<String> = None;
+    let mut bugzilla_api_key: Option<String> = None;
+    let mut changelog = false;
+    let mut watch_builds = false;
+    let mut koschei = false;
+    let mut metrics_file: Option<PathBuf> = None;
+    let mut notifier_name = String::from("desktop");
+    let mut notifier_config = NotifierConfig::default();
+
+    if let Ok(config) = config {
+        username = Some(config.fas.username);
+        other_usernames = config.fas.usernames;
+        interests = Some(Interests::from(config.fedora_update_notifier.interests));
 
-    let lines: Vec<&str> = installed.trim().split('\n').collect();
+        if let Some(url) = &config.fedora_update_notifier.bodhi_url {
+            source::set_bodhi_url(url);
+        }
 
-    let mut packages: Vec<NVR> = Vec::new();
-    for line in lines {
-        let (n, _, v, r, _) = parse_filename(line)?;
-        packages.push(NVR { n, v, r });
+        if let Some(retries) = config.fedora_update_notifier.retries {
+            policy.attempts = retries;
+        }
+        if let Some(backoff) = config.fedora_update_notifier.backoff {
+            policy.backoff = Duration::from_secs(backoff);
+        }
+        if let Some(value) = config.fedora_update_notifier.http_timeout {
+            policy.timeout = Duration::from_secs(value);
+        }
+        if let Some(value) = config.fedora_update_notifier.command_timeout {
+            subprocess::set_timeout(Duration::from_secs(value));
+        }
+        if let Some(value) = config.fedora_update_notifier.lock_wait {
+            dnf::set_lock_wait(Duration::from_secs(value));
+        }
+        if let Some(value) = config.fedora_update_notifier.incremental {
+            incremental = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.cache_installed {
+            cache_installed = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.refresh {
+            refresh = value;
+        }
+        if let Some(value) = &config.fedora_update_notifier.query_strategy {
+            strategy = QueryStrategy::parse(value)?;
+        }
+        if let Some(value) = config.fedora_update_notifier.skip_stable {
+            filters.skip_stable = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.skip_failing {
+            filters.skip_failing = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.critpath_only {
+            filters.critpath_only = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.types {
+            filters.types = report::parse_types(&value.join(","))?;
+        }
+        if let Some(value) = &config.fedora_update_notifier.min_severity {
+            filters.min_severity = Some(Severity::parse(value)?);
+        }
+        filters.min_age = config.fedora_update_notifier.min_age;
+        filters.max_age = config.fedora_update_notifier.max_age;
+        if let Some(value) = config.fedora_update_notifier.renotify {
+            filters.renotify = report::parse_renotify(&value.join(","))?;
+        }
+        if let Some(value) = config.fedora_update_notifier.excluded_authors {
+            filters.excluded_authors = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.skip_maintained {
+            skip_maintained = value;
+        }
+        if let Some(value) = &config.fedora_update_notifier.sort {
+            sort = SortOrder::parse(value)?;
+        }
+        if let Some(value) = config.fedora_update_notifier.check_repo {
+            check_repo = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.cross_check {
+            cross_check = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.check_orphans {
+            check_orphans = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.soak_days {
+            soak.days = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.soak_boots {
+            soak.boots = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.auto_comment {
+            value.validate()?;
+            auto_comment = Some(value);
+        }
+        if let Some(value) = config.fedora_update_notifier.templates {
+            templates = Templates::new(value)?;
+        }
+        if let Some(value) = config.fedora_update_notifier.group_notifications {
+            group_notifications = value;
+        }
+        rate_limit.per_run = config.fedora_update_notifier.max_notifications_per_run;
+        rate_limit.per_day = config.fedora_update_notifier.max_notifications_per_day;
+        if let Some(value) = config.fedora_update_notifier.notification_interval {
+            rate_limit.interval = Duration::from_secs(value);
+        }
+        if let Some(value) = config.fedora_update_notifier.quiet_hours {
+            quiet_hours = Some(QuietHours::parse(&value)?);
+        }
+        if let Some(value) = config.fedora_update_notifier.skip_metered {
+            skip_metered = value;
+        }
+        min_battery = config.fedora_update_notifier.min_battery;
+        if let Some(value) = config.fedora_update_notifier.scan_toolboxes {
+            scan_toolboxes = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.scan_mock {
+            scan_mock = value;
+        }
+        hosts = config.fedora_update_notifier.hosts;
+        if let Some(value) = config.fedora_update_notifier.override_warning_days {
+            override_warning_days = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.release_notices {
+            release_notices = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.eol_warning_days {
+            eol_warning_days = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.my_bugs {
+            my_bugs = value;
+        }
+        bugzilla_email = config.fedora_update_notifier.bugzilla_email;
+        bugzilla_api_key = config.fedora_update_notifier.bugzilla_api_key;
+        if let Some(value) = config.fedora_update_notifier.changelog {
+            changelog = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.watch_builds {
+            watch_builds = value;
+        }
+        if let Some(value) = config.fedora_update_notifier.koschei {
+            koschei = value;
+        }
+        metrics_file = config.fedora_update_notifier.metrics_file;
+        if let Some(value) = config.fedora_update_notifier.notifier {
+            notifier_name = value;
+        }
+        notifier_config = config.fedora_update_notifier.notifiers;
     }
 
-    // query bodhi for packages in updates-testing
-    let bodhi = match BodhiClientBuilder::default().build().await {
-        Ok(bodhi) => bodhi,
-        Err(error) => {
-            return Err(format!("{}", error));
+    let format = Format::parse(matches.value_of("output").unwrap_or("text"))?;
+    notifier::print_to_stderr(format == Format::Json);
+
+    if matches.is_present("skip-stable") {
+        filters.skip_stable = true;
+    }
+
+    if matches.is_present("skip-failing") {
+        filters.skip_failing = true;
+    }
+
+    if matches.is_present("critpath-only") {
+        filters.critpath_only = true;
+    }
+
+    if let Some(value) = matches.value_of("types") {
+        filters.types = report::parse_types(value)?;
+    }
+
+    if let Some(value) = matches.value_of("renotify") {
+        filters.renotify = report::parse_renotify(value)?;
+    }
+
+    if let Some(value) = matches.value_of("min-severity") {
+        filters.min_severity = Some(Severity::parse(value)?);
+    }
+
+    if let Some(value) = matches.value_of("min-age") {
+        filters.min_age = match value.parse() {
+            Ok(days) => Some(days),
+            Err(_) => {
+                return Err(Failure::usage(format!("Invalid minimum age: {}", value)));
+            },
+        };
+    }
+
+    if let Some(value) = matches.value_of("max-age") {
+        filters.max_age = match value.parse() {
+            Ok(days) => Some(days),
+            Err(_) => {
+                return Err(Failure::usage(format!("Invalid maximum age: {}", value)));
+            },
+        };
+    }
+
+    if let Some(value) = matches.value_of("sort") {
+        sort = SortOrder::parse(value)?;
+    }
+
+    if matches.is_present("needs-karma") {
+        filters.needs_karma = true;
+        sort = SortOrder::NeedsKarma;
+    }
+
+    if matches.is_present("my-bugs") {
+        my_bugs = true;
+    }
+
+    if matches.is_present("scan-toolboxes") {
+        scan_toolboxes = true;
+    }
+
+    if matches.is_present("scan-mock") {
+        scan_mock = true;
+    }
+
+    if let Some(value) = matches.value_of("metrics-file") {
+        metrics_file = Some(PathBuf::from(value));
+    }
+
+    if matches.is_present("changelog") {
+        changelog = true;
+    }
+
+    if matches.is_present("watch-builds") {
+        watch_builds = true;
+    }
+
+    if matches.is_present("koschei") {
+        koschei = true;
+    }
+
+    if matches.is_present("allow-metered") {
+        skip_metered = false;
+    }
+
+    if matches.is_present("allow-battery") {
+        min_battery = None;
+    }
+
+    if let Some(value) = matches.value_of("notifier") {
+        notifier_name = value.to_owned();
+    }
+    if matches.is_present("no-notify") {
+        notifier_name = String::from("null");
+    }
+    if matches.is_present("group-notifications") {
+        group_notifications = true;
+    }
+    let mut notifier = notifier::from_name(&notifier_name, &notifier_config)?;
+    if dry_run {
+        notifier = Box::new(DryRunNotifier::new(&notifier_name));
+    } else if notifier_name != "stdout" && notifier_name != "null" {
+        // notifications printed to the terminal were asked for, and don't interrupt anything
+        let desktop = notifier_name == "desktop";
+        notifier = Box::new(RateLimitingNotifier::new(notifier, rate_limit));
+        // notifications over the daily limit are delivered like notifications that were held back
+        if quiet_hours.is_some() || desktop || rate_limit.per_day.is_some() {
+            notifier = Box::new(DeferringNotifier::new(notifier, quiet_hours, desktop));
+        }
+    }
+    if templates.has_notification() {
+        notifier = Box::new(TemplateNotifier::new(notifier, templates.clone()));
+    }
+
+    if matches.is_present("full-refresh") {
+        incremental = false;
+        cache_installed = false;
+    }
+
+    if let Some(value) = matches.value_of("query-strategy") {
+        strategy = QueryStrategy::parse(value)?;
+    }
+
+    if let Some(retries) = matches.value_of("retries") {
+        policy.attempts = match retries.parse() {
+            Ok(retries) => retries,
+            Err(_) => {
+                return Err(Failure::usage(format!("Invalid number of retries: {}", retries)));
+            },
+        };
+    }
+
+    if let Some(backoff) = matches.value_of("backoff") {
+        policy.backoff = match backoff.parse() {
+            Ok(backoff) => Duration::from_secs(backoff),
+            Err(_) => {
+                return Err(Failure::usage(format!("Invalid retry backoff: {}", backoff)));
+            },
+        };
+    }
+
+    let cli_username = matches.value_of("username");
+    let cli_interests: Option<Vec<&str>> = matches.values_of("interests").map(|values| values.collect());
+
+    if let Some(cli_username) = cli_username {
+        username = Some(cli_username.to_owned());
+    }
+
+    if let Some(cli_interests) = cli_interests {
+        let mut strings: Vec<String> = cli_interests.into_iter().map(|e| e.to_owned()).collect();
+
+        // don't override interests, but append
+        match &mut interests {
+            None => {
+                interests = Some(Interests {
+                    packages: strings,
+                    ..Default::default()
+                })
+            },
+            Some(nonempty) => nonempty.packages.append(&mut strings),
+        }
+    }
+
+    let username = match username {
+        Some(username) => username,
+        None => {
+            return Err(Failure::config(String::from("No FAS username was specified.")));
         },
     };
 
-    let releases = vec![release.clone()];
+    // feedback is submitted as the first user, comments of any of them count
+    let mut usernames = vec![username.clone()];
+    for other in other_usernames {
+        if !usernames.contains(&other) {
+            usernames.push(other);
+        }
+    }
+
+    match matches.subcommand() {
+        ("show", Some(matches)) => {
+            let alias = matches.value_of("alias").unwrap_or_default();
 
-    let query = bodhi::query::UpdateQuery::new()
-        .releases(&releases)
-        .content_type(ContentType::RPM)
-        .status(UpdateStatus::Testing);
+            let backend: Box<dyn PackageBackend> = Box::new(FallbackBackend::default());
+            let source = BodhiSource::new(policy)?;
 
-    let updates = match bodhi.paginated_request(&query).await {
-        Ok(updates) => updates,
-        Err(error) => {
-            return Err(format!("{}", error));
+            let (packages, update) = tokio::join!(
+                tokio::task::spawn_blocking(move || backend.installed()),
+                source.update(alias),
+            );
+
+            let packages = match packages {
+                Ok(packages) => packages?,
+                Err(error) => {
+                    return Err(Failure::from(format!("{}", error)));
+                },
+            };
+            let update = update?;
+
+            let summary = UpdateSummary::new(&Evaluation::new(&update, &packages)?);
+
+            match format {
+                Format::Text => output::print_update(&summary),
+                Format::Json => output::print_json_update(&summary)?,
+            }
+            return Ok(Outcome::default());
         },
-    };
+        ("login", Some(_)) => {
+            Authenticator::new(&username)?.login().await?;
+            return Ok(Outcome::default());
+        },
+        ("logout", Some(_)) => {
+            Authenticator::new(&username)?.logout()?;
+            return Ok(Outcome::default());
+        },
+        ("extend-override", Some(matches)) => {
+            let nvr = matches.value_of("nvr").unwrap_or_default();
+            let days = match matches.value_of("days").map(str::parse) {
+                Some(Ok(days)) => days,
+                Some(Err(_)) => {
+                    return Err(Failure::usage(String::from("Invalid number of days.")));
+                },
+                None => overrides::DEFAULT_EXTEND_DAYS,
+            };
 
-    // filter out updates created by the current user
-    let updates: Vec<Update> = updates
-        .into_iter()
-        .filter(|update| update.user.name != username)
-        .collect();
+            let access_token = Authenticator::new(&username)?.access_token().await?;
+            let expiration_date = overrides::extend(&access_token, nvr, days).await?;
+
+            println!("Extended buildroot override for {} until {}.", nvr, expiration_date);
+            return Ok(Outcome::default());
+        },
+        ("comment", Some(matches)) => {
+            let alias = matches.value_of("alias").unwrap_or_default();
 
-    // filter out updates that were already commented on
-    let mut relevant_updates: Vec<&Update> = Vec::new();
-    for update in &updates {
-        if let Some(comments) = &update.comments {
-            let mut commented = false;
+            let mut feedback = Feedback {
+                text: matches.value_of("text").unwrap_or_default().to_owned(),
+                karma: feedback::parse_karma(matches.value_of("karma").unwrap_or("0"))?,
+                testcases: Vec::new(),
+            };
 
-            for comment in comments {
-                if comment.user.name == username {
-                    commented = true;
+            if let Some(testcases) = matches.values_of("testcase") {
+                for testcase in testcases {
+                    feedback.testcases.push(feedback::parse_testcase(testcase)?);
                 }
             }
 
-            if !commented {
-                relevant_updates.push(update);
+            // refreshing the login fails as well if bodhi is not reachable
+            let submitted = match Authenticator::new(&username)?.access_token().await {
+                Ok(access_token) => match feedback::submit(&access_token, alias, &feedback).await {
+                    Ok(()) => Ok(()),
+                    Err(SubmitError::Unavailable(error)) => Err(error),
+                    Err(error) => return Err(Failure::from(error.to_string())),
+                },
+                Err(error) => {
+                    if is_online(policy).await? {
+                        return Err(Failure::from(error));
+                    }
+                    Err(error)
+                },
+            };
+
+            match submitted {
+                Ok(()) => println!("Submitted feedback for {}.", alias),
+                Err(error) => {
+                    debug!("{}", error);
+                    feedback::queue(alias, &feedback)?;
+                    println!(
+                        "Bodhi is not reachable, the feedback for {} will be submitted by the next run.",
+                        alias
+                    );
+                },
             }
-        } else {
-            relevant_updates.push(update);
+            return Ok(Outcome::default());
+        },
+        _ => {},
+    }
+
+    // timer-driven runs on laptops often happen before the network is up, which is not an error
+    if !is_online(policy).await? {
+        journal::status("Postponed: bodhi is not reachable");
+        journal::info("Skipped checking for updates: bodhi is not reachable.");
+        return Ok(Outcome {
+            offline: true,
+            ..Default::default()
+        });
+    }
+
+    // feedback that couldn't be submitted earlier is submitted before anything else is queried
+    if !read_only {
+        if let Err(error) = feedback::submit_queued(&username, notifier.as_ref()).await {
+            warn!("Failed to submit queued feedback: {}", error);
         }
     }
 
-    // filter out updates for packages that are not installed
-    let mut installed_updates: Vec<&Update> = Vec::new();
-    for update in &relevant_updates {
-        let mut nvrs: Vec<NVR> = Vec::new();
+    // querying bodhi can download many megabytes, so this waits for the next run (interactive use is not affected)
+    if matches.subcommand_matches("tui").is_none() && list.is_none() && open.is_none() {
+        if skip_metered && power::is_metered() {
+            journal::status("Postponed: the network connection is metered");
+            journal::info("Skipped checking for updates: the network connection is metered.");
+            return Ok(Outcome::default());
+        }
 
-        for build in &update.builds {
-            let (n, v, r) = parse_nvr(&build.nvr)?;
-            nvrs.push(NVR { n, v, r });
+        let battery_level = min_battery.and_then(|_| power::battery_level());
+        if let (Some(level), Some(min_battery)) = (battery_level, min_battery) {
+            if level < min_battery {
+                journal::status("Postponed: the battery level is low");
+                journal::info(&format!(
+                    "Skipped checking for updates: the battery level is {:.0}%.",
+                    level
+                ));
+                return Ok(Outcome::default());
+            }
         }
+    }
+
+    if matches.is_present("mine") {
+        journal::status("Querying your updates");
 
-        for nvr in nvrs {
-            if packages.contains(&nvr) {
-                installed_updates.push(update);
+        // also show updates which were pushed to stable recently
+        let since = chrono::Utc::now() - chrono::Duration::days(MINE_STABLE_DAYS);
+        let since = since.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+        let source = BodhiSource::new(policy)?;
+        let (updates, buildroot_overrides) =
+            futures::try_join!(source.user_updates(&username, &since), source.overrides(&username))?;
+
+        let cache = if dry_run {
+            Cache::open_read_only()
+        } else {
+            Cache::open()
+        };
+        let cache = match cache {
+            Ok(cache) => Some(cache),
+            Err(error) => {
+                warn!("Failed to open cache: {}", error);
+                None
+            },
+        };
+
+        let mut summaries: Vec<UpdateSummary> = Vec::new();
+        let mut alerts: Vec<(usize, Vec<Alert>)> = Vec::new();
+
+        for update in &updates {
+            let evaluation = Evaluation::new(update, &[])?;
+
+            // alerts are only sent once, which needs the cache to remember them
+            if let (Some(cache), "pending" | "testing") = (&cache, evaluation.status()) {
+                let mut new: Vec<Alert> = Vec::new();
+                for alert in evaluation.alerts() {
+                    match cache.is_new_alert(&update.alias, &alert.key) {
+                        Ok(true) => new.push(alert),
+                        Ok(false) => {},
+                        Err(error) => warn!("Failed to check alert for {}: {}", &update.alias, error),
+                    }
+                }
+                if !new.is_empty() {
+                    alerts.push((summaries.len(), new));
+                }
+            }
+
+            summaries.push(UpdateSummary::new(&evaluation));
+        }
+
+        // warn about overrides which are about to expire once
+        let mut expiring: Vec<(&BuildrootOverride, String)> = Vec::new();
+        if let Some(cache) = &cache {
+            for buildroot_override in &buildroot_overrides {
+                if buildroot_override
+                    .days_left()
+                    .map(|days| days > override_warning_days)
+                    .unwrap_or(true)
+                {
+                    continue;
+                }
+                let key = format!("override:{}", &buildroot_override.expiration_date);
+                match cache.is_new_alert(&buildroot_override.nvr, &key) {
+                    Ok(true) => expiring.push((buildroot_override, key)),
+                    Ok(false) => {},
+                    Err(error) => warn!("Failed to check alert for {}: {}", &buildroot_override.nvr, error),
+                }
+            }
+        }
+
+        let mine = MineSummary {
+            updates: summaries,
+            overrides: buildroot_overrides.iter().map(OverrideSummary::new).collect(),
+        };
+
+        match format {
+            Format::Text => output::print_mine(&mine, override_warning_days),
+            Format::Json => output::print_json_mine(&mine)?,
+        }
+
+        notifier.flush().await?;
+
+        for (index, new) in alerts {
+            let summary = &mine.updates[index];
+            let texts: Vec<&str> = new.iter().map(|alert| alert.text.as_str()).collect();
+            notifier
+                .notify(&Message {
+                    summary: &format!("{}: {}", &summary.alias, texts.join(", ")),
+                    url: &summary.url,
+                    updates: std::slice::from_ref(summary),
+                    urgent: true,
+                    custom_body: None,
+                })
+                .await?;
+
+            if let Some(cache) = &cache {
+                for alert in new {
+                    if let Err(error) = cache.record_alert(&summary.alias, &alert.key) {
+                        warn!("Failed to record alert for {}: {}", &summary.alias, error);
+                    }
+                }
             }
         }
+
+        for (buildroot_override, key) in expiring {
+            notifier
+                .notify(&Message {
+                    summary: &format!("Buildroot override for {} is about to expire", &buildroot_override.nvr),
+                    url: &buildroot_override.url(),
+                    updates: &[],
+                    urgent: false,
+                    custom_body: None,
+                })
+                .await?;
+
+            if let Some(cache) = &cache {
+                if let Err(error) = cache.record_alert(&buildroot_override.nvr, &key) {
+                    warn!("Failed to record alert for {}: {}", &buildroot_override.nvr, error);
+                }
+            }
+        }
+        notifier.finish().await?;
+
+        return Ok(Outcome::default());
+    }
+
+    let interests = match interests {
+        Some(interests) => interests,
+        None => {
+            return Err(Failure::config(String::from("No interests were specified.")));
+        },
+    };
+
+    journal::ready();
+
+    // query rpm for current release
+    let release = get_release()?;
+
+    if !interests.is_empty() {
+        journal::status("Resolving interesting packages");
     }
+    let interests = interests.expand(policy).await?;
+
+    if skip_maintained {
+        journal::status("Querying your packages in dist-git");
 
-    // collect relevant packages
-    let mut installed_packages: Vec<&str> = Vec::new();
-    for update in &installed_updates {
-        for build in &update.builds {
-            let (n, _, _) = parse_nvr(&build.nvr)?;
-            installed_packages.push(n);
+        let pagure = Pagure::new(policy)?;
+        for username in &usernames {
+            match pagure.packages(username).await {
+                Ok(packages) => filters.maintained.extend(packages),
+                Err(error) => warn!("Failed to query packages of {} in dist-git: {}", username, error),
+            }
         }
     }
 
-    // sort and remove duplicates
-    installed_packages.sort();
-    installed_packages.dedup_by(|a, b| a == b);
+    journal::status(&format!("Querying installed packages and updates for {}", release));
+
+    // query dnf for installed packages and bodhi for packages in updates-testing at the same time
+    let mut backend: Box<dyn PackageBackend> = Box::new(FallbackBackend::default());
+    if cache_installed {
+        backend = Box::new(CachedBackend::new(backend, read_only));
+    }
+    let mut source = BodhiSource::new(policy)?;
+    if incremental {
+        source = source.incremental(&usernames, (refresh * 60 * 60) as i64);
+    }
+    if read_only {
+        source = source.read_only();
+    }
 
-    println!();
-    if !installed_packages.is_empty() {
-        // construct update URL
-        let feedback_url = format!(
-            "https://bodhi.fedoraproject.org/updates/?release={}&status=testing&packages={}",
-            release,
-            installed_packages.join(",")
+    let started = Instant::now();
+    let (packages, mut updates) = if strategy == QueryStrategy::Full {
+        let (packages, updates) = tokio::join!(
+            query_installed(backend),
+            source
+                .testing_updates(&release)
+                .instrument(info_span!("bodhi", release = %release)),
         );
+        (packages?, updates?)
+    } else {
+        // package-filtered queries need to know installed packages first
+        let packages = query_installed(backend).await?;
+        let names = package_names(&packages, &interests);
+        debug!("Querying updates for {} packages", names.len());
+
+        let updates = source
+            .package_updates(&release, &names)
+            .instrument(info_span!("bodhi", release = %release))
+            .await?;
+        (packages, updates)
+    };
+    let query_duration = started.elapsed();
+
+    info!(
+        "Found {} installed source packages and {} updates in testing ({:.1}s)",
+        packages.len(),
+        updates.len(),
+        query_duration.as_secs_f64()
+    );
+
+    // updates that were already commented on are skipped, which needs all of their comments
+    source
+        .complete_comments(&mut updates, &package_names(&packages, &interests))
+        .await;
+
+    // module updates are only published for releases that still have modular content
+    let mut streams: Vec<NEVR> = Vec::new();
+    let mut module_updates: Vec<Update> = Vec::new();
+    if let Some(modular_release) = modules::modular_release(&release) {
+        match tokio::task::spawn_blocking(modules::installed_modules).await {
+            Ok(Ok(installed)) => streams = installed,
+            Ok(Err(error)) => warn!("Failed to query installed module streams: {}", error),
+            Err(error) => warn!("Failed to query installed module streams: {}", error),
+        }
 
-        // send notification for updates that are ready for feedback
-        Notification::new()
-            .summary("Installed updates are ready for feedback")
-            .body(&feedback_url)
-            .icon("dialog-information")
-            .show()
-            .expect("Unable to send desktop notification.");
+        if !streams.is_empty() {
+            match source.content_updates(&modular_release, "module").await {
+                Ok(updates) => {
+                    module_updates = updates
+                        .into_iter()
+                        .filter(|update| modules::is_installed_stream(update, &streams))
+                        .collect()
+                },
+                Err(error) => warn!("Failed to query module updates: {}", error),
+            }
+        }
+    }
 
-        println!("Installed updates are ready for feedback:");
-        for installed_package in installed_packages {
-            println!("- {}", installed_package);
+    let mut images: Vec<NEVR> = Vec::new();
+    let mut container_updates: Vec<Update> = Vec::new();
+    if let Some(container_release) = containers::container_release(&release) {
+        match tokio::task::spawn_blocking(containers::pulled_images).await {
+            Ok(Ok(pulled)) => images = pulled,
+            Ok(Err(error)) => warn!("Failed to query pulled container images: {}", error),
+            Err(error) => warn!("Failed to query pulled container images: {}", error),
         }
-        println!("Feedback URL: {}", &feedback_url);
+
+        if !images.is_empty() {
+            match source.content_updates(&container_release, "container").await {
+                Ok(updates) => container_updates = updates,
+                Err(error) => warn!("Failed to query container updates: {}", error),
+            }
+        }
+    }
+
+    if cross_check {
+        match tokio::task::spawn_blocking(updateinfo::local_advisories).await {
+            Ok(Ok(advisories)) => {
+                for discrepancy in updateinfo::cross_check(&advisories, &packages, &updates) {
+                    warn!("{}", discrepancy);
+                }
+            },
+            Ok(Err(error)) => warn!("Failed to query advisories from dnf: {}", error),
+            Err(error) => warn!("Failed to query advisories from dnf: {}", error),
+        }
+    }
+
+    let cache = if read_only {
+        Cache::open_read_only()
     } else {
-        println!("No updates for installed packages are waiting for feedback.");
+        Cache::open()
+    };
+    let cache = match cache {
+        Ok(cache) => Some(cache),
+        Err(error) => {
+            warn!("Failed to open cache: {}", error);
+            None
+        },
+    };
+
+    let mut report = evaluate(&packages, &updates, &usernames, &interests, &filters, cache.as_ref())?;
+
+    // module streams are evaluated separately, so they aren't mixed up with source packages of the same name
+    if !module_updates.is_empty() {
+        let modular = evaluate(
+            &streams,
+            &module_updates,
+            &usernames,
+            &interests,
+            &filters,
+            cache.as_ref(),
+        )?;
+        report.installed.extend(modular.installed);
+        report.pending.extend(modular.pending);
+        report.sort(SortOrder::Priority);
     }
 
-    if interests.is_empty() {
-        return Ok(());
+    if !container_updates.is_empty() {
+        // newer candidates for pulled images are always interesting
+        let mut image_interests = interests.clone();
+        image_interests.extend(images.iter().map(|image| image.n.clone()));
+
+        let candidates = evaluate(
+            &images,
+            &container_updates,
+            &usernames,
+            &image_interests,
+            &filters,
+            cache.as_ref(),
+        )?;
+        report.installed.extend(candidates.installed);
+        report.pending.extend(candidates.pending);
+        report.sort(SortOrder::Priority);
     }
 
-    // check if there are updates for "interesting" packages that aren't installed yet
-    let mut pending_updates: Vec<&Update> = Vec::new();
-    for update in &relevant_updates {
-        let mut pending_nvrs: Vec<NVR> = Vec::new();
+    // evaluations and comments of updates that were not queried anymore (i.e. because they were pushed to stable) are
+    // not needed
+    if let Some(cache) = &cache {
+        let aliases: HashSet<&str> = updates
+            .iter()
+            .chain(&module_updates)
+            .chain(&container_updates)
+            .map(|update| update.alias.as_str())
+            .collect();
+        if let Err(error) = cache.prune_updates(&aliases) {
+            warn!("Failed to remove old updates from cache: {}", error);
+        }
+    }
+
+    // don't suggest testing builds of packages that dnf will not upgrade
+    let locked = match tokio::task::spawn_blocking(dnf::locked_sources).await {
+        Ok(Ok(locked)) => locked,
+        Ok(Err(error)) => {
+            warn!("Failed to query excluded or locked packages: {}", error);
+            HashSet::new()
+        },
+        Err(error) => {
+            warn!("Failed to query excluded or locked packages: {}", error);
+            HashSet::new()
+        },
+    };
+    if !locked.is_empty() {
+        for evaluation in &mut report.pending {
+            evaluation.drop_locked(&locked);
+        }
+        report.pending.retain(|evaluation| {
+            let is_older = evaluation.is_older();
+            if !is_older {
+                debug!(alias = %evaluation.update.alias, "Skipped: excluded or locked in dnf");
+            }
+            is_older
+        });
+    }
 
-        for build in &update.builds {
-            let (n, v, r) = parse_nvr(&build.nvr)?;
-            pending_nvrs.push(NVR { n, v, r });
+    let repos = if (check_repo && !report.installed.is_empty()) || check_orphans {
+        match tokio::task::spawn_blocking(dnf::installed_repos).await {
+            Ok(Ok(repos)) => Some(repos),
+            Ok(Err(error)) => {
+                warn!("Failed to query repositories of installed packages: {}", error);
+                None
+            },
+            Err(error) => {
+                warn!("Failed to query repositories of installed packages: {}", error);
+                None
+            },
         }
+    } else {
+        None
+    };
 
-        for nvr in &pending_nvrs {
-            if packages.contains(nvr) {
-                // update is already installed, skip it
-                continue;
-            } else {
-                // check if the package is installed, but not the pending update
-                let mut is_installed: bool = false;
-                for pending_nvr in &pending_nvrs {
-                    for package in &packages {
-                        if pending_nvr.n == package.n {
-                            is_installed = true;
-                        }
+    if let Some(repos) = repos.as_ref().filter(|_| check_repo) {
+        report.installed.retain(|evaluation| {
+            let from_testing = evaluation.is_from_testing(repos);
+            if !from_testing {
+                debug!(alias = %evaluation.update.alias, "Skipped: not installed from updates-testing");
+            }
+            from_testing
+        });
+    }
+
+    // builds from updates-testing that are not in testing anymore either went stable or were orphaned
+    if let Some(repos) = repos.as_ref().filter(|_| check_orphans) {
+        let candidates = orphans::candidates(&packages, &updates, repos);
+
+        if !candidates.is_empty() {
+            match source.build_updates(&orphans::nvrs(&candidates)).await {
+                Ok(build_updates) => {
+                    for orphan in orphans::find(&candidates, &build_updates) {
+                        warn!("{}", orphan.message());
                     }
+                },
+                Err(error) => warn!("Failed to query updates of installed testing builds: {}", error),
+            }
+        }
+    }
+
+    if let Some(cache) = &cache {
+        report.retain(|evaluation| {
+            let dismissed = cache.is_dismissed(&evaluation.update.alias);
+            if dismissed {
+                debug!(alias = %evaluation.update.alias, "Skipped: ignored or snoozed");
+            }
+            !dismissed
+        });
+        report.track_installs(cache, &soak);
+    }
+
+    if let Some(auto_comment) = auto_comment.as_ref().filter(|_| list.is_none() && open.is_none()) {
+        // commenting right after installation is exactly what the soak period is supposed to prevent
+        if soak.days == 0 && soak.boots == 0 {
+            return Err(Failure::config(String::from(
+                "Automatic comments require soak_days or soak_boots to be set.",
+            )));
+        }
+
+        // "works for me" is only true for builds that were actually installed, not for newer ones
+        let tracked: Vec<&Evaluation> = report.installed.iter().filter(|e| e.is_tested()).collect();
+
+        let mut commented: Vec<String> = Vec::new();
+        if dry_run {
+            let system = SystemInfo::collect(&release.to_string());
+
+            for evaluation in tracked {
+                let feedback = auto_comment.feedback(&system, evaluation);
+                println!(
+                    "[dry-run] Would submit automatic feedback for {} (karma {}): {}",
+                    &evaluation.update.alias, feedback.karma, feedback.text
+                );
+                commented.push(evaluation.update.alias.clone());
+            }
+        } else if !tracked.is_empty() {
+            journal::status("Submitting automatic feedback");
+
+            let access_token = Authenticator::new(&username)?.access_token().await?;
+            let system = SystemInfo::collect(&release.to_string());
+
+            for evaluation in tracked {
+                let alias = &evaluation.update.alias;
+                match feedback::submit(&access_token, alias, &auto_comment.feedback(&system, evaluation)).await {
+                    Ok(()) => {
+                        journal::info(&format!("Submitted automatic feedback for {}.", alias));
+                        commented.push(alias.clone());
+                    },
+                    Err(error) => warn!("Failed to submit automatic feedback for {}: {}", alias, error),
                 }
+            }
+        }
+
+        report
+            .installed
+            .retain(|evaluation| !commented.contains(&evaluation.update.alias));
+    }
+
+    if my_bugs {
+        let email = match &bugzilla_email {
+            Some(email) => email,
+            None => {
+                return Err(Failure::config(String::from(
+                    "No bugzilla e-mail address was specified.",
+                )));
+            },
+        };
+
+        let bugzilla = Bugzilla::new(email, bugzilla_api_key.as_deref(), policy)?;
+        let bugs = bugzilla.my_bugs(&report.bug_ids()).await?;
+
+        // only keep updates that fix bugs that were reported by the user or that the user is CC'd on
+        report.retain(|evaluation| {
+            let fixes = evaluation.fixes_any(&bugs);
+            if !fixes {
+                debug!(alias = %evaluation.update.alias, "Skipped: doesn't fix any of your bugs");
+            }
+            fixes
+        });
+    }
+
+    report.sort(sort);
+
+    let mut outcome = Outcome {
+        feedback: report.installed.len(),
+        pending: report.pending.len(),
+        ..Default::default()
+    };
+
+    let mut summary = Summary::new(&release, &report, &interests, &Source::local());
+
+    if changelog && !summary.pending.is_empty() {
+        journal::status("Querying changelogs of interesting updates");
+
+        let mdapi = Mdapi::new(policy)?;
+        for update in &mut summary.pending {
+            for package in &update.packages {
+                let installed = match packages.iter().find(|nevr| &nevr.n == package) {
+                    Some(installed) => installed,
+                    None => continue,
+                };
+
+                match mdapi.newer_entries(&release, installed).await {
+                    Ok(entries) => update.changelogs.push(PackageChangelog {
+                        package: package.clone(),
+                        entries,
+                    }),
+                    Err(error) => warn!("Failed to get changelog of {}: {}", package, error),
+                }
+            }
+        }
+    }
 
-                // check if the package is interesting
-                let mut is_interesting: bool = false;
-                for pending_nvr in &pending_nvrs {
-                    for interest in &interests {
-                        if interest == pending_nvr.n {
-                            is_interesting = true;
+    if koschei && !summary.pending.is_empty() {
+        journal::status("Querying koschei for failing rebuilds");
+
+        // koschei tracks source packages, which are the names of the builds in bodhi
+        let mut sources: Vec<String> = summary
+            .pending
+            .iter()
+            .flat_map(|update| &update.builds)
+            .filter_map(|nvr| nvr::parse_nvr(nvr).ok())
+            .map(|(n, _, _)| n.to_owned())
+            .collect();
+        sources.sort();
+        sources.dedup();
+
+        let rebuilds = Koschei::new(policy)?;
+        match rebuilds.failing_packages(&release, &sources).await {
+            Ok(failing) => {
+                for update in &mut summary.pending {
+                    for nvr in &update.builds {
+                        match nvr::parse_nvr(nvr) {
+                            Ok((n, _, _)) if failing.iter().any(|package| package == n) => {
+                                update.failing_rebuilds.push(RebuildSummary {
+                                    package: n.to_owned(),
+                                    url: koschei::package_url(&release, n),
+                                })
+                            },
+                            _ => {},
                         }
                     }
                 }
+            },
+            Err(error) => warn!("Failed to query koschei: {}", error),
+        }
+    }
 
-                if is_installed && is_interesting {
-                    pending_updates.push(update);
-                } else {
-                    continue;
+    if watch_builds {
+        journal::status("Querying completed builds in koji");
+
+        // without a previous check, only builds of the last day are shown
+        let now = chrono::Utc::now().timestamp();
+        let since = cache
+            .as_ref()
+            .and_then(|cache| cache.last_build_check(&release))
+            .unwrap_or(now - 24 * 60 * 60);
+
+        let koji = Koji::new(policy)?;
+        match koji.completed_builds(&release, since, &interests, &username).await {
+            Ok(builds) => {
+                summary.builds = builds;
+                if let Some(cache) = &cache {
+                    if let Err(error) = cache.store_build_check(&release, now) {
+                        warn!("Failed to store time of the last build check: {}", error);
+                    }
                 }
-            };
+            },
+            Err(error) => warn!("Failed to query completed builds in koji: {}", error),
         }
     }
 
-    // deduplicate pending updates
-    pending_updates.sort_by(|a, b| a.alias.cmp(&b.alias));
-    pending_updates.dedup_by(|a, b| a.alias == b.alias);
+    // feedback is only meaningful once the updated code is actually running
+    if matches.is_present("details") && !summary.feedback.is_empty() {
+        journal::status("Checking whether services need to be restarted");
 
-    if !interests.is_empty() && !pending_updates.is_empty() {
-        println!();
+        match tokio::task::spawn_blocking(dnf::needs_restarting).await {
+            Ok(Ok(restart)) => summary.restart = Some(restart),
+            Ok(Err(error)) => warn!("Failed to check whether services need to be restarted: {}", error),
+            Err(error) => warn!("Failed to check whether services need to be restarted: {}", error),
+        }
+    }
 
-        // construct interesting URL
-        let interesting_url = format!(
-            "https://bodhi.fedoraproject.org/updates/?release={}&status=testing&packages={}",
-            release,
-            &interests.join(",")
-        );
+    let mut targets: Vec<Target> = Vec::new();
 
-        // don't clobber the DBus notification server
-        sleep(Duration::from_secs(1));
-
-        Notification::new()
-            .summary("Updates for interesting packages are available for testing.")
-            .body(&interesting_url)
-            .icon("dialog-information")
-            .show()
-            .unwrap();
-
-        println!("Updates for interesting packages are available for testing:");
-        for pending_update in pending_updates {
-            let builds: Vec<&str> = pending_update.builds.iter().map(|b| b.nvr.as_ref()).collect();
-            println!("- {}", &pending_update.alias);
-            for build in builds {
-                println!("  - {}", build);
+    if scan_toolboxes {
+        journal::status("Querying toolbox containers");
+
+        match tokio::task::spawn_blocking(target::toolboxes).await {
+            Ok(Ok(toolboxes)) => targets.extend(toolboxes),
+            Ok(Err(error)) => warn!("Failed to query toolbox containers: {}", error),
+            Err(error) => warn!("Failed to query toolbox containers: {}", error),
+        }
+    }
+
+    if scan_mock {
+        journal::status("Querying mock chroots");
+
+        match tokio::task::spawn_blocking(target::mock_chroots).await {
+            Ok(Ok(chroots)) => targets.extend(chroots),
+            Ok(Err(error)) => warn!("Failed to query mock chroots: {}", error),
+            Err(error) => warn!("Failed to query mock chroots: {}", error),
+        }
+    }
+
+    if !hosts.is_empty() {
+        journal::status("Connecting to remote hosts");
+
+        match tokio::task::spawn_blocking(move || target::hosts(&hosts)).await {
+            Ok(hosts) => targets.extend(hosts),
+            Err(error) => warn!("Failed to connect to remote hosts: {}", error),
+        }
+    }
+
+    for target in targets {
+        let target_source = target.source.clone();
+        journal::status(&format!("Querying installed packages in {}", &target_source));
+
+        // package-filtered updates for this system don't cover packages installed elsewhere
+        let updates = Some(updates.as_slice()).filter(|_| strategy == QueryStrategy::Full);
+
+        match scan_target(target, &source, &release, updates, &usernames, &interests, &filters).await {
+            Ok(target_summary) => {
+                outcome.feedback += target_summary.feedback.len();
+                outcome.pending += target_summary.pending.len();
+                summary.targets.push(TargetSummary {
+                    source: target_source,
+                    summary: target_summary,
+                });
+            },
+            Err(error) => warn!("Failed to check {}: {}", &target_source, error),
+        }
+    }
+
+    templates.apply_urls(&mut summary)?;
+
+    if matches.subcommand_matches("tui").is_some() {
+        // karma can only be submitted when logged in, everything else works without
+        let access_token = match Authenticator::new(&username) {
+            Ok(authenticator) => authenticator.access_token().await.ok(),
+            Err(_) => None,
+        };
+
+        tui::run(&summary, cache.as_ref(), access_token.as_deref()).await?;
+        return Ok(outcome);
+    }
+
+    if let Some(open) = open {
+        open_pages(&summary, open.value_of("alias"), open.is_present("all"))?;
+        return Ok(outcome);
+    }
+
+    if let Some(list) = list {
+        if list.is_present("installed") {
+            summary.select(true, false);
+        } else if list.is_present("interesting") {
+            summary.select(false, true);
+        }
+
+        match format {
+            Format::Text => output::print_list(&summary),
+            Format::Json => output::print_json(&summary)?,
+        }
+        return Ok(outcome);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("report") {
+        let format = ExportFormat::parse(matches.value_of("format").unwrap_or("html"))?;
+        let path = PathBuf::from(matches.value_of("out").unwrap_or_default());
+
+        export::write(&summary, format, &path)?;
+        return Ok(outcome);
+    }
+
+    if let Some(metrics_file) = metrics_file.as_ref().filter(|_| !dry_run) {
+        metrics::write_textfile(metrics_file, &metrics::render(&summary, query_duration))?;
+    }
+
+    // comps groups are only looked up if there is anything to notify about
+    let has_updates = outcome.feedback > 0 || outcome.pending > 0;
+    let categories = if group_notifications && has_updates && notifier_name != "null" {
+        match tokio::task::spawn_blocking(Categories::load).await {
+            Ok(categories) => Some(categories),
+            Err(error) => {
+                warn!("Failed to look up categories of updates: {}", error);
+                None
+            },
+        }
+    } else {
+        None
+    };
+
+    notifier.flush().await?;
+    notifier::notify_summary(notifier.as_ref(), &summary, categories.as_ref())
+        .instrument(info_span!("notify", notifier = %notifier_name))
+        .await?;
+
+    if let Some(cache) = cache.as_ref().filter(|_| notifier_name != "null") {
+        for update in summary.feedback.iter().chain(&summary.pending) {
+            if let Err(error) = cache.record_notified(&update.alias) {
+                warn!("Failed to record notification for {}: {}", &update.alias, error);
             }
         }
+    }
 
-        println!("Install the relevant updates with:");
-        println!("sudo dnf upgrade --enablerepo=updates-testing --advisory=UPDATE_TITLE");
-    } else if !interests.is_empty() {
-        println!();
-        println!("No updates for interesting packages are available.");
+    // notices are only sent once, which needs the cache to remember them
+    if let Some(cache) = cache.as_ref().filter(|_| release_notices) {
+        match source.releases().await {
+            Ok(available) => {
+                for notice in releases::notices(&available, &release.to_string(), eol_warning_days) {
+                    match cache.is_new_alert(&release.to_string(), &notice.key) {
+                        Ok(true) => {
+                            notifier
+                                .notify(&Message {
+                                    summary: &notice.summary,
+                                    url: notice.url,
+                                    updates: &[],
+                                    urgent: notice.urgent,
+                                    custom_body: None,
+                                })
+                                .await?;
+
+                            if let Err(error) = cache.record_alert(&release.to_string(), &notice.key) {
+                                warn!("Failed to record release notice: {}", error);
+                            }
+                        },
+                        Ok(false) => {},
+                        Err(error) => warn!("Failed to check release notice: {}", error),
+                    }
+                }
+            },
+            Err(error) => warn!("Failed to query releases: {}", error),
+        }
+    }
+    notifier.finish().await?;
+    outcome.unsent = notifier.fell_back();
+
+    if matches.is_present("aggregate") {
+        let aggregate = Aggregate::new(&summary);
+        match format {
+            Format::Text => output::print_aggregate_text(&aggregate),
+            Format::Json => output::print_aggregate_json(&aggregate)?,
+        }
+    } else {
+        match format {
+            Format::Text => match templates.text(&summary)? {
+                Some(text) => print!("{}", text),
+                None => output::print_text(&summary, &interests, matches.is_present("details")),
+            },
+            Format::Json => output::print_json(&summary)?,
+        }
     }
 
-    Ok(())
+    Ok(outcome)
 }
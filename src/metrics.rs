@@ -0,0 +1,97 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::output::Summary;
+
+// labels are quoted, so backslashes, quotes, and newlines need to be escaped
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+// metrics in the Prometheus text format, for the node_exporter textfile collector
+pub fn render(summary: &Summary, query_duration: Duration) -> String {
+    let mut machines = vec![("local", "local", summary)];
+    for target in &summary.targets {
+        machines.push((
+            target.source.kind.as_str(),
+            target.source.name.as_str(),
+            &target.summary,
+        ));
+    }
+
+    let mut metrics = String::new();
+
+    let _ = writeln!(
+        metrics,
+        "# HELP fedora_update_notifier_feedback_pending Installed testing updates that are waiting for feedback."
+    );
+    let _ = writeln!(metrics, "# TYPE fedora_update_notifier_feedback_pending gauge");
+    for (kind, name, summary) in &machines {
+        let _ = writeln!(
+            metrics,
+            "fedora_update_notifier_feedback_pending{{kind=\"{}\",machine=\"{}\"}} {}",
+            kind,
+            escape(name),
+            summary.feedback.len()
+        );
+    }
+
+    let _ = writeln!(
+        metrics,
+        "# HELP fedora_update_notifier_interesting_available Testing updates for interesting packages that are not installed."
+    );
+    let _ = writeln!(metrics, "# TYPE fedora_update_notifier_interesting_available gauge");
+    for (kind, name, summary) in &machines {
+        let _ = writeln!(
+            metrics,
+            "fedora_update_notifier_interesting_available{{kind=\"{}\",machine=\"{}\"}} {}",
+            kind,
+            escape(name),
+            summary.pending.len()
+        );
+    }
+
+    let _ = writeln!(
+        metrics,
+        "# HELP fedora_update_notifier_last_success_timestamp_seconds Time of the last successful query of bodhi."
+    );
+    let _ = writeln!(
+        metrics,
+        "# TYPE fedora_update_notifier_last_success_timestamp_seconds gauge"
+    );
+    let _ = writeln!(
+        metrics,
+        "fedora_update_notifier_last_success_timestamp_seconds {}",
+        Utc::now().timestamp()
+    );
+
+    let _ = writeln!(
+        metrics,
+        "# HELP fedora_update_notifier_query_duration_seconds Duration of querying installed packages and updates."
+    );
+    let _ = writeln!(metrics, "# TYPE fedora_update_notifier_query_duration_seconds gauge");
+    let _ = writeln!(
+        metrics,
+        "fedora_update_notifier_query_duration_seconds {:.3}",
+        query_duration.as_secs_f64()
+    );
+
+    metrics
+}
+
+// the file is replaced atomically, so node_exporter never reads partial contents
+pub fn write_textfile(path: &Path, metrics: &str) -> Result<(), String> {
+    let temporary = path.with_extension("prom.tmp");
+
+    if let Err(error) = std::fs::write(&temporary, metrics) {
+        return Err(format!("Unable to write {}: {}", temporary.display(), error));
+    }
+
+    match std::fs::rename(&temporary, path) {
+        Ok(()) => Ok(()),
+        Err(error) => Err(format!("Unable to write {}: {}", path.display(), error)),
+    }
+}
@@ -0,0 +1,97 @@
+use std::convert::TryFrom;
+use std::process::Command;
+
+use bodhi::data::{FedoraRelease, Update};
+
+use crate::nvr::{dedup, parse_nvr, NEVR};
+use crate::subprocess;
+
+// installed module streams are identified by their NSVC (name, stream, version, context), i.e.
+// "nodejs:18:3820230519071148:6fb1f6a2", while the NVRs of module builds in bodhi look like
+// "nodejs-18-3820230519071148.6fb1f6a2" - so streams are treated like packages with the stream as version
+fn parse_modularity_label(label: &str) -> Option<NEVR> {
+    let mut parts = label.trim().split(':');
+
+    match (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(n), Some(s), Some(v), Some(c), None) if [n, s, v, c].iter().all(|part| !part.is_empty()) => Some(NEVR {
+            n: n.to_owned(),
+            e: 0,
+            v: s.to_owned(),
+            r: format!("{}.{}", v, c),
+        }),
+        _ => None,
+    }
+}
+
+// modular updates are published for separate releases, which only exist up to Fedora 38 and EPEL 8
+pub fn modular_release(release: &FedoraRelease) -> Option<FedoraRelease> {
+    FedoraRelease::try_from(format!("{}M", release).as_str()).ok()
+}
+
+// returns the module streams that packages are installed from
+pub fn installed_modules() -> Result<Vec<NEVR>, String> {
+    let output = match subprocess::output(
+        Command::new("rpm")
+            .arg("--query")
+            .arg("--all")
+            .arg("--queryformat")
+            .arg("%{MODULARITYLABEL}\n"),
+    ) {
+        Ok(output) => output,
+        Err(error) => {
+            return Err(format!("Failed to run rpm: {}", error));
+        },
+    };
+
+    if !output.status.success() {
+        return Err(String::from("Failed to query modularity labels of installed packages."));
+    }
+
+    // packages that are not part of a module have the label "(none)"
+    Ok(dedup(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(parse_modularity_label)
+            .collect(),
+    ))
+}
+
+// updates for other streams than the installed ones would only look like upgrades
+pub fn is_installed_stream(update: &Update, modules: &[NEVR]) -> bool {
+    update.builds.iter().all(|build| match parse_nvr(&build.nvr) {
+        Ok((n, s, _)) => modules.iter().any(|module| module.n == n && module.v == s),
+        Err(_) => false,
+    })
+}
+
+// "name:stream" of the module builds in an update
+pub fn module_streams(builds: &[String]) -> Vec<String> {
+    builds
+        .iter()
+        .filter_map(|nvr| parse_nvr(nvr).ok())
+        .map(|(n, s, _)| format!("{}:{}", n, s))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modularity_labels() {
+        let module = parse_modularity_label("nodejs:18:3820230519071148:6fb1f6a2").unwrap();
+        assert_eq!(module.n, "nodejs");
+        assert_eq!(module.v, "18");
+        assert_eq!(module.r, "3820230519071148.6fb1f6a2");
+
+        let (n, v, r) = parse_nvr("nodejs-18-3820230519071148.6fb1f6a2").unwrap();
+        assert_eq!((module.n.as_str(), module.v.as_str(), module.r.as_str()), (n, v, r));
+    }
+
+    #[test]
+    fn invalid_modularity_labels() {
+        for label in ["", "(none)", "nodejs:18", "nodejs:18:3820230519071148:", "a:b:c:d:e"] {
+            assert!(parse_modularity_label(label).is_none(), "{}", label);
+        }
+    }
+}
@@ -0,0 +1,1144 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use notify_rust::{Hint, Notification, Timeout, Urgency};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::cache::{Cache, DeferredNotification};
+use crate::categories::{Categories, Category};
+use crate::export::escape;
+use crate::http;
+use crate::l10n::{tr, trf};
+use crate::output::{notification_body, Summary, UpdateSummary};
+use crate::portal;
+use crate::quiet::{self, QuietHours};
+use crate::templates::Templates;
+
+// this is also the payload for webhooks, which shares the update schema with JSON output
+#[derive(Debug, Serialize)]
+pub struct Message<'a> {
+    pub summary: &'a str,
+    pub url: &'a str,
+    pub updates: &'a [UpdateSummary],
+    // problems that need to be acted on, i.e. negative karma for own updates
+    pub urgent: bool,
+    // rendered from a user-provided template instead of the default body
+    #[serde(skip)]
+    pub custom_body: Option<String>,
+}
+
+impl<'a> Message<'a> {
+    pub fn body(&self) -> String {
+        match &self.custom_body {
+            Some(body) => body.clone(),
+            None => notification_body(self.url, self.updates),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Notifier: Sync {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String>;
+
+    // delivers notifications that were held back earlier
+    async fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // sends notifications that were batched during this run
+    async fn finish(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    // whether notifications could only be printed to the terminal
+    fn fell_back(&self) -> bool {
+        false
+    }
+}
+
+// package names that are listed in notifications for a category
+const CATEGORY_PACKAGES: usize = 5;
+
+fn category_body(url: &str, updates: &[UpdateSummary]) -> String {
+    // updates are sorted by priority, which is kept for the package names
+    let mut packages: Vec<&str> = Vec::new();
+    for package in updates.iter().flat_map(|update| &update.packages) {
+        if !packages.contains(&package.as_str()) {
+            packages.push(package);
+        }
+    }
+
+    let names = packages
+        .iter()
+        .take(CATEGORY_PACKAGES)
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let names = match packages.len().checked_sub(CATEGORY_PACKAGES) {
+        Some(more) if more > 0 => trf("packages-and-more", &[("packages", &names), ("count", &more)]),
+        _ => names,
+    };
+
+    format!("{}\n{}", url, names)
+}
+
+// one notification per category, with the number of updates and some of the package names
+async fn notify_categories(
+    notifier: &dyn Notifier,
+    categories: &Categories,
+    id: &'static str,
+    location: &str,
+    url: &str,
+    updates: &[UpdateSummary],
+) -> Result<(), String> {
+    let mut grouped: BTreeMap<Category, Vec<UpdateSummary>> = BTreeMap::new();
+    for update in updates {
+        grouped
+            .entry(categories.category(update))
+            .or_default()
+            .push(update.clone());
+    }
+
+    for (category, updates) in grouped {
+        notifier
+            .notify(&Message {
+                summary: &trf(
+                    id,
+                    &[
+                        ("location", &location),
+                        ("category", &tr(category.label())),
+                        ("count", &updates.len()),
+                    ],
+                ),
+                url,
+                updates: &updates,
+                urgent: false,
+                custom_body: Some(category_body(url, &updates)),
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+// updates that are relevant on multiple systems (i.e. on the host and in a toolbox) are only included in the
+// notification for the first of them, which lists the other systems as well
+fn merge_updates(lists: &[&[UpdateSummary]]) -> Vec<Vec<UpdateSummary>> {
+    let mut merged: Vec<Vec<UpdateSummary>> = Vec::new();
+    let mut first: HashMap<&str, (usize, usize)> = HashMap::new();
+
+    for (index, updates) in lists.iter().enumerate() {
+        let mut kept: Vec<UpdateSummary> = Vec::new();
+
+        for update in updates.iter() {
+            match first.get(update.alias.as_str()) {
+                Some(&(list, position)) => {
+                    if let (Some(merged), Some(source)) = (merged.get_mut(list), &update.source) {
+                        merged[position].also_in.push(source.clone());
+                    }
+                },
+                None => {
+                    first.insert(&update.alias, (index, kept.len()));
+                    kept.push(update.clone());
+                },
+            }
+        }
+
+        merged.push(kept);
+    }
+
+    merged
+}
+
+// sends notifications for updates that are ready for feedback, and for interesting updates
+async fn notify_location(
+    notifier: &dyn Notifier,
+    summary: &Summary,
+    feedback: &[UpdateSummary],
+    pending: &[UpdateSummary],
+    location: &str,
+    categories: Option<&Categories>,
+) -> Result<(), String> {
+    // there is nothing left to notify about if all updates were merged into notifications for other systems
+    let feedback_url = summary.feedback_url.as_ref().filter(|_| !feedback.is_empty());
+    let interesting_url = summary.interesting_url.as_ref().filter(|_| !pending.is_empty());
+
+    if let Some(categories) = categories {
+        if let Some(feedback_url) = feedback_url {
+            let id = "installed-ready-category";
+            notify_categories(notifier, categories, id, location, feedback_url, feedback).await?;
+        }
+        if let Some(interesting_url) = interesting_url {
+            let id = "interesting-available-category";
+            notify_categories(notifier, categories, id, location, interesting_url, pending).await?;
+        }
+        return Ok(());
+    }
+
+    if let Some(feedback_url) = feedback_url {
+        notifier
+            .notify(&Message {
+                summary: &trf("installed-ready", &[("location", &location)]),
+                url: feedback_url,
+                updates: feedback,
+                urgent: false,
+                custom_body: None,
+            })
+            .await?;
+    }
+
+    if let Some(interesting_url) = interesting_url {
+        notifier
+            .notify(&Message {
+                summary: &trf("interesting-available", &[("location", &location)]),
+                url: interesting_url,
+                updates: pending,
+                urgent: false,
+                custom_body: None,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+// updates are grouped into categories if they are given
+pub async fn notify_summary(
+    notifier: &dyn Notifier,
+    summary: &Summary,
+    categories: Option<&Categories>,
+) -> Result<(), String> {
+    let mut locations: Vec<(String, &Summary)> = vec![(String::new(), summary)];
+    locations.extend(
+        summary
+            .targets
+            .iter()
+            .map(|target| (target.location(), &target.summary)),
+    );
+
+    let feedback: Vec<&[UpdateSummary]> = locations
+        .iter()
+        .map(|(_, summary)| summary.feedback.as_slice())
+        .collect();
+    let pending: Vec<&[UpdateSummary]> = locations
+        .iter()
+        .map(|(_, summary)| summary.pending.as_slice())
+        .collect();
+    let (feedback, pending) = (merge_updates(&feedback), merge_updates(&pending));
+
+    for (((location, summary), feedback), pending) in locations.iter().zip(&feedback).zip(&pending) {
+        notify_location(notifier, summary, feedback, pending, location, categories).await?;
+    }
+
+    for build in &summary.builds {
+        notifier
+            .notify(&Message {
+                summary: &trf("koji-build", &[("nvr", &build.nvr)]),
+                url: &build.url,
+                updates: &[],
+                urgent: false,
+                custom_body: None,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NotifierConfig {
+    #[serde(default)]
+    pub matrix: Option<MatrixConfig>,
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+    #[serde(default)]
+    pub styles: BTreeMap<String, NotificationStyle>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StyleUrgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl From<StyleUrgency> for Urgency {
+    fn from(urgency: StyleUrgency) -> Self {
+        match urgency {
+            StyleUrgency::Low => Urgency::Low,
+            StyleUrgency::Normal => Urgency::Normal,
+            StyleUrgency::Critical => Urgency::Critical,
+        }
+    }
+}
+
+impl StyleUrgency {
+    // priorities of the notification portal
+    fn priority(self) -> &'static str {
+        match self {
+            StyleUrgency::Low => "low",
+            StyleUrgency::Normal => "normal",
+            StyleUrgency::Critical => "urgent",
+        }
+    }
+}
+
+// icon name (from the freedesktop icon theme) and urgency of desktop notifications
+#[derive(Clone, Debug, Deserialize)]
+pub struct NotificationStyle {
+    pub icon: String,
+    pub urgency: StyleUrgency,
+    // seconds until the notification expires (0 for never), the notification daemon decides by default
+    #[serde(default)]
+    pub timeout: Option<u32>,
+    // keep the notification in the notification center until it is dismissed
+    #[serde(default)]
+    pub resident: bool,
+}
+
+fn default_styles() -> BTreeMap<String, NotificationStyle> {
+    [
+        ("alert", "dialog-warning", StyleUrgency::Critical, true),
+        ("urgent", "security-high", StyleUrgency::Critical, true),
+        ("security", "security-medium", StyleUrgency::Normal, false),
+        ("bugfix", "software-update-available", StyleUrgency::Normal, false),
+        ("enhancement", "software-update-available", StyleUrgency::Low, false),
+        ("newpackage", "software-update-available", StyleUrgency::Low, false),
+        ("default", "dialog-information", StyleUrgency::Normal, false),
+    ]
+    .iter()
+    .map(|(key, icon, urgency, resident)| {
+        let style = NotificationStyle {
+            icon: String::from(*icon),
+            urgency: *urgency,
+            timeout: None,
+            resident: *resident,
+        };
+        (String::from(*key), style)
+    })
+    .collect()
+}
+
+// styles are looked up for urgent messages ("alert") first, then for the severities and types of their updates
+// from most to least important, and "default" is used if nothing else matches
+fn style_keys(message: &Message) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+
+    if message.urgent {
+        keys.push("alert");
+    }
+
+    let severities = ["urgent", "high", "medium", "low"];
+    let types = ["security", "bugfix", "enhancement", "newpackage"];
+    for key in severities.iter().chain(types.iter()) {
+        if message
+            .updates
+            .iter()
+            .any(|update| update.severity == *key || update.update_type == *key)
+        {
+            keys.push(*key);
+        }
+    }
+
+    keys.push("default");
+    keys
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MatrixConfig {
+    pub homeserver: String,
+    pub access_token: String,
+    pub room: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookTemplate {
+    #[default]
+    Json,
+    Ntfy,
+    Gotify,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub template: WebhookTemplate,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+pub fn from_name(name: &str, config: &NotifierConfig) -> Result<Box<dyn Notifier>, String> {
+    match name {
+        "desktop" if portal::is_sandboxed() => Ok(Box::new(PortalNotifier::new(&config.styles))),
+        "desktop" => Ok(Box::new(DesktopNotifier::new(&config.styles))),
+        "stdout" => Ok(Box::new(StdoutNotifier)),
+        "null" => Ok(Box::new(NullNotifier)),
+        "matrix" => match &config.matrix {
+            Some(matrix) => Ok(Box::new(MatrixNotifier::new(matrix.clone())?)),
+            None => Err(String::from("The matrix notifier is not configured.")),
+        },
+        "webhook" => match &config.webhook {
+            Some(webhook) => Ok(Box::new(WebhookNotifier::new(webhook.clone())?)),
+            None => Err(String::from("The webhook notifier is not configured.")),
+        },
+        _ => Err(format!("Unknown notifier: {}", name)),
+    }
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    match http::client_builder().build() {
+        Ok(client) => Ok(client),
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+pub struct DesktopNotifier {
+    sent: AtomicBool,
+    unavailable: AtomicBool,
+    styles: BTreeMap<String, NotificationStyle>,
+}
+
+// there is no notification daemon in SSH sessions, on ttys, or in containers
+pub fn has_notification_daemon() -> bool {
+    let session_bus = std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        || dirs::runtime_dir().map(|dir| dir.join("bus").exists()).unwrap_or(false);
+
+    session_bus && notify_rust::get_server_information().is_ok()
+}
+
+impl DesktopNotifier {
+    // configured styles replace the default ones with the same key
+    pub fn new(styles: &BTreeMap<String, NotificationStyle>) -> Self {
+        let mut merged = default_styles();
+        merged.extend(styles.clone());
+
+        DesktopNotifier {
+            sent: AtomicBool::new(false),
+            unavailable: AtomicBool::new(false),
+            styles: merged,
+        }
+    }
+
+    fn fallback(&self, reason: &str) {
+        if !self.unavailable.swap(true, Ordering::SeqCst) {
+            warn!("{}, falling back to terminal output.", reason);
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for DesktopNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return StdoutNotifier.notify(message).await;
+        }
+
+        if !self.sent.swap(true, Ordering::SeqCst) && !has_notification_daemon() {
+            self.fallback("No desktop notification daemon is available");
+            return StdoutNotifier.notify(message).await;
+        }
+
+        let mut notification = Notification::new();
+        notification.summary(message.summary).body(&message.body());
+
+        if let Some(style) = style_keys(message).iter().find_map(|key| self.styles.get(*key)) {
+            notification.icon(&style.icon).urgency(style.urgency.into());
+
+            match style.timeout {
+                Some(0) => {
+                    notification.timeout(Timeout::Never);
+                },
+                Some(seconds) => {
+                    notification.timeout(Timeout::Milliseconds(seconds * 1000));
+                },
+                None => {},
+            }
+
+            if style.resident {
+                notification.hint(Hint::Resident(true));
+            }
+        }
+
+        match notification.show() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                self.fallback(&format!("Unable to send desktop notification ({})", error));
+                StdoutNotifier.notify(message).await
+            },
+        }
+    }
+
+    fn fell_back(&self) -> bool {
+        self.unavailable.load(Ordering::SeqCst)
+    }
+}
+
+// inside a flatpak sandbox, the notification server is only reachable through the notification portal
+pub struct PortalNotifier {
+    unavailable: AtomicBool,
+    styles: BTreeMap<String, NotificationStyle>,
+}
+
+impl PortalNotifier {
+    pub fn new(styles: &BTreeMap<String, NotificationStyle>) -> Self {
+        let mut merged = default_styles();
+        merged.extend(styles.clone());
+
+        PortalNotifier {
+            unavailable: AtomicBool::new(false),
+            styles: merged,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for PortalNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return StdoutNotifier.notify(message).await;
+        }
+
+        // icons, timeouts, and resident notifications are not supported by the portal
+        let priority = match style_keys(message).iter().find_map(|key| self.styles.get(*key)) {
+            Some(style) => style.urgency.priority(),
+            None => StyleUrgency::Normal.priority(),
+        };
+
+        // the summary identifies the notification, so newer versions replace older ones
+        match portal::add_notification(message.summary, message.summary, &message.body(), priority).await {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                if !self.unavailable.swap(true, Ordering::SeqCst) {
+                    warn!("{}, falling back to terminal output.", error);
+                }
+                StdoutNotifier.notify(message).await
+            },
+        }
+    }
+
+    fn fell_back(&self) -> bool {
+        self.unavailable.load(Ordering::SeqCst)
+    }
+}
+
+// printed notifications go to stderr when stdout is used for JSON output
+static PRINT_TO_STDERR: OnceLock<bool> = OnceLock::new();
+
+pub fn print_to_stderr(stderr: bool) {
+    let _ = PRINT_TO_STDERR.set(stderr);
+}
+
+fn print_line(line: &str) {
+    if PRINT_TO_STDERR.get().copied().unwrap_or(false) {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+pub struct StdoutNotifier;
+
+#[async_trait]
+impl Notifier for StdoutNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        if message.urgent {
+            print_line(&format!("[notification] (urgent) {}", message.summary));
+        } else {
+            print_line(&format!("[notification] {}", message.summary));
+        }
+        for line in message.body().lines() {
+            print_line(&format!("[notification] {}", line));
+        }
+        Ok(())
+    }
+}
+
+// prints notifications instead of sending them, for checking filters and configuration
+pub struct DryRunNotifier {
+    name: String,
+}
+
+impl DryRunNotifier {
+    pub fn new(name: &str) -> Self {
+        DryRunNotifier { name: name.to_owned() }
+    }
+}
+
+#[async_trait]
+impl Notifier for DryRunNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        let urgent = if message.urgent { " urgent" } else { "" };
+        print_line(&format!(
+            "[dry-run] Would send{} {} notification: {}",
+            urgent, self.name, message.summary
+        ));
+        print_line(&format!("[dry-run] URL: {}", message.url));
+        for line in message.body().lines() {
+            print_line(&format!("[dry-run]   {}", line));
+        }
+        Ok(())
+    }
+}
+
+// renders summary and body of notifications from templates before passing them on
+pub struct TemplateNotifier {
+    inner: Box<dyn Notifier>,
+    templates: Templates,
+}
+
+impl TemplateNotifier {
+    pub fn new(inner: Box<dyn Notifier>, templates: Templates) -> Self {
+        TemplateNotifier { inner, templates }
+    }
+}
+
+#[async_trait]
+impl Notifier for TemplateNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        let summary = self.templates.summary(message)?;
+
+        self.inner
+            .notify(&Message {
+                summary: summary.as_deref().unwrap_or(message.summary),
+                url: message.url,
+                updates: message.updates,
+                urgent: message.urgent,
+                custom_body: self.templates.body(message)?.or_else(|| message.custom_body.clone()),
+            })
+            .await
+    }
+
+    async fn flush(&self) -> Result<(), String> {
+        self.inner.flush().await
+    }
+
+    async fn finish(&self) -> Result<(), String> {
+        self.inner.finish().await
+    }
+
+    fn fell_back(&self) -> bool {
+        self.inner.fell_back()
+    }
+}
+
+// holds back notifications during quiet hours and while the desktop is in do-not-disturb mode,
+// they are stored in the cache and delivered by a later run instead
+pub struct DeferringNotifier {
+    inner: Box<dyn Notifier>,
+    allowed: bool,
+}
+
+impl DeferringNotifier {
+    pub fn new(inner: Box<dyn Notifier>, quiet_hours: Option<QuietHours>, desktop: bool) -> Self {
+        let quiet = quiet_hours.map(|quiet_hours| quiet_hours.is_now()).unwrap_or(false);
+        let allowed = !quiet && !(desktop && (quiet::is_do_not_disturb() || quiet::is_away()));
+
+        DeferringNotifier { inner, allowed }
+    }
+}
+
+#[async_trait]
+impl Notifier for DeferringNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        if self.allowed {
+            return self.inner.notify(message).await;
+        }
+
+        debug!("Deferring notification: {}", message.summary);
+        Cache::open()?.defer_notification(&DeferredNotification {
+            summary: message.summary.to_owned(),
+            url: message.url.to_owned(),
+            body: message.body(),
+            urgent: message.urgent,
+        })
+    }
+
+    async fn flush(&self) -> Result<(), String> {
+        if !self.allowed {
+            return Ok(());
+        }
+
+        // the cache is only opened while it is accessed, the connection can't be held across await points
+        let deferred = Cache::open()?.take_deferred()?;
+
+        // notifications that piled up (i.e. while the screen was locked) are delivered as one
+        if deferred.len() > 1 {
+            let summaries: Vec<&str> = deferred
+                .iter()
+                .map(|notification| notification.summary.as_str())
+                .collect();
+
+            return self
+                .inner
+                .notify(&Message {
+                    summary: &trf("held-back", &[("count", &deferred.len())]),
+                    url: &deferred[0].url,
+                    updates: &[],
+                    urgent: deferred.iter().any(|notification| notification.urgent),
+                    custom_body: Some(format!("{}\n{}", &deferred[0].url, summaries.join("\n"))),
+                })
+                .await;
+        }
+
+        for notification in deferred {
+            self.inner
+                .notify(&Message {
+                    summary: &notification.summary,
+                    url: &notification.url,
+                    updates: &[],
+                    urgent: notification.urgent,
+                    custom_body: Some(notification.body),
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&self) -> Result<(), String> {
+        self.inner.finish().await
+    }
+
+    fn fell_back(&self) -> bool {
+        self.inner.fell_back()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RateLimit {
+    pub per_run: Option<u32>,
+    pub per_day: Option<u32>,
+    // time between notifications, so the notification server doesn't replace them right away
+    pub interval: Duration,
+}
+
+struct Overflow {
+    summaries: Vec<String>,
+    url: String,
+    updates: Vec<UpdateSummary>,
+    urgent: bool,
+}
+
+// limits the number of notifications per run and per day: notifications over the limit for this run are sent as a
+// single summary at the end of the run, and notifications over the daily limit are held back like during quiet hours
+pub struct RateLimitingNotifier {
+    inner: Box<dyn Notifier>,
+    limit: RateLimit,
+    sent: AtomicU32,
+    last: Mutex<Option<Instant>>,
+    overflow: Mutex<Option<Overflow>>,
+}
+
+const DAY: i64 = 24 * 60 * 60;
+
+impl RateLimitingNotifier {
+    pub fn new(inner: Box<dyn Notifier>, limit: RateLimit) -> Self {
+        RateLimitingNotifier {
+            inner,
+            limit,
+            sent: AtomicU32::new(0),
+            last: Mutex::new(None),
+            overflow: Mutex::new(None),
+        }
+    }
+
+    fn sent_today(&self) -> Result<u32, String> {
+        Cache::open()?.notifications_since(chrono::Utc::now().timestamp() - DAY)
+    }
+
+    async fn send(&self, message: &Message<'_>) -> Result<(), String> {
+        let wait = self
+            .last
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .map(|last| self.limit.interval.saturating_sub(last.elapsed()));
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+
+        self.inner.notify(message).await?;
+
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+        self.sent.fetch_add(1, Ordering::SeqCst);
+        if self.limit.per_day.is_some() {
+            Cache::open()?.record_notification(chrono::Utc::now().timestamp())?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Notifier for RateLimitingNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        if let Some(per_day) = self.limit.per_day {
+            if self.sent_today()? >= per_day {
+                debug!("Deferring notification over the daily limit: {}", message.summary);
+                return Cache::open()?.defer_notification(&DeferredNotification {
+                    summary: message.summary.to_owned(),
+                    url: message.url.to_owned(),
+                    body: message.body(),
+                    urgent: message.urgent,
+                });
+            }
+        }
+
+        if let Some(per_run) = self.limit.per_run {
+            if self.sent.load(Ordering::SeqCst) >= per_run {
+                debug!("Batching notification over the limit for this run: {}", message.summary);
+                let mut overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner);
+                let overflow = overflow.get_or_insert_with(|| Overflow {
+                    summaries: Vec::new(),
+                    url: message.url.to_owned(),
+                    updates: Vec::new(),
+                    urgent: false,
+                });
+                overflow.summaries.push(message.summary.to_owned());
+                overflow.updates.extend(message.updates.iter().cloned());
+                overflow.urgent |= message.urgent;
+                return Ok(());
+            }
+        }
+
+        self.send(message).await
+    }
+
+    async fn flush(&self) -> Result<(), String> {
+        self.inner.flush().await
+    }
+
+    async fn finish(&self) -> Result<(), String> {
+        let overflow = self.overflow.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+        if let Some(overflow) = overflow {
+            // notifications without updates (i.e. for koji builds) still count as one
+            let count = overflow.updates.len().max(overflow.summaries.len());
+
+            self.send(&Message {
+                summary: &trf("more-updates", &[("count", &count)]),
+                url: &overflow.url,
+                updates: &overflow.updates,
+                urgent: overflow.urgent,
+                custom_body: Some(format!("{}\n{}", overflow.url, overflow.summaries.join("\n"))),
+            })
+            .await?;
+        }
+
+        self.inner.finish().await
+    }
+
+    fn fell_back(&self) -> bool {
+        self.inner.fell_back()
+    }
+}
+
+pub struct NullNotifier;
+
+#[async_trait]
+impl Notifier for NullNotifier {
+    async fn notify(&self, _message: &Message<'_>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct MatrixMessage {
+    msgtype: &'static str,
+    body: String,
+    format: &'static str,
+    formatted_body: String,
+}
+
+pub struct MatrixNotifier {
+    client: reqwest::Client,
+    config: MatrixConfig,
+    counter: AtomicU32,
+}
+
+impl MatrixNotifier {
+    pub fn new(config: MatrixConfig) -> Result<Self, String> {
+        Ok(MatrixNotifier {
+            client: http_client()?,
+            config,
+            counter: AtomicU32::new(0),
+        })
+    }
+
+    // transaction IDs need to be unique per access token
+    fn transaction_id(&self) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        format!("fun-{}-{}", now, self.counter.fetch_add(1, Ordering::SeqCst))
+    }
+
+    // PUT /_matrix/client/v3/rooms/{room}/send/m.room.message/{transaction}
+    fn endpoint(&self, transaction_id: &str) -> Result<reqwest::Url, String> {
+        let mut endpoint = match reqwest::Url::parse(&self.config.homeserver) {
+            Ok(endpoint) => endpoint,
+            Err(error) => {
+                return Err(format!("Invalid matrix homeserver URL: {}", error));
+            },
+        };
+
+        match endpoint.path_segments_mut() {
+            Ok(mut segments) => {
+                segments.pop_if_empty().extend(&[
+                    "_matrix",
+                    "client",
+                    "v3",
+                    "rooms",
+                    self.config.room.as_str(),
+                    "send",
+                    "m.room.message",
+                    transaction_id,
+                ]);
+            },
+            Err(_) => {
+                return Err(String::from("Invalid matrix homeserver URL."));
+            },
+        }
+
+        Ok(endpoint)
+    }
+
+    fn request(&self, message: &Message<'_>) -> Result<reqwest::Request, String> {
+        let endpoint = self.endpoint(&self.transaction_id())?;
+
+        // summaries can contain package names and user-provided templates, which are not HTML
+        let link = escape(message.url);
+        let mut html = format!(
+            "<b>{}</b><br><a href=\"{}\">{}</a><ul>",
+            escape(message.summary),
+            link,
+            link
+        );
+        for update in message.updates {
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> ({})</li>",
+                escape(&update.url),
+                escape(&update.alias),
+                escape(&update.karma_info())
+            ));
+        }
+        html.push_str("</ul>");
+
+        let content = MatrixMessage {
+            // clients don't notify for notices
+            msgtype: if message.urgent { "m.text" } else { "m.notice" },
+            body: format!("{}\n{}", message.summary, message.body()),
+            format: "org.matrix.custom.html",
+            formatted_body: html,
+        };
+
+        match self
+            .client
+            .put(endpoint)
+            .bearer_auth(&self.config.access_token)
+            .json(&content)
+            .build()
+        {
+            Ok(request) => Ok(request),
+            Err(error) => Err(format!("Unable to send matrix notification: {}", error)),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for MatrixNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        let request = self.request(message)?;
+
+        match self
+            .client
+            .execute(request)
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to send matrix notification: {}", error)),
+        }
+    }
+}
+
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig) -> Result<Self, String> {
+        Ok(WebhookNotifier {
+            client: http_client()?,
+            config,
+        })
+    }
+
+    fn request(&self, message: &Message<'_>) -> Result<reqwest::RequestBuilder, String> {
+        Ok(match self.config.template {
+            WebhookTemplate::Json => {
+                let request = self.client.post(&self.config.url).json(message);
+                match &self.config.token {
+                    Some(token) => request.bearer_auth(token),
+                    None => request,
+                }
+            },
+            // https://docs.ntfy.sh/publish/#publish-as-json, since headers can't contain translated titles
+            WebhookTemplate::Ntfy => {
+                let (server, topic) = ntfy_topic(&self.config.url)?;
+                let payload = serde_json::json!({
+                    "topic": topic,
+                    "title": message.summary,
+                    "message": message.body(),
+                    "click": message.url,
+                    "tags": ["package"],
+                    "priority": if message.urgent { 5 } else { 3 },
+                });
+                let request = self.client.post(server).json(&payload);
+                match &self.config.token {
+                    Some(token) => request.bearer_auth(token),
+                    None => request,
+                }
+            },
+            // https://gotify.net/docs/pushmsg
+            WebhookTemplate::Gotify => {
+                let mut payload = serde_json::json!({
+                    "title": message.summary,
+                    "message": message.body(),
+                    "extras": {
+                        "client::notification": { "click": { "url": message.url } }
+                    }
+                });
+                // otherwise, the default priority of the application is used
+                if message.urgent {
+                    payload["priority"] = serde_json::json!(8);
+                }
+                let request = self
+                    .client
+                    .post(format!("{}/message", self.config.url.trim_end_matches('/')))
+                    .json(&payload);
+                match &self.config.token {
+                    Some(token) => request.header("X-Gotify-Key", token),
+                    None => request,
+                }
+            },
+        })
+    }
+}
+
+// JSON messages are published to the server itself, i.e. "https://ntfy.sh/TOPIC" is split into server and topic
+fn ntfy_topic(url: &str) -> Result<(reqwest::Url, String), String> {
+    let mut server = match reqwest::Url::parse(url) {
+        Ok(server) => server,
+        Err(error) => {
+            return Err(format!("Invalid ntfy URL: {}", error));
+        },
+    };
+
+    let topic = server
+        .path_segments()
+        .and_then(|segments| segments.filter(|segment| !segment.is_empty()).last())
+        .map(String::from);
+
+    match (topic, server.path_segments_mut()) {
+        (Some(topic), Ok(mut segments)) => {
+            segments.pop_if_empty().pop();
+            drop(segments);
+            Ok((server, topic))
+        },
+        _ => Err(String::from("The ntfy URL doesn't include a topic.")),
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, message: &Message<'_>) -> Result<(), String> {
+        match self
+            .request(message)?
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Unable to send webhook notification: {}", error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matrix_request() {
+        let notifier = MatrixNotifier::new(MatrixConfig {
+            homeserver: String::from("https://matrix.example.org/"),
+            access_token: String::from("secret"),
+            room: String::from("!room:example.org"),
+        })
+        .unwrap();
+
+        let request = notifier
+            .request(&Message {
+                summary: "<b>Updates</b>",
+                url: "https://bodhi.fedoraproject.org/updates/?search=a&b",
+                updates: &[],
+                urgent: false,
+                custom_body: None,
+            })
+            .unwrap();
+
+        assert_eq!(request.method(), reqwest::Method::PUT);
+        assert_eq!(request.url().host_str(), Some("matrix.example.org"));
+        assert!(request
+            .url()
+            .path()
+            .starts_with("/_matrix/client/v3/rooms/!room:example.org/send/m.room.message/fun-"));
+
+        let body: serde_json::Value = serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(
+            body["formatted_body"],
+            "<b>&lt;b&gt;Updates&lt;/b&gt;</b><br><a href=\"https://bodhi.fedoraproject.org/updates/?search=a&amp;b\">\
+             https://bodhi.fedoraproject.org/updates/?search=a&amp;b</a><ul></ul>"
+        );
+    }
+
+    #[test]
+    fn ntfy_request() {
+        let notifier = WebhookNotifier::new(WebhookConfig {
+            url: String::from("https://ntfy.example.org/updates/"),
+            template: WebhookTemplate::Ntfy,
+            token: None,
+        })
+        .unwrap();
+
+        let request = notifier
+            .request(&Message {
+                summary: "Sicherheitsaktualisierungen für Rückmeldung",
+                url: "https://bodhi.fedoraproject.org/updates/",
+                updates: &[],
+                urgent: true,
+                custom_body: None,
+            })
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.url().as_str(), "https://ntfy.example.org/");
+
+        let body: serde_json::Value = serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["topic"], "updates");
+        assert_eq!(body["title"], "Sicherheitsaktualisierungen für Rückmeldung");
+        assert_eq!(body["click"], "https://bodhi.fedoraproject.org/updates/");
+        assert_eq!(body["priority"], 5);
+
+        assert!(ntfy_topic("https://ntfy.example.org/").is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use std::cmp::Ordering;
+
+use bodhi::data::Build;
+use serde::{Deserialize, Serialize};
+
+use crate::version::evrcmp;
+
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct NEVR {
+    pub n: String,
+    pub e: u32,
+    pub v: String,
+    pub r: String,
+}
+
+impl NEVR {
+    // parse file names of source packages, as printed by dnf
+    pub fn from_filename(filename: &str) -> Result<Self, String> {
+        let (n, e, v, r, _) = parse_filename(filename)?;
+
+        Ok(NEVR {
+            n: n.to_owned(),
+            e: parse_epoch(e)?,
+            v: v.to_owned(),
+            r: r.to_owned(),
+        })
+    }
+
+    // parse installed binary packages as "NAME EPOCH SOURCERPM", since subpackages often have a different name than
+    // their source package (i.e. python3-requests), and file names of source packages don't contain the epoch
+    pub fn from_package(package: &str) -> Result<Self, String> {
+        let (epoch, sourcerpm) = match package.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [_name, epoch, sourcerpm] => (*epoch, *sourcerpm),
+            _ => {
+                return Err(format!("Unexpected error when parsing installed packages: {}", package));
+            },
+        };
+
+        let mut nevr = NEVR::from_filename(sourcerpm)?;
+
+        // rpm prints "(none)" for packages without epoch
+        if nevr.e == 0 && epoch != "(none)" {
+            nevr.e = parse_epoch(epoch)?;
+        }
+
+        Ok(nevr)
+    }
+
+    pub fn from_build(build: &Build) -> Result<Self, String> {
+        let (n, v, r) = parse_nvr(&build.nvr)?;
+
+        Ok(NEVR {
+            n: n.to_owned(),
+            e: build.epoch.unwrap_or(0),
+            v: v.to_owned(),
+            r: r.to_owned(),
+        })
+    }
+
+    pub fn evr(&self) -> (u32, &str, &str) {
+        (self.e, &self.v, &self.r)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum InstallState {
+    NotInstalled,
+    Older,
+    Installed,
+    Newer,
+}
+
+impl InstallState {
+    pub fn is_installed(self) -> bool {
+        matches!(self, InstallState::Installed | InstallState::Newer)
+    }
+}
+
+pub fn install_state(packages: &[NEVR], build: &NEVR) -> InstallState {
+    let mut state = InstallState::NotInstalled;
+
+    // there can be multiple installed versions of a package (i.e. kernel)
+    for package in packages.iter().filter(|package| package.n == build.n) {
+        match evrcmp(package.evr(), build.evr()) {
+            Ordering::Equal => return InstallState::Installed,
+            Ordering::Greater => state = InstallState::Newer,
+            Ordering::Less => {
+                if state == InstallState::NotInstalled {
+                    state = InstallState::Older;
+                }
+            },
+        }
+    }
+
+    state
+}
+
+fn parse_epoch(epoch: &str) -> Result<u32, String> {
+    match epoch.parse() {
+        Ok(epoch) => Ok(epoch),
+        Err(_) => Err(format!("Unexpected error when parsing epoch: {}", epoch)),
+    }
+}
+
+// removes duplicates, i.e. from source packages of multilib packages which are installed for multiple architectures
+pub fn dedup(mut packages: Vec<NEVR>) -> Vec<NEVR> {
+    packages.sort_by(|a, b| (&a.n, a.e, &a.v, &a.r).cmp(&(&b.n, b.e, &b.v, &b.r)));
+    packages.dedup();
+    packages
+}
+
+// splits "N-V-R" or "N-E:V-R", versions can contain "~" (pre-releases) and "^" (snapshots), but no dashes
+fn split_nevr(nevr: &str) -> Option<(&str, &str, &str, &str)> {
+    let (nev, r) = nevr.rsplit_once('-')?;
+    let (n, ev) = nev.rsplit_once('-')?;
+
+    let (e, v) = match ev.split_once(':') {
+        Some((e, v)) => (e, v),
+        None => ("0", ev),
+    };
+
+    let valid = !n.is_empty()
+        && !e.is_empty()
+        && !v.is_empty()
+        && !r.is_empty()
+        && !v.contains(':')
+        && !r.contains(':')
+        && e.bytes().all(|c| c.is_ascii_digit());
+
+    if valid {
+        Some((n, e, v, r))
+    } else {
+        None
+    }
+}
+
+pub fn parse_nevra(nevra: &str) -> Result<(&str, &str, &str, &str, &str), String> {
+    let parsed = nevra.rsplit_once('.').and_then(|(nevr, a)| {
+        let (n, e, v, r) = split_nevr(nevr)?;
+        if a.is_empty() {
+            None
+        } else {
+            Some((n, e, v, r, a))
+        }
+    });
+
+    match parsed {
+        Some(nevra) => Ok(nevra),
+        None => Err(format!("Unexpected error when parsing NEVRAs: {}", nevra)),
+    }
+}
+
+pub fn parse_filename(nevrax: &str) -> Result<(&str, &str, &str, &str, &str), String> {
+    match nevrax.strip_suffix(".rpm") {
+        Some(nevra) => parse_nevra(nevra),
+        None => Err(format!("Unexpected error when parsing dnf output: {}", nevrax)),
+    }
+}
+
+pub fn parse_nvr(nvr: &str) -> Result<(&str, &str, &str), String> {
+    match split_nvr(nvr) {
+        Some(nvr) => Ok(nvr),
+        None => Err(format!("Unexpected error when parsing NVRs: {}", nvr)),
+    }
+}
+
+// parses "NAME SOURCERPM" into the names of an installed binary package and of the source package it was built from
+pub fn parse_source(package: &str) -> Result<(&str, &str), String> {
+    match package.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [name, sourcerpm] => {
+            let (source, _, _, _, _) = parse_filename(sourcerpm)?;
+            Ok((name, source))
+        },
+        _ => Err(format!("Unexpected error when parsing installed packages: {}", package)),
+    }
+}
+
+// build NVRs from bodhi never contain an epoch
+fn split_nvr(nvr: &str) -> Option<(&str, &str, &str)> {
+    let (nv, r) = nvr.rsplit_once('-')?;
+    let (n, v) = nv.rsplit_once('-')?;
+
+    if n.is_empty() || v.is_empty() || r.is_empty() || v.contains(':') {
+        None
+    } else {
+        Some((n, v, r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nevr(n: &str, e: u32, v: &str, r: &str) -> NEVR {
+        NEVR {
+            n: n.to_owned(),
+            e,
+            v: v.to_owned(),
+            r: r.to_owned(),
+        }
+    }
+
+    #[test]
+    fn filenames() {
+        let cases = [
+            ("kernel-6.5.6-300.fc39.src.rpm", nevr("kernel", 0, "6.5.6", "300.fc39")),
+            (
+                "python-setuptools-67.7.2-7.fc39.src.rpm",
+                nevr("python-setuptools", 0, "67.7.2", "7.fc39"),
+            ),
+            (
+                "perl-Test-Simple-1.302195-1.fc39.src.rpm",
+                nevr("perl-Test-Simple", 0, "1.302195", "1.fc39"),
+            ),
+            (
+                "python3.12-3.12.0-1.fc39.src.rpm",
+                nevr("python3.12", 0, "3.12.0", "1.fc39"),
+            ),
+            (
+                "mesa-23.3.0~rc1-1.fc40.src.rpm",
+                nevr("mesa", 0, "23.3.0~rc1", "1.fc40"),
+            ),
+            (
+                "fish-3.6.1^20231004git7d4d5c1-1.fc40.src.rpm",
+                nevr("fish", 0, "3.6.1^20231004git7d4d5c1", "1.fc40"),
+            ),
+            ("golang-1.21.3-1.fc39.src.rpm", nevr("golang", 0, "1.21.3", "1.fc39")),
+            ("gcc-13.2.1-4.fc39.src.rpm", nevr("gcc", 0, "13.2.1", "4.fc39")),
+            ("vim-2:9.0.2048-1.fc39.src.rpm", nevr("vim", 2, "9.0.2048", "1.fc39")),
+        ];
+
+        for (filename, expected) in cases {
+            assert_eq!(NEVR::from_filename(filename).unwrap(), expected, "{}", filename);
+        }
+    }
+
+    #[test]
+    fn invalid_filenames() {
+        let cases = [
+            "",
+            "(none)",
+            "gpg-pubkey",
+            "kernel.src.rpm",
+            "kernel-6.5.6.src.rpm",
+            "kernel--300.fc39.src.rpm",
+            "kernel-6.5.6-.src.rpm",
+            "kernel-6.5.6-300.fc39.src",
+            "kernel-x:6.5.6-300.fc39.src.rpm",
+            "kernel-1:2:6.5.6-300.fc39.src.rpm",
+        ];
+
+        for filename in cases {
+            assert!(NEVR::from_filename(filename).is_err(), "{}", filename);
+        }
+    }
+
+    #[test]
+    fn packages() {
+        let cases = [
+            (
+                "kernel-core 0 kernel-6.5.6-300.fc39.src.rpm",
+                nevr("kernel", 0, "6.5.6", "300.fc39"),
+            ),
+            (
+                "python3-setuptools 0 python-setuptools-67.7.2-7.fc39.src.rpm",
+                nevr("python-setuptools", 0, "67.7.2", "7.fc39"),
+            ),
+            (
+                "perl-Test-Simple 3 perl-Test-Simple-1.302195-1.fc39.src.rpm",
+                nevr("perl-Test-Simple", 3, "1.302195", "1.fc39"),
+            ),
+            (
+                "glibc-langpack-en (none) glibc-2.38-7.fc39.src.rpm",
+                nevr("glibc", 0, "2.38", "7.fc39"),
+            ),
+            (
+                "mesa-libGL 0 mesa-23.3.0~rc1-1.fc40.src.rpm",
+                nevr("mesa", 0, "23.3.0~rc1", "1.fc40"),
+            ),
+            (
+                "vim-enhanced 2 vim-9.0.2048-1.fc39.src.rpm",
+                nevr("vim", 2, "9.0.2048", "1.fc39"),
+            ),
+            (
+                "vim-minimal 2 vim-2:9.0.2048-1.fc39.src.rpm",
+                nevr("vim", 2, "9.0.2048", "1.fc39"),
+            ),
+        ];
+
+        for (package, expected) in cases {
+            assert_eq!(NEVR::from_package(package).unwrap(), expected, "{}", package);
+        }
+
+        let invalid = [
+            "",
+            "gpg-pubkey (none) (none)",
+            "vim-enhanced vim-9.0.2048-1.fc39.src.rpm",
+            "vim-enhanced x vim-9.0.2048-1.fc39.src.rpm",
+        ];
+
+        for package in invalid {
+            assert!(NEVR::from_package(package).is_err(), "{}", package);
+        }
+    }
+
+    #[test]
+    fn nevras() {
+        assert_eq!(
+            parse_nevra("vim-enhanced-2:9.0.2048-1.fc39.x86_64").unwrap(),
+            ("vim-enhanced", "2", "9.0.2048", "1.fc39", "x86_64")
+        );
+        assert_eq!(
+            parse_nevra("glibc-langpack-en-2.38-7.fc39.i686").unwrap(),
+            ("glibc-langpack-en", "0", "2.38", "7.fc39", "i686")
+        );
+        assert_eq!(
+            parse_nevra("firefox-119.0~b9-1.fc40.aarch64").unwrap(),
+            ("firefox", "0", "119.0~b9", "1.fc40", "aarch64")
+        );
+        assert_eq!(
+            parse_nevra("python3-pip-23.2.1-1.fc39.noarch").unwrap(),
+            ("python3-pip", "0", "23.2.1", "1.fc39", "noarch")
+        );
+        assert!(parse_nevra("python3-pip-23.2.1-1.").is_err());
+        assert!(parse_nevra("noarch").is_err());
+    }
+
+    #[test]
+    fn nvrs() {
+        let cases = [
+            ("glibc-2.38-7.fc39", ("glibc", "2.38", "7.fc39")),
+            ("rust-zbus-3.14.1-1.fc39", ("rust-zbus", "3.14.1", "1.fc39")),
+            ("mesa-23.3.0~rc1-1.fc40", ("mesa", "23.3.0~rc1", "1.fc40")),
+            (
+                "fish-3.6.1^20231004git7d4d5c1-1.fc40",
+                ("fish", "3.6.1^20231004git7d4d5c1", "1.fc40"),
+            ),
+            ("texlive-base-20230311-77.fc39", ("texlive-base", "20230311", "77.fc39")),
+            ("python-django4.2-4.2.6-1.fc39", ("python-django4.2", "4.2.6", "1.fc39")),
+        ];
+
+        for (nvr, expected) in cases {
+            assert_eq!(parse_nvr(nvr).unwrap(), expected, "{}", nvr);
+        }
+
+        for nvr in [
+            "glibc",
+            "glibc-2.38",
+            "-2.38-7.fc39",
+            "glibc--7.fc39",
+            "glibc-1:2.38-7.fc39",
+        ] {
+            assert!(parse_nvr(nvr).is_err(), "{}", nvr);
+        }
+    }
+
+    #[test]
+    fn binary_to_source() {
+        assert_eq!(
+            parse_source("kernel-core kernel-6.5.6-300.fc39.src.rpm"),
+            Ok(("kernel-core", "kernel"))
+        );
+        assert_eq!(
+            parse_source("python3-requests python-requests-2.28.2-5.fc39.src.rpm"),
+            Ok(("python3-requests", "python-requests"))
+        );
+        assert!(parse_source("gpg-pubkey (none)").is_err());
+    }
+
+    #[test]
+    fn multiarch_duplicates() {
+        let packages = vec![
+            nevr("glibc", 0, "2.38", "7.fc39"),
+            nevr("mesa", 0, "23.2.1", "2.fc39"),
+            nevr("glibc", 0, "2.38", "7.fc39"),
+            nevr("kernel", 0, "6.5.6", "300.fc39"),
+            nevr("kernel", 0, "6.5.7", "300.fc39"),
+        ];
+
+        assert_eq!(
+            dedup(packages),
+            vec![
+                nevr("glibc", 0, "2.38", "7.fc39"),
+                nevr("kernel", 0, "6.5.6", "300.fc39"),
+                nevr("kernel", 0, "6.5.7", "300.fc39"),
+                nevr("mesa", 0, "23.2.1", "2.fc39"),
+            ]
+        );
+    }
+
+    #[test]
+    fn install_states() {
+        let installed = vec![
+            nevr("mesa", 0, "23.3.0~rc1", "1.fc40"),
+            nevr("fish", 0, "3.6.1^20231004git7d4d5c1", "1.fc40"),
+            nevr("kernel", 0, "6.5.6", "300.fc39"),
+            nevr("kernel", 0, "6.5.7", "300.fc39"),
+            nevr("vim", 2, "9.0.2048", "1.fc39"),
+        ];
+
+        let cases = [
+            // pre-releases sort before the final release
+            (nevr("mesa", 0, "23.3.0", "1.fc40"), InstallState::Older),
+            (nevr("mesa", 0, "23.3.0~rc2", "1.fc40"), InstallState::Older),
+            (nevr("mesa", 0, "23.3.0~rc1", "1.fc40"), InstallState::Installed),
+            // snapshots sort after the release they're based on, but before the next release
+            (nevr("fish", 0, "3.6.1", "1.fc40"), InstallState::Newer),
+            (nevr("fish", 0, "3.6.2", "1.fc40"), InstallState::Older),
+            // any installed version of a package can match
+            (nevr("kernel", 0, "6.5.6", "300.fc39"), InstallState::Installed),
+            (nevr("kernel", 0, "6.5.5", "300.fc39"), InstallState::Newer),
+            (nevr("kernel", 0, "6.5.8", "300.fc39"), InstallState::Older),
+            (nevr("vim", 1, "9.1.0", "1.fc39"), InstallState::Newer),
+            (nevr("emacs", 1, "29.1", "2.fc39"), InstallState::NotInstalled),
+        ];
+
+        for (build, expected) in cases {
+            assert_eq!(install_state(&installed, &build), expected, "{:?}", build);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::{HashMap, HashSet};
+
+use bodhi::data::{Update, UpdateStatus};
+
+use crate::dnf::{EPEL_TESTING_REPO, TESTING_REPO};
+use crate::nvr::NEVR;
+
+#[derive(Debug)]
+pub struct Orphan<'a> {
+    pub nevr: &'a NEVR,
+    // the update that contained the build, if there ever was one
+    pub update: Option<&'a Update>,
+}
+
+impl<'a> Orphan<'a> {
+    pub fn message(&self) -> String {
+        let nvr = nvr(self.nevr);
+
+        let reason = match self.update {
+            Some(update) => format!("update {} was {}", &update.alias, status(update)),
+            None => String::from("it is not part of any update in bodhi"),
+        };
+
+        format!(
+            "You are running an orphaned testing build: {} will never be pushed to stable ({}). \
+             Run \"sudo dnf distro-sync\" to go back to the version from the stable repositories.",
+            nvr, reason
+        )
+    }
+}
+
+fn nvr(nevr: &NEVR) -> String {
+    format!("{}-{}-{}", &nevr.n, &nevr.v, &nevr.r)
+}
+
+fn status(update: &Update) -> &'static str {
+    match update.status {
+        UpdateStatus::Unpushed => "unpushed",
+        UpdateStatus::Obsolete => "obsoleted",
+        _ => "withdrawn",
+    }
+}
+
+// builds that are still on their way to stable, or already got there
+fn is_alive(update: &Update) -> bool {
+    matches!(
+        update.status,
+        UpdateStatus::Pending | UpdateStatus::Testing | UpdateStatus::Stable
+    )
+}
+
+// returns installed packages that were installed from a testing repository, but are not part of any update in
+// testing, so they either went stable in the meantime or were orphaned
+pub fn candidates<'a>(
+    packages: &'a [NEVR],
+    updates: &[Update],
+    repos: &HashMap<String, HashSet<String>>,
+) -> Vec<&'a NEVR> {
+    let testing: HashSet<&str> = updates
+        .iter()
+        .flat_map(|update| &update.builds)
+        .map(|build| build.nvr.as_str())
+        .collect();
+
+    packages
+        .iter()
+        .filter(|nevr| match repos.get(&nevr.n) {
+            Some(repos) => repos
+                .iter()
+                .any(|repo| repo.starts_with(TESTING_REPO) || repo.starts_with(EPEL_TESTING_REPO)),
+            None => false,
+        })
+        .filter(|nevr| !testing.contains(nvr(nevr).as_str()))
+        .collect()
+}
+
+pub fn nvrs(candidates: &[&NEVR]) -> Vec<String> {
+    candidates.iter().map(|nevr| nvr(nevr)).collect()
+}
+
+// candidates are orphaned if none of the updates that contain them are still alive
+pub fn find<'a>(candidates: &[&'a NEVR], updates: &'a [Update]) -> Vec<Orphan<'a>> {
+    let mut orphans: Vec<Orphan> = Vec::new();
+
+    for nevr in candidates {
+        let nvr = nvr(nevr);
+        let containing: Vec<&Update> = updates
+            .iter()
+            .filter(|update| update.builds.iter().any(|build| build.nvr == nvr))
+            .collect();
+
+        if !containing.iter().any(|update| is_alive(update)) {
+            orphans.push(Orphan {
+                nevr,
+                update: containing.first().copied(),
+            });
+        }
+    }
+
+    orphans
+}
@@ -0,0 +1,987 @@
+use bodhi::data::FedoraRelease;
+use chrono::{DateTime, Local};
+use crossterm::style::Color;
+use serde::Serialize;
+
+use crate::cache::{HistoryEntry, MonthlyStats};
+use crate::changelog::ChangelogEntry;
+use crate::containers::is_container;
+use crate::dnf::{is_modular, testing_repo, RestartHints};
+use crate::journal;
+use crate::koji::CompletedBuild;
+use crate::l10n::{tr, trf};
+use crate::modules::module_streams;
+use crate::nvr::InstallState;
+use crate::overrides::BuildrootOverride;
+use crate::report::{Evaluation, Report};
+use crate::source::bodhi_url;
+use crate::table::{Cell, Table};
+use crate::target::Source;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown output format: {}", format)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BugSummary {
+    pub id: u32,
+    pub title: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TestCaseSummary {
+    pub name: String,
+    pub url: String,
+}
+
+impl TestCaseSummary {
+    fn new(name: &str) -> Self {
+        TestCaseSummary {
+            name: name.to_owned(),
+            url: format!("https://fedoraproject.org/wiki/{}", name.replace(' ', "_")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RebuildSummary {
+    pub package: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct KarmaSummary {
+    pub user: String,
+    pub karma: i32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PackageChangelog {
+    pub package: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateSummary {
+    pub alias: String,
+    pub url: String,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub update_type: String,
+    pub severity: String,
+    pub submitter: String,
+    pub notes: String,
+    pub builds: Vec<String>,
+    pub packages: Vec<String>,
+    pub newer_installed: Vec<String>,
+    pub days_installed: Option<i64>,
+    pub boots_installed: Option<u32>,
+    pub critpath: bool,
+    pub karma: i32,
+    pub karma_feedback: Vec<KarmaSummary>,
+    pub stable_karma: Option<i32>,
+    pub days_in_testing: Option<i64>,
+    pub days_to_autopush: Option<i64>,
+    pub gating: Option<String>,
+    pub bugs: Vec<BugSummary>,
+    pub test_cases: Vec<TestCaseSummary>,
+    pub changelogs: Vec<PackageChangelog>,
+    // packages whose continuous rebuilds in koschei are failing
+    pub failing_rebuilds: Vec<RebuildSummary>,
+    // where the update is relevant, only set for updates that were matched against installed packages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    // other systems where the update is relevant as well, if notifications for them were merged
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub also_in: Vec<Source>,
+}
+
+impl UpdateSummary {
+    pub fn new(evaluation: &Evaluation) -> Self {
+        let update = evaluation.update;
+
+        UpdateSummary {
+            alias: update.alias.clone(),
+            url: format!("{}/updates/{}", bodhi_url(), update.alias),
+            status: evaluation.status().to_owned(),
+            update_type: evaluation.update_type().to_owned(),
+            severity: evaluation.severity().as_str().to_owned(),
+            submitter: update.user.name.clone(),
+            notes: update.notes.clone(),
+            builds: update.builds.iter().map(|build| build.nvr.clone()).collect(),
+            packages: evaluation.builds.iter().map(|(nevr, _)| nevr.n.clone()).collect(),
+            newer_installed: evaluation
+                .builds
+                .iter()
+                .filter(|(_, state)| *state == InstallState::Newer)
+                .map(|(nevr, _)| nevr.n.clone())
+                .collect(),
+            critpath: evaluation.is_critpath(),
+            days_installed: evaluation.install.map(|install| install.days()),
+            boots_installed: evaluation.install.map(|install| install.boots),
+            karma: evaluation.karma(),
+            karma_feedback: match &update.comments {
+                Some(comments) => comments
+                    .iter()
+                    .filter(|comment| comment.karma != 0)
+                    .map(|comment| KarmaSummary {
+                        user: comment.user.name.clone(),
+                        karma: comment.karma,
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+            stable_karma: evaluation.stable_karma(),
+            days_in_testing: evaluation.days_in_testing(),
+            days_to_autopush: evaluation.days_to_autopush(),
+            gating: evaluation.gating_status().map(String::from),
+            bugs: update
+                .bugs
+                .iter()
+                .map(|bug| BugSummary {
+                    id: bug.bug_id,
+                    title: bug.title.clone().unwrap_or_default(),
+                })
+                .collect(),
+            test_cases: match &update.test_cases {
+                Some(test_cases) => test_cases.iter().map(|tc| TestCaseSummary::new(&tc.name)).collect(),
+                None => Vec::new(),
+            },
+            changelogs: Vec::new(),
+            failing_rebuilds: Vec::new(),
+            source: None,
+            also_in: Vec::new(),
+        }
+    }
+
+    // i.e. " in toolbox fedora-toolbox-39", updates for the local system don't need this
+    pub fn location(&self) -> String {
+        if !self.also_in.is_empty() {
+            let sources: Vec<String> = self.source.iter().chain(&self.also_in).map(Source::to_string).collect();
+            return format!(" in {}", sources.join(", "));
+        }
+
+        match &self.source {
+            Some(source) if !source.is_local() => format!(" in {}", source),
+            _ => String::new(),
+        }
+    }
+
+    // i.e. "security, severity: high, karma: 1/3, autopush in 4 days"
+    pub fn karma_info(&self) -> String {
+        let mut info = self.update_type.clone();
+
+        if self.severity != "unspecified" {
+            info.push_str(&format!(", severity: {}", self.severity));
+        }
+
+        match self.stable_karma {
+            Some(stable_karma) => info.push_str(&format!(", karma: {}/{}", self.karma, stable_karma)),
+            None => info.push_str(&format!(", karma: {}", self.karma)),
+        }
+
+        match self.days_to_autopush {
+            Some(0) => info.push_str(", autopush pending"),
+            Some(1) => info.push_str(", autopush in 1 day"),
+            Some(days) => info.push_str(&format!(", autopush in {} days", days)),
+            None => {},
+        }
+
+        if let Some(gating) = &self.gating {
+            info.push_str(&format!(", gating: {}", gating));
+        }
+
+        if self.critpath {
+            info.push_str(", critical path");
+        }
+
+        match self.days_installed {
+            Some(0) => info.push_str(", installed today"),
+            Some(1) => info.push_str(", installed 1 day ago"),
+            Some(days) => info.push_str(&format!(", installed {} days ago", days)),
+            None => {},
+        }
+
+        info
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub release: String,
+    pub feedback_url: Option<String>,
+    pub feedback: Vec<UpdateSummary>,
+    pub interesting_url: Option<String>,
+    pub pending: Vec<UpdateSummary>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<TargetSummary>,
+    // builds that completed in koji, but might not have been submitted to bodhi yet
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub builds: Vec<CompletedBuild>,
+    // services and the system need to be restarted before installed updates can be tested properly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<RestartHints>,
+}
+
+// results for other systems, i.e. toolbox containers
+#[derive(Debug, Serialize)]
+pub struct TargetSummary {
+    #[serde(flatten)]
+    pub source: Source,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+impl TargetSummary {
+    // i.e. " in toolbox fedora-toolbox-39", for notifications and terminal output
+    pub fn location(&self) -> String {
+        format!(" in {}", &self.source)
+    }
+}
+
+fn packages_url(release: &FedoraRelease, packages: &[&str]) -> String {
+    format!(
+        "{}/updates/?release={}&status=testing&packages={}",
+        bodhi_url(),
+        release,
+        packages.join(",")
+    )
+}
+
+impl Summary {
+    pub fn new(release: &FedoraRelease, report: &Report, interests: &[String], source: &Source) -> Self {
+        let installed_packages = report.installed_packages();
+
+        let feedback_url = if !installed_packages.is_empty() {
+            Some(packages_url(release, &installed_packages))
+        } else {
+            None
+        };
+
+        // interests can be expanded to hundreds of packages, so only link the ones with updates
+        let interesting_url = if !interests.is_empty() && !report.pending.is_empty() {
+            Some(packages_url(release, &report.pending_packages(interests)))
+        } else {
+            None
+        };
+
+        let summarize = |evaluation: &Evaluation| {
+            let mut update = UpdateSummary::new(evaluation);
+            update.source = Some(source.clone());
+            update
+        };
+
+        Summary {
+            release: release.to_string(),
+            feedback_url,
+            feedback: report.installed.iter().map(summarize).collect(),
+            interesting_url,
+            pending: report.pending.iter().map(summarize).collect(),
+            targets: Vec::new(),
+            builds: Vec::new(),
+            restart: None,
+        }
+    }
+
+    // i.e. to only list installed updates that are ready for feedback
+    pub fn select(&mut self, installed: bool, interesting: bool) {
+        if !installed {
+            self.feedback_url = None;
+            self.feedback.clear();
+        }
+        if !interesting {
+            self.interesting_url = None;
+            self.pending.clear();
+        }
+
+        for target in &mut self.targets {
+            target.summary.select(installed, interesting);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateEntry {
+    pub alias: String,
+    pub url: String,
+    pub karma: i32,
+    pub stable_karma: Option<i32>,
+    // machines where builds of the update are installed, but feedback is missing
+    pub installed_on: Vec<String>,
+    // machines where the update is available for interesting packages
+    pub available_on: Vec<String>,
+}
+
+// updates grouped by advisory across all checked machines
+#[derive(Debug, Serialize)]
+pub struct Aggregate {
+    pub machines: Vec<String>,
+    pub updates: Vec<AggregateEntry>,
+}
+
+impl Aggregate {
+    pub fn new(summary: &Summary) -> Self {
+        let mut machines = vec![Source::local().name];
+        machines.extend(summary.targets.iter().map(|target| target.source.name.clone()));
+
+        let summaries = std::iter::once(summary).chain(summary.targets.iter().map(|target| &target.summary));
+
+        let mut updates: Vec<AggregateEntry> = Vec::new();
+        for (machine, summary) in machines.iter().zip(summaries) {
+            let all = summary
+                .feedback
+                .iter()
+                .map(|update| (update, true))
+                .chain(summary.pending.iter().map(|update| (update, false)));
+
+            for (update, installed) in all {
+                let position = match updates.iter().position(|entry| entry.alias == update.alias) {
+                    Some(position) => position,
+                    None => {
+                        updates.push(AggregateEntry {
+                            alias: update.alias.clone(),
+                            url: update.url.clone(),
+                            karma: update.karma,
+                            stable_karma: update.stable_karma,
+                            installed_on: Vec::new(),
+                            available_on: Vec::new(),
+                        });
+                        updates.len() - 1
+                    },
+                };
+
+                if installed {
+                    updates[position].installed_on.push(machine.clone());
+                } else {
+                    updates[position].available_on.push(machine.clone());
+                }
+            }
+        }
+
+        // updates that are installed on the most machines first
+        updates.sort_by_key(|entry| std::cmp::Reverse(entry.installed_on.len()));
+
+        Aggregate { machines, updates }
+    }
+}
+
+pub fn print_aggregate_text(aggregate: &Aggregate) {
+    let total = aggregate.machines.len();
+
+    println!("Checked machines: {}", aggregate.machines.join(", "));
+    println!();
+    println!("{:<24} {:>9} {:>9} {:>7}", "UPDATE", "INSTALLED", "AVAILABLE", "KARMA");
+
+    for entry in &aggregate.updates {
+        let karma = match entry.stable_karma {
+            Some(stable_karma) => format!("{}/{}", entry.karma, stable_karma),
+            None => entry.karma.to_string(),
+        };
+
+        println!(
+            "{:<24} {:>9} {:>9} {:>7}",
+            &entry.alias,
+            format!("{}/{}", entry.installed_on.len(), total),
+            format!("{}/{}", entry.available_on.len(), total),
+            karma
+        );
+    }
+
+    for entry in aggregate.updates.iter().filter(|entry| !entry.installed_on.is_empty()) {
+        println!();
+        println!(
+            "{}: installed on {}/{} machines, feedback missing ({})",
+            &entry.alias,
+            entry.installed_on.len(),
+            total,
+            entry.installed_on.join(", ")
+        );
+    }
+}
+
+pub fn print_aggregate_json(aggregate: &Aggregate) -> Result<(), String> {
+    match serde_json::to_string_pretty(aggregate) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+// notification body with the bodhi URL and a short line for every update
+pub fn notification_body(url: &str, updates: &[UpdateSummary]) -> String {
+    let mut body = String::from(url);
+
+    for update in updates {
+        body.push_str(&format!(
+            "\n{}{} ({})",
+            &update.alias,
+            update.location(),
+            update.karma_info()
+        ));
+    }
+
+    body
+}
+
+pub fn print_json(summary: &Summary) -> Result<(), String> {
+    match serde_json::to_string_pretty(summary) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+fn print_details(update: &UpdateSummary) {
+    for bug in &update.bugs {
+        println!("  * rhbz#{}: {}", bug.id, &bug.title);
+    }
+    for test_case in &update.test_cases {
+        println!("  * test case: {} ({})", &test_case.name, &test_case.url);
+    }
+    for rebuild in &update.failing_rebuilds {
+        println!(
+            "  * rebuilds of {} are failing in koschei ({})",
+            &rebuild.package, &rebuild.url
+        );
+    }
+    print_changelogs(update, "  ");
+}
+
+fn has_details(update: &UpdateSummary) -> bool {
+    !update.bugs.is_empty()
+        || !update.test_cases.is_empty()
+        || !update.failing_rebuilds.is_empty()
+        || update.changelogs.iter().any(|changelog| !changelog.entries.is_empty())
+}
+
+// details don't fit into tables, so they are listed below them
+fn print_all_details(updates: &[UpdateSummary]) {
+    for update in updates.iter().filter(|update| has_details(update)) {
+        println!("{}:", &update.alias);
+        print_details(update);
+    }
+}
+
+fn print_changelogs(update: &UpdateSummary, indent: &str) {
+    for changelog in &update.changelogs {
+        if changelog.entries.is_empty() {
+            continue;
+        }
+
+        println!(
+            "{}Changes in {} since the installed version:",
+            indent, &changelog.package
+        );
+        for entry in &changelog.entries {
+            println!("{}  * {}", indent, &entry.author);
+            for line in entry.text.lines() {
+                println!("{}    {}", indent, line);
+            }
+        }
+    }
+}
+
+pub fn install_command(alias: &str) -> String {
+    format!(
+        "sudo dnf upgrade --enablerepo={} --advisory={}",
+        testing_repo(alias),
+        alias
+    )
+}
+
+// prints everything about an update that would otherwise require opening its bodhi page
+pub fn print_update(update: &UpdateSummary) {
+    println!("{} ({})", &update.alias, &update.url);
+    println!("{}", trf("submitter", &[("submitter", &update.submitter)]));
+    println!("{}", trf("status", &[("status", &update.karma_info())]));
+    if let Some(source) = update.source.as_ref().filter(|source| !source.is_local()) {
+        println!("{}", trf("system", &[("system", source)]));
+    }
+
+    println!("{}", tr("builds"));
+    for build in &update.builds {
+        println!("  - {}", build);
+    }
+
+    if !update.notes.trim().is_empty() {
+        println!("{}", tr("notes"));
+        for line in update.notes.trim().lines() {
+            println!("  {}", line);
+        }
+    }
+
+    if !update.karma_feedback.is_empty() {
+        println!("{}", tr("karma"));
+        for feedback in &update.karma_feedback {
+            println!("  {:+} from {}", feedback.karma, &feedback.user);
+        }
+    }
+
+    if !update.bugs.is_empty() {
+        println!("{}", tr("bugs"));
+        for bug in &update.bugs {
+            println!("  - rhbz#{}: {}", bug.id, &bug.title);
+        }
+    }
+
+    if !update.test_cases.is_empty() {
+        println!("{}", tr("test-cases"));
+        for test_case in &update.test_cases {
+            println!("  - {} ({})", &test_case.name, &test_case.url);
+        }
+    }
+
+    if !update.failing_rebuilds.is_empty() {
+        println!("Failing rebuilds in koschei:");
+        for rebuild in &update.failing_rebuilds {
+            println!("  - {} ({})", &rebuild.package, &rebuild.url);
+        }
+    }
+
+    print_changelogs(update, "");
+
+    // candidate images are pulled from the registry, not installed with dnf
+    if is_container(&update.alias) {
+        return;
+    }
+
+    println!("{}", tr("install-with"));
+    if is_modular(&update.alias) {
+        for stream in module_streams(&update.builds) {
+            println!("  sudo dnf module enable {}", stream);
+        }
+    }
+    println!("  fedora-update-notifier install {}", &update.alias);
+    println!("  {}", install_command(&update.alias));
+}
+
+pub fn print_json_update(update: &UpdateSummary) -> Result<(), String> {
+    match serde_json::to_string_pretty(update) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverrideSummary {
+    pub nvr: String,
+    pub url: String,
+    pub expiration_date: String,
+    pub days_left: Option<i64>,
+}
+
+impl OverrideSummary {
+    pub fn new(buildroot_override: &BuildrootOverride) -> Self {
+        OverrideSummary {
+            nvr: buildroot_override.nvr.clone(),
+            url: buildroot_override.url(),
+            expiration_date: buildroot_override.expiration_date.clone(),
+            days_left: buildroot_override.days_left(),
+        }
+    }
+}
+
+// status of updates and buildroot overrides of the current user, for packagers
+#[derive(Debug, Serialize)]
+pub struct MineSummary {
+    pub updates: Vec<UpdateSummary>,
+    pub overrides: Vec<OverrideSummary>,
+}
+
+pub fn print_mine(mine: &MineSummary, warning_days: i64) {
+    let (stable, active): (Vec<&UpdateSummary>, Vec<&UpdateSummary>) =
+        mine.updates.iter().partition(|update| update.status == "stable");
+
+    if active.is_empty() {
+        println!("You have no updates in testing.");
+    } else {
+        println!("Your updates:");
+    }
+
+    for update in active {
+        println!();
+        println!("{} ({}): {}", &update.alias, &update.status, update.builds.join(", "));
+        println!("  {}", update.karma_info());
+
+        let negative: Vec<String> = update
+            .karma_feedback
+            .iter()
+            .filter(|feedback| feedback.karma < 0)
+            .map(|feedback| format!("{} ({:+})", &feedback.user, feedback.karma))
+            .collect();
+        if !negative.is_empty() {
+            println!("  Negative feedback: {}", negative.join(", "));
+        }
+
+        println!("  {}", &update.url);
+    }
+
+    if !stable.is_empty() {
+        println!();
+        println!("Pushed to stable recently:");
+        for update in stable {
+            println!("- {}: {}", &update.alias, update.builds.join(", "));
+        }
+    }
+
+    if !mine.overrides.is_empty() {
+        println!();
+        println!("Buildroot overrides:");
+        for summary in &mine.overrides {
+            match summary.days_left {
+                Some(days) if days <= warning_days => println!(
+                    "- {}: expires {} (extend with \"fedora-update-notifier extend-override {}\")",
+                    &summary.nvr, &summary.expiration_date, &summary.nvr
+                ),
+                _ => println!("- {}: expires {}", &summary.nvr, &summary.expiration_date),
+            }
+        }
+    }
+}
+
+pub fn print_history(entries: &[HistoryEntry]) {
+    if entries.is_empty() {
+        println!("No updates were recorded yet.");
+        return;
+    }
+
+    let mut table = Table::new(&["DATE", "EVENT", "UPDATE", "KARMA"]);
+    for entry in entries {
+        let date = match DateTime::from_timestamp(entry.time, 0) {
+            Some(time) => time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+            None => String::new(),
+        };
+        let karma = match entry.karma {
+            Some(karma) if karma < 0 => Cell::colored(format!("{:+}", karma), Some(Color::Red)),
+            Some(karma) if karma > 0 => Cell::colored(format!("{:+}", karma), Some(Color::Green)),
+            Some(karma) => Cell::new(karma.to_string()),
+            None => Cell::new(""),
+        };
+
+        table.push(vec![
+            Cell::new(date),
+            Cell::new(&entry.event),
+            Cell::new(&entry.alias),
+            karma,
+        ]);
+    }
+    table.print();
+}
+
+pub fn print_json_history(entries: &[HistoryEntry]) -> Result<(), String> {
+    match serde_json::to_string_pretty(entries) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+pub fn print_stats(stats: &[MonthlyStats]) {
+    if stats.is_empty() {
+        println!("No updates were recorded yet.");
+        return;
+    }
+
+    let mut table = Table::new(&["MONTH", "NOTIFIED", "TESTED", "COMMENTED", "KARMA"]);
+    for month in stats {
+        table.push(vec![
+            Cell::new(&month.month),
+            Cell::new(month.notified.to_string()),
+            Cell::new(month.tested.to_string()),
+            Cell::new(month.commented.to_string()),
+            Cell::new(month.karma.to_string()),
+        ]);
+    }
+    table.print();
+}
+
+pub fn print_json_stats(stats: &[MonthlyStats]) -> Result<(), String> {
+    match serde_json::to_string_pretty(stats) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+pub fn print_json_mine(mine: &MineSummary) -> Result<(), String> {
+    match serde_json::to_string_pretty(mine) {
+        Ok(json) => {
+            println!("{}", json);
+            Ok(())
+        },
+        Err(error) => Err(format!("{}", error)),
+    }
+}
+
+fn days(days: Option<i64>) -> String {
+    match days {
+        Some(1) => String::from("1 day"),
+        Some(days) => format!("{} days", days),
+        None => String::from("-"),
+    }
+}
+
+fn severity_color(update: &UpdateSummary) -> Option<Color> {
+    match update.severity.as_str() {
+        "urgent" | "high" => Some(Color::Red),
+        "medium" => Some(Color::Yellow),
+        _ => None,
+    }
+}
+
+fn karma_color(update: &UpdateSummary) -> Option<Color> {
+    match update.stable_karma {
+        _ if update.karma < 0 => Some(Color::Red),
+        Some(stable_karma) if update.karma >= stable_karma => Some(Color::Green),
+        _ => None,
+    }
+}
+
+// updates that will be pushed to stable without reaching their stable karma threshold
+fn autopush_color(update: &UpdateSummary) -> Option<Color> {
+    match (update.days_to_autopush, update.stable_karma) {
+        (Some(_), Some(stable_karma)) if update.karma < stable_karma => Some(Color::Yellow),
+        _ => None,
+    }
+}
+
+// installed updates list the installed packages, other updates list their builds
+fn print_table(updates: &[UpdateSummary], installed: bool) {
+    let mut header = vec!["UPDATE", "TYPE", "SEVERITY", "KARMA", "TESTING", "AUTOPUSH", "GATING"];
+    if installed {
+        header.extend(["INSTALLED", "PACKAGES"]);
+    } else {
+        header.push("BUILDS");
+    }
+    let mut table = Table::new(&header);
+
+    for update in updates {
+        let mut update_type = update.update_type.clone();
+        if update.critpath {
+            update_type.push_str(", critpath");
+        }
+
+        let karma = match update.stable_karma {
+            Some(stable_karma) => format!("{}/{}", update.karma, stable_karma),
+            None => update.karma.to_string(),
+        };
+
+        let packages: Vec<String> = if installed {
+            update
+                .packages
+                .iter()
+                .map(|package| {
+                    if update.newer_installed.contains(package) {
+                        format!("{} (newer build installed)", package)
+                    } else {
+                        package.clone()
+                    }
+                })
+                .collect()
+        } else {
+            update.builds.clone()
+        };
+
+        let mut row = vec![
+            Cell::new(&update.alias),
+            Cell::colored(
+                update_type,
+                Some(Color::Red).filter(|_| update.update_type == "security"),
+            ),
+            Cell::colored(&update.severity, severity_color(update)),
+            Cell::colored(karma, karma_color(update)),
+            Cell::new(days(update.days_in_testing)),
+            Cell::colored(days(update.days_to_autopush), autopush_color(update)),
+            Cell::new(update.gating.as_deref().unwrap_or("-")),
+        ];
+        if installed {
+            row.push(Cell::new(days(update.days_installed)));
+        }
+        row.push(Cell::new(packages.join(", ")));
+
+        table.push(row);
+    }
+
+    table.print();
+}
+
+fn print_list_section(heading: &str, updates: &[UpdateSummary], installed: bool) {
+    if updates.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{}", heading);
+    print_table(updates, installed);
+}
+
+fn print_list_summary(summary: &Summary, location: &str) {
+    print_list_section(
+        &trf("installed-ready-list", &[("location", &location)]),
+        &summary.feedback,
+        true,
+    );
+    print_list_section(
+        &trf("interesting-available-list", &[("location", &location)]),
+        &summary.pending,
+        false,
+    );
+}
+
+// one line per update, without notes or links
+pub fn print_list(summary: &Summary) {
+    print_list_summary(summary, "");
+
+    for target in &summary.targets {
+        print_list_summary(&target.summary, &target.location());
+    }
+}
+
+pub fn print_text(summary: &Summary, interests: &[String], details: bool) {
+    print_summary(summary, interests, details, "");
+
+    for target in &summary.targets {
+        print_summary(&target.summary, interests, details, &target.location());
+    }
+
+    print_builds(&summary.builds);
+}
+
+fn print_builds(builds: &[CompletedBuild]) {
+    if builds.is_empty() {
+        return;
+    }
+
+    if journal::is_journal() {
+        let nvrs: Vec<&str> = builds.iter().map(|build| build.nvr.as_str()).collect();
+        journal::info(&format!("New builds completed in koji: {}", nvrs.join(", ")));
+        return;
+    }
+
+    println!();
+    println!("{}", tr("koji-builds"));
+    for build in builds {
+        println!("- {} by {} ({})", &build.nvr, &build.owner, &build.url);
+    }
+}
+
+fn print_restart(restart: &RestartHints) {
+    if restart.reboot {
+        println!();
+        println!("{}", tr("reboot-needed"));
+    }
+
+    if !restart.services.is_empty() {
+        println!();
+        println!("{}", tr("restart-services"));
+        for service in &restart.services {
+            println!("  {}", service);
+        }
+    }
+}
+
+fn print_summary(summary: &Summary, interests: &[String], details: bool, location: &str) {
+    let journal = journal::is_journal();
+
+    if !journal {
+        println!();
+    }
+
+    match &summary.feedback_url {
+        Some(feedback_url) if journal => {
+            let aliases: Vec<&str> = summary.feedback.iter().map(|u| u.alias.as_str()).collect();
+            journal::info(&trf(
+                "installed-ready-aliases",
+                &[
+                    ("location", &location),
+                    ("aliases", &aliases.join(", ")),
+                    ("url", feedback_url),
+                ],
+            ));
+        },
+        Some(feedback_url) if details => {
+            println!("{}", trf("installed-ready-list", &[("location", &location)]));
+            for update in &summary.feedback {
+                println!();
+                print_update(update);
+            }
+            if let Some(restart) = &summary.restart {
+                print_restart(restart);
+            }
+            println!();
+            println!("{}", trf("feedback-url", &[("url", feedback_url)]));
+        },
+        Some(feedback_url) => {
+            println!("{}", trf("installed-ready-list", &[("location", &location)]));
+            print_table(&summary.feedback, true);
+            print_all_details(&summary.feedback);
+            println!("{}", trf("feedback-url", &[("url", feedback_url)]));
+        },
+        None => {
+            journal::info(&trf("installed-none", &[("location", &location)]));
+        },
+    }
+
+    if interests.is_empty() {
+        return;
+    }
+
+    if !journal {
+        println!();
+    }
+
+    match &summary.interesting_url {
+        Some(interesting_url) if journal => {
+            let aliases: Vec<&str> = summary.pending.iter().map(|u| u.alias.as_str()).collect();
+            journal::info(&trf(
+                "interesting-available-aliases",
+                &[
+                    ("location", &location),
+                    ("aliases", &aliases.join(", ")),
+                    ("url", interesting_url),
+                ],
+            ));
+        },
+        Some(_) if details => {
+            println!("{}", trf("interesting-available-list", &[("location", &location)]));
+            for update in &summary.pending {
+                println!();
+                print_update(update);
+            }
+        },
+        Some(_) => {
+            println!("{}", trf("interesting-available-list", &[("location", &location)]));
+            print_table(&summary.pending, false);
+            print_all_details(&summary.pending);
+
+            println!("{}", tr("install-relevant"));
+            println!("fedora-update-notifier install UPDATE_ALIAS");
+        },
+        None => {
+            journal::info(&trf("interesting-none", &[("location", &location)]));
+        },
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::feedback::{client, csrf_token};
+use crate::retry::{retry, send, RetryPolicy};
+use crate::source::bodhi_url;
+
+pub const DEFAULT_WARNING_DAYS: i64 = 2;
+pub const DEFAULT_EXTEND_DAYS: i64 = 7;
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// buildroot overrides make builds available in the buildroot before they are pushed to stable
+#[derive(Debug, Deserialize)]
+pub struct BuildrootOverride {
+    pub nvr: String,
+    pub notes: String,
+    pub expiration_date: String,
+}
+
+#[derive(Debug, Serialize)]
+struct EditedOverride<'a> {
+    nvr: &'a str,
+    notes: &'a str,
+    expiration_date: String,
+    expired: bool,
+    edited: &'a str,
+    csrf_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverrideResponse {
+    #[serde(rename = "override")]
+    buildroot_override: BuildrootOverride,
+}
+
+impl BuildrootOverride {
+    pub fn url(&self) -> String {
+        format!("{}/overrides/{}", bodhi_url(), self.nvr)
+    }
+
+    pub fn days_left(&self) -> Option<i64> {
+        let expiration = NaiveDateTime::parse_from_str(&self.expiration_date, DATE_FORMAT).ok()?;
+        Some((expiration - Utc::now().naive_utc()).num_days())
+    }
+}
+
+// sets the expiration date of an override to some days from now
+pub async fn extend(access_token: &str, nvr: &str, days: i64) -> Result<String, String> {
+    let client = client()?;
+
+    let what = format!("Querying buildroot override for {}", nvr);
+    let response = retry(RetryPolicy::default(), &what, || {
+        send(client.get(format!("{}/overrides/{}", bodhi_url(), nvr)))
+    })
+    .await?;
+
+    let current: OverrideResponse = match response.json().await {
+        Ok(current) => current,
+        Err(error) => {
+            return Err(format!("Unexpected response from bodhi: {}", error));
+        },
+    };
+
+    let expiration_date = (Utc::now() + Duration::days(days)).format(DATE_FORMAT).to_string();
+
+    let edited = EditedOverride {
+        nvr,
+        notes: &current.buildroot_override.notes,
+        expiration_date: expiration_date.clone(),
+        expired: false,
+        edited: nvr,
+        csrf_token: csrf_token(&client, access_token).await?,
+    };
+
+    match client
+        .post(format!("{}/overrides/", bodhi_url()))
+        .bearer_auth(access_token)
+        .json(&edited)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => Ok(expiration_date),
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Failed to extend buildroot override ({}): {}", status, body))
+        },
+        Err(error) => Err(format!("Failed to extend buildroot override: {}", error)),
+    }
+}
@@ -0,0 +1,145 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{DynamicType, OwnedObjectPath};
+use zbus::Message;
+
+use crate::cache::cache_dir;
+use crate::dnf::TESTING_REPO;
+
+const PACKAGEKIT: &str = "org.freedesktop.PackageKit";
+const PACKAGEKIT_PATH: &str = "/org/freedesktop/PackageKit";
+const PACKAGEKIT_OFFLINE: &str = "org.freedesktop.PackageKit.Offline";
+const PACKAGEKIT_TRANSACTION: &str = "org.freedesktop.PackageKit.Transaction";
+
+// PK_TRANSACTION_FLAG_ENUM_ONLY_TRUSTED | PK_TRANSACTION_FLAG_ENUM_ONLY_DOWNLOAD
+const FLAGS_ONLY_DOWNLOAD: u64 = (1 << 1) | (1 << 3);
+
+// written by PackageKit when an offline update was downloaded, and removed after it was applied
+const PREPARED_UPDATE: &str = "/var/lib/PackageKit/prepared-update";
+
+// left behind by older versions, which enabled updates-testing (or the repository named in the file) for staging
+const TESTING_MARKER: &str = "packagekit-updates-testing";
+
+pub struct PackageKit {
+    connection: Connection,
+}
+
+impl PackageKit {
+    pub fn new() -> Result<Self, String> {
+        match Connection::system() {
+            Ok(connection) => Ok(PackageKit { connection }),
+            Err(error) => Err(format!("Failed to connect to the system bus: {}", error)),
+        }
+    }
+
+    // runs a PackageKit transaction and returns all signals that were emitted until it finished
+    fn transaction<B>(&self, method: &str, body: &B) -> Result<Vec<Arc<Message>>, zbus::Error>
+    where
+        B: Serialize + DynamicType,
+    {
+        let manager = Proxy::new(&self.connection, PACKAGEKIT, PACKAGEKIT_PATH, PACKAGEKIT)?;
+        let path: OwnedObjectPath = manager.call("CreateTransaction", &())?;
+
+        let transaction = Proxy::new(&self.connection, PACKAGEKIT, path.as_str(), PACKAGEKIT_TRANSACTION)?;
+        let signals = transaction.receive_all_signals()?;
+
+        // allow polkit to ask for authentication
+        transaction.call_method("SetHints", &(vec!["interactive=true"],))?;
+        transaction.call_method(method, body)?;
+
+        let mut messages = Vec::new();
+        for message in signals {
+            let member = message.member().map(|member| member.to_string());
+            match member.as_deref() {
+                Some("ErrorCode") => {
+                    let (_code, details): (u32, String) = message.body()?;
+                    return Err(zbus::Error::Failure(details));
+                },
+                Some("Finished") => break,
+                _ => messages.push(message),
+            }
+        }
+
+        Ok(messages)
+    }
+
+    fn is_repo_enabled(&self, repo: &str) -> Result<bool, zbus::Error> {
+        for message in self.transaction("GetRepoList", &(0u64,))? {
+            if message.member().as_deref() == Some("RepoDetail") {
+                let (id, _description, enabled): (String, String, bool) = message.body()?;
+                if id == repo {
+                    return Ok(enabled);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn disable_repo(&self, repo: &str) -> Result<(), zbus::Error> {
+        self.transaction("RepoEnable", &(repo, false)).map(|_| ())
+    }
+
+    // downloads updates and prepares them to be installed on the next reboot
+    pub fn stage(&self, package_ids: &[String], repo: &str) -> Result<(), String> {
+        // PackageKit has no per-transaction repositories, and enabling updates-testing system-wide until the next
+        // reboot would make dnf and GNOME Software pull in all testing updates, not only the selected ones
+        match self.is_repo_enabled(repo) {
+            Ok(true) => {},
+            Ok(false) => {
+                return Err(format!(
+                    "The {} repository is not enabled, use \"install\" for these updates instead.",
+                    repo
+                ));
+            },
+            Err(error) => {
+                return Err(format!("Failed to query repositories from PackageKit: {}", error));
+            },
+        }
+
+        if let Err(error) = self.transaction("UpdatePackages", &(FLAGS_ONLY_DOWNLOAD, package_ids)) {
+            return Err(format!("Failed to download updates: {}", error));
+        }
+
+        let offline = match Proxy::new(&self.connection, PACKAGEKIT, PACKAGEKIT_PATH, PACKAGEKIT_OFFLINE) {
+            Ok(offline) => offline,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        match offline.call_method("Trigger", &("reboot",)) {
+            Ok(_) => Ok(()),
+            Err(error) => Err(format!("Failed to schedule offline update: {}", error)),
+        }
+    }
+}
+
+// disables updates-testing again if an older version enabled it for staging updates that were installed since then
+pub fn restore_testing_repo() -> Result<(), String> {
+    let marker = match cache_dir() {
+        Some(dir) => dir.join(TESTING_MARKER),
+        None => return Ok(()),
+    };
+
+    if !marker.exists() || Path::new(PREPARED_UPDATE).exists() {
+        return Ok(());
+    }
+
+    // markers from older versions are empty
+    let repo = std::fs::read_to_string(&marker).unwrap_or_default();
+    let repo = match repo.trim() {
+        "" => TESTING_REPO,
+        repo => repo,
+    };
+
+    if let Err(error) = PackageKit::new()?.disable_repo(repo) {
+        return Err(format!("Failed to disable {}: {}", repo, error));
+    }
+
+    let _ = std::fs::remove_file(marker);
+    Ok(())
+}
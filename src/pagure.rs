@@ -0,0 +1,101 @@
+use serde::Deserialize;
+
+use crate::http;
+use crate::retry::{retry, RetryPolicy};
+
+const PAGURE_URL: &str = "https://src.fedoraproject.org";
+
+const PROJECTS_PER_PAGE: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+struct ProjectList {
+    projects: Vec<Project>,
+    #[serde(default)]
+    pagination: Option<Pagination>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Project {
+    name: String,
+    namespace: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pagination {
+    pages: u32,
+}
+
+pub struct Pagure {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl Pagure {
+    pub fn new(policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(Pagure { client, policy })
+    }
+
+    async fn fetch(&self, path: &str, params: &[(&str, &str)], page: u32) -> Result<ProjectList, reqwest::Error> {
+        self.client
+            .get(format!("{}/api/0/{}", PAGURE_URL, path))
+            .query(params)
+            .query(&[("page", page), ("per_page", PROJECTS_PER_PAGE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    async fn fetch_all(&self, path: &str, params: &[(&str, &str)]) -> Result<Vec<String>, String> {
+        let mut packages = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let list = retry(
+                self.policy,
+                &format!("Querying dist-git for {} (page {})", path, page),
+                || self.fetch(path, params, page),
+            )
+            .await?;
+
+            packages.extend(
+                list.projects
+                    .into_iter()
+                    .filter(|project| project.namespace.as_deref() == Some("rpms"))
+                    .map(|project| project.name),
+            );
+
+            match list.pagination {
+                Some(pagination) if page < pagination.pages => page += 1,
+                _ => break,
+            }
+        }
+
+        Ok(packages)
+    }
+
+    // returns the source packages that a user (or a "@group") has commit access to
+    pub async fn packages(&self, maintainer: &str) -> Result<Vec<String>, String> {
+        match maintainer.strip_prefix('@') {
+            Some(group) => {
+                self.fetch_all(&format!("group/{}", group), &[("projects", "true"), ("acl", "commit")])
+                    .await
+            },
+            None => {
+                self.fetch_all(
+                    "projects",
+                    &[("namespace", "rpms"), ("username", maintainer), ("short", "true")],
+                )
+                .await
+            },
+        }
+    }
+}
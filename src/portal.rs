@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use zbus::zvariant::Value;
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const NOTIFICATION_INTERFACE: &str = "org.freedesktop.portal.Notification";
+
+// flatpak puts this file into the root of every sandbox
+pub fn is_sandboxed() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+// notifications with the same ID replace each other, the portal shows them with the icon of the application
+pub async fn add_notification(id: &str, title: &str, body: &str, priority: &str) -> Result<(), String> {
+    let connection = match zbus::Connection::session().await {
+        Ok(connection) => connection,
+        Err(error) => {
+            return Err(format!("Failed to connect to the session bus: {}", error));
+        },
+    };
+
+    let mut notification: HashMap<&str, Value> = HashMap::new();
+    notification.insert("title", Value::from(title));
+    notification.insert("body", Value::from(body));
+    notification.insert("priority", Value::from(priority));
+
+    match connection
+        .call_method(
+            Some(PORTAL_BUS_NAME),
+            PORTAL_OBJECT_PATH,
+            Some(NOTIFICATION_INTERFACE),
+            "AddNotification",
+            &(id, notification),
+        )
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(format!("Failed to send notification through the portal: {}", error)),
+    }
+}
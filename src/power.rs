@@ -0,0 +1,73 @@
+use std::convert::TryFrom;
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedValue;
+
+// NMMetered values that mean the connection is (probably) metered
+const NM_METERED_YES: u32 = 1;
+const NM_METERED_GUESS_YES: u32 = 3;
+
+// NMConnectivityState values
+const NM_CONNECTIVITY_NONE: u32 = 1;
+const NM_CONNECTIVITY_PORTAL: u32 = 2;
+const NM_CONNECTIVITY_FULL: u32 = 4;
+
+fn system_property<T>(destination: &str, path: &str, interface: &str, property: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+    T::Error: Into<zbus::Error>,
+{
+    let connection = Connection::system().ok()?;
+    let proxy = Proxy::new(&connection, destination, path, interface).ok()?;
+    proxy.get_property::<T>(property).ok()
+}
+
+// without NetworkManager, connections are assumed to not be metered
+pub fn is_metered() -> bool {
+    let metered: Option<u32> = system_property(
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+        "Metered",
+    );
+
+    matches!(metered, Some(NM_METERED_YES | NM_METERED_GUESS_YES))
+}
+
+// None if NetworkManager is not available or doesn't know (i.e. if connectivity checking is disabled)
+pub fn has_connectivity() -> Option<bool> {
+    let connectivity: Option<u32> = system_property(
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+        "Connectivity",
+    );
+
+    match connectivity {
+        Some(NM_CONNECTIVITY_FULL) => Some(true),
+        // behind a captive portal, nothing but the login page is reachable
+        Some(NM_CONNECTIVITY_NONE | NM_CONNECTIVITY_PORTAL) => Some(false),
+        _ => None,
+    }
+}
+
+// battery charge in percent, or None if the system is running on AC power (or UPower is not available)
+pub fn battery_level() -> Option<f64> {
+    let on_battery: bool = system_property(
+        "org.freedesktop.UPower",
+        "/org/freedesktop/UPower",
+        "org.freedesktop.UPower",
+        "OnBattery",
+    )?;
+
+    if !on_battery {
+        return None;
+    }
+
+    system_property(
+        "org.freedesktop.UPower",
+        "/org/freedesktop/UPower/devices/DisplayDevice",
+        "org.freedesktop.UPower.Device",
+        "Percentage",
+    )
+}
@@ -0,0 +1,52 @@
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use crate::journal;
+
+// all progress bars are drawn together, so concurrent queries don't overwrite each other
+static PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+
+// progress is only shown in terminals, and not with --quiet
+pub fn init(quiet: bool) {
+    let target = if !quiet && !journal::is_journal() && std::io::stderr().is_terminal() {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+
+    let _ = PROGRESS.set(MultiProgress::with_draw_target(target));
+}
+
+fn add(bar: ProgressBar) -> ProgressBar {
+    match PROGRESS.get() {
+        Some(progress) => progress.add(bar),
+        None => {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+            bar
+        },
+    }
+}
+
+// for operations that don't report how far along they are, i.e. dnf repoquery
+pub fn spinner(message: impl Into<Cow<'static, str>>) -> ProgressBar {
+    let bar = add(ProgressBar::new_spinner());
+    if let Ok(style) = ProgressStyle::with_template("{spinner} {msg} ({elapsed})") {
+        bar.set_style(style);
+    }
+    bar.set_message(message);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+pub fn pages(message: impl Into<Cow<'static, str>>, pages: u64) -> ProgressBar {
+    let bar = add(ProgressBar::new(pages));
+    if let Ok(style) = ProgressStyle::with_template("{msg} [{bar:30}] page {pos}/{len}") {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar.set_message(message);
+    bar
+}
@@ -0,0 +1,144 @@
+use std::process::Command;
+
+use chrono::{Local, Timelike};
+use std::convert::TryFrom;
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+
+// notifications are held back during this time of day, i.e. "22:00-07:00"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuietHours {
+    // minutes since midnight
+    start: u32,
+    end: u32,
+}
+
+fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+
+    if hours < 24 && minutes < 60 {
+        Some(hours * 60 + minutes)
+    } else {
+        None
+    }
+}
+
+impl QuietHours {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let parsed = value
+            .split_once('-')
+            .and_then(|(start, end)| Some((parse_time(start)?, parse_time(end)?)));
+
+        match parsed {
+            Some((start, end)) => Ok(QuietHours { start, end }),
+            None => Err(format!(
+                "Invalid quiet hours (expected i.e. \"22:00-07:00\"): {}",
+                value
+            )),
+        }
+    }
+
+    fn contains(&self, minute: u32) -> bool {
+        if self.start <= self.end {
+            self.start <= minute && minute < self.end
+        } else {
+            // quiet hours that span midnight
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    pub fn is_now(&self) -> bool {
+        let now = Local::now();
+        self.contains(now.hour() * 60 + now.minute())
+    }
+}
+
+// GNOME keeps its do-not-disturb switch in gsettings
+fn gnome_do_not_disturb() -> bool {
+    match Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+    {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim() == "false",
+        _ => false,
+    }
+}
+
+// KDE Plasma exposes it as a property of the notification server
+fn kde_do_not_disturb() -> bool {
+    let connection = match Connection::session() {
+        Ok(connection) => connection,
+        Err(_) => return false,
+    };
+
+    let proxy = match Proxy::new(
+        &connection,
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        "org.freedesktop.Notifications",
+    ) {
+        Ok(proxy) => proxy,
+        Err(_) => return false,
+    };
+
+    proxy.get_property::<bool>("Inhibited").unwrap_or(false)
+}
+
+pub fn is_do_not_disturb() -> bool {
+    gnome_do_not_disturb() || kde_do_not_disturb()
+}
+
+fn login_property<T>(path: &str, interface: &str, property: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+    T::Error: Into<zbus::Error>,
+{
+    let connection = Connection::system().ok()?;
+    let proxy = Proxy::new(&connection, "org.freedesktop.login1", path, interface).ok()?;
+    proxy.get_property::<T>(property).ok()
+}
+
+// logind knows if the graphical session of the user is locked, or idle (as reported by the desktop, which works the
+// same on Wayland and X11), notifications would expire unseen until the user is back
+pub fn is_away() -> bool {
+    let display: Option<(String, OwnedObjectPath)> = login_property(
+        "/org/freedesktop/login1/user/self",
+        "org.freedesktop.login1.User",
+        "Display",
+    );
+
+    // without a graphical session, there is nobody to come back
+    let session = match display {
+        Some((id, path)) if !id.is_empty() => path,
+        _ => return false,
+    };
+
+    let locked: Option<bool> = login_property(session.as_str(), "org.freedesktop.login1.Session", "LockedHint");
+    let idle: Option<bool> = login_property(session.as_str(), "org.freedesktop.login1.Session", "IdleHint");
+
+    locked.unwrap_or(false) || idle.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hours() {
+        let night = QuietHours::parse("22:00-07:00").unwrap();
+        assert!(night.contains(23 * 60));
+        assert!(night.contains(6 * 60 + 59));
+        assert!(!night.contains(7 * 60));
+        assert!(!night.contains(12 * 60));
+
+        let lunch = QuietHours::parse("12:00 - 13:30").unwrap();
+        assert!(lunch.contains(12 * 60 + 45));
+        assert!(!lunch.contains(13 * 60 + 30));
+
+        for invalid in ["", "22:00", "24:00-07:00", "22:60-07:00", "ten-eleven"] {
+            assert!(QuietHours::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
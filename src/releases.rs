@@ -0,0 +1,88 @@
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+
+pub const DEFAULT_EOL_WARNING_DAYS: i64 = 30;
+
+const EOL_URL: &str = "https://docs.fedoraproject.org/en-US/releases/eol/";
+const UPGRADE_URL: &str = "https://docs.fedoraproject.org/en-US/quick-docs/upgrading-fedora-offline/";
+
+// releases as returned by bodhi, only the fields that are needed here
+#[derive(Debug, Deserialize)]
+pub struct ReleaseInfo {
+    pub name: String,
+    pub long_name: String,
+    pub version: String,
+    pub id_prefix: String,
+    pub state: String,
+    #[serde(default)]
+    pub eol: Option<String>,
+}
+
+impl ReleaseInfo {
+    fn version(&self) -> Option<u32> {
+        self.version.parse().ok()
+    }
+
+    pub fn days_left(&self) -> Option<i64> {
+        let eol = NaiveDate::parse_from_str(self.eol.as_deref()?, "%Y-%m-%d").ok()?;
+        Some((eol - Utc::now().date_naive()).num_days())
+    }
+}
+
+#[derive(Debug)]
+pub struct Notice {
+    // identifies the event, so notices are only sent once
+    pub key: String,
+    pub summary: String,
+    pub url: &'static str,
+    pub urgent: bool,
+}
+
+// returns notices about the end of life of the running release, and about newer stable releases
+pub fn notices(releases: &[ReleaseInfo], current: &str, warning_days: i64) -> Vec<Notice> {
+    let current = match releases.iter().find(|release| release.name == current) {
+        Some(current) => current,
+        None => return Vec::new(),
+    };
+
+    let mut notices = Vec::new();
+
+    if let (Some(days), Some(eol)) = (current.days_left(), &current.eol) {
+        if days <= warning_days {
+            let summary = if days < 0 {
+                format!("{} reached its end of life on {}", &current.long_name, eol)
+            } else {
+                format!(
+                    "{} reaches its end of life on {} (in {} days)",
+                    &current.long_name, eol, days
+                )
+            };
+
+            notices.push(Notice {
+                key: format!("eol:{}", eol),
+                summary,
+                url: EOL_URL,
+                urgent: days < 0,
+            });
+        }
+    }
+
+    // releases with the same prefix are of the same kind (i.e. not container or flatpak releases)
+    let version = current.version();
+    let newest = releases
+        .iter()
+        .filter(|release| release.id_prefix == current.id_prefix && release.state == "current")
+        .filter(|release| version.is_some() && release.version() > version)
+        .max_by_key(|release| release.version());
+
+    if let Some(newest) = newest {
+        notices.push(Notice {
+            key: format!("release:{}", &newest.name),
+            summary: format!("{} is available", &newest.long_name),
+            url: UPGRADE_URL,
+            urgent: false,
+        });
+    }
+
+    notices
+}
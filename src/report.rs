@@ -0,0 +1,1014 @@
+use std::collections::{HashMap, HashSet};
+
+use bodhi::data::{BodhiDate, TestGatingStatus, Update, UpdateRequest, UpdateSeverity, UpdateStatus, UpdateType};
+use chrono::{NaiveDateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument, warn};
+
+use crate::cache::{boot_id, Cache, CachedEvaluation, InstallRecord};
+use crate::dnf::testing_repo;
+use crate::nvr::{install_state, InstallState, NEVR};
+
+pub const UPDATE_TYPES: &[&str] = &["bugfix", "enhancement", "newpackage", "security", "unspecified"];
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Unspecified,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Severity {
+    pub fn parse(severity: &str) -> Result<Self, String> {
+        match severity {
+            "unspecified" => Ok(Severity::Unspecified),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "urgent" => Ok(Severity::Urgent),
+            _ => Err(format!("Unknown update severity: {}", severity)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Unspecified => "unspecified",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Urgent => "urgent",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortOrder {
+    // updates that are most in need of feedback first
+    Priority,
+    // updates that have been in testing the longest first
+    Age,
+    // updates that miss the most karma for the time until they are pushed to stable anyway first
+    NeedsKarma,
+}
+
+impl SortOrder {
+    pub fn parse(order: &str) -> Result<Self, String> {
+        match order {
+            "priority" => Ok(SortOrder::Priority),
+            "age" => Ok(SortOrder::Age),
+            "needs-karma" => Ok(SortOrder::NeedsKarma),
+            _ => Err(format!("Unknown sort order: {}", order)),
+        }
+    }
+}
+
+// reasons for showing updates again after commenting on them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Renotify {
+    // the comment was only text, without karma
+    NoKarma,
+    // builds were added or replaced after the comment
+    NewBuilds,
+}
+
+// parses a comma-separated list of reasons, i.e. "no-karma,new-builds"
+pub fn parse_renotify(reasons: &str) -> Result<Vec<Renotify>, String> {
+    reasons
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| match value {
+            "no-karma" => Ok(Renotify::NoKarma),
+            "new-builds" => Ok(Renotify::NewBuilds),
+            _ => Err(format!("Unknown reason for showing updates again: {}", value)),
+        })
+        .collect()
+}
+
+// parses a comma-separated list of update types, i.e. "security,bugfix"
+pub fn parse_types(types: &str) -> Result<Vec<String>, String> {
+    types
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            if UPDATE_TYPES.contains(&value) {
+                Ok(value.to_owned())
+            } else {
+                Err(format!("Unknown update type: {}", value))
+            }
+        })
+        .collect()
+}
+
+// problem with an update of the current user that needs to be acted on
+#[derive(Debug)]
+pub struct Alert {
+    // identifies the event, so alerts are only sent once
+    pub key: String,
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub struct Evaluation<'a> {
+    pub update: &'a Update,
+    pub builds: Vec<(NEVR, InstallState)>,
+    pub install: Option<InstallRecord>,
+}
+
+impl<'a> Evaluation<'a> {
+    pub fn new(update: &'a Update, packages: &[NEVR]) -> Result<Self, String> {
+        let mut builds: Vec<(NEVR, InstallState)> = Vec::new();
+
+        for build in &update.builds {
+            let nevr = match NEVR::from_build(build) {
+                Ok(nevr) => nevr,
+                Err(error) => {
+                    warn!(alias = %update.alias, "Skipping build: {}", error);
+                    continue;
+                },
+            };
+            let state = install_state(packages, &nevr);
+            builds.push((nevr, state));
+        }
+
+        Ok(Evaluation {
+            update,
+            builds,
+            install: None,
+        })
+    }
+
+    fn from_cache(update: &'a Update, states: &[InstallState]) -> Result<Self, String> {
+        let mut builds: Vec<(NEVR, InstallState)> = Vec::new();
+
+        for (build, state) in update.builds.iter().zip(states) {
+            builds.push((NEVR::from_build(build)?, *state));
+        }
+
+        Ok(Evaluation {
+            update,
+            builds,
+            install: None,
+        })
+    }
+
+    // at least one build of the update (or a newer one) is installed
+    pub fn is_installed(&self) -> bool {
+        self.builds.iter().any(|(_, state)| state.is_installed())
+    }
+
+    // all builds of the update are installed in exactly the version from the update
+    fn is_fully_installed(&self) -> bool {
+        !self.builds.is_empty() && self.builds.iter().all(|(_, state)| *state == InstallState::Installed)
+    }
+
+    // builds of the update that are installed in exactly this version
+    fn installed_builds(&self) -> String {
+        let mut nvrs: Vec<String> = self
+            .builds
+            .iter()
+            .filter(|(_, state)| *state == InstallState::Installed)
+            .map(|(nevr, _)| format!("{}-{}-{}", &nevr.n, &nevr.v, &nevr.r))
+            .collect();
+        nvrs.sort_unstable();
+        nvrs.join(" ")
+    }
+
+    // only builds that were used as they are for the whole soak period were actually tested; install records are
+    // started over when the installed builds change, so they always belong to the builds that are installed now
+    pub fn is_tested(&self) -> bool {
+        self.is_fully_installed() && self.install.is_some()
+    }
+
+    // at least one package of the update is installed, but only in an older version
+    pub fn is_older(&self) -> bool {
+        self.builds.iter().any(|(_, state)| *state == InstallState::Older)
+    }
+
+    pub fn karma(&self) -> i32 {
+        self.update.karma.unwrap_or(0)
+    }
+
+    pub fn stable_karma(&self) -> Option<i32> {
+        self.update.stable_karma
+    }
+
+    pub fn has_stable_karma(&self) -> bool {
+        self.stable_karma()
+            .map(|stable| self.karma() >= stable)
+            .unwrap_or(false)
+    }
+
+    // negative karma, failing gating tests, and requests to unpush updates
+    pub fn alerts(&self) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for comment in self
+            .update
+            .comments
+            .iter()
+            .flatten()
+            .filter(|comment| comment.karma < 0)
+        {
+            alerts.push(Alert {
+                key: format!("karma:{}:{}", &comment.user.name, &comment.timestamp),
+                text: format!("{:+} karma from {}", comment.karma, &comment.user.name),
+            });
+        }
+
+        // gating tests run again for new builds
+        if self.is_failing_gating() {
+            alerts.push(Alert {
+                key: format!("gating:{}", build_list(self.update)),
+                text: String::from("gating tests failed"),
+            });
+        }
+
+        if matches!(
+            self.update.request,
+            Some(UpdateRequest::Unpush) | Some(UpdateRequest::Revoke)
+        ) {
+            let modified = self.update.date_modified.as_ref().map(|date| date.to_string());
+            alerts.push(Alert {
+                key: format!("unpush:{}", modified.unwrap_or_default()),
+                text: String::from("unpush requested"),
+            });
+        }
+
+        alerts
+    }
+
+    pub fn is_requested_stable(&self) -> bool {
+        matches!(self.update.request, Some(UpdateRequest::Stable))
+    }
+
+    pub fn gating_status(&self) -> Option<&'static str> {
+        #[allow(unreachable_patterns)]
+        let status = match self.update.test_gating_status.as_ref()? {
+            TestGatingStatus::Waiting => "waiting",
+            TestGatingStatus::Ignored => "ignored",
+            TestGatingStatus::Queued => "queued",
+            TestGatingStatus::Running => "running",
+            TestGatingStatus::Passed => "passed",
+            TestGatingStatus::Failed => "failed",
+            TestGatingStatus::GreenwaveFailed => "greenwave failed",
+            _ => "unknown",
+        };
+
+        Some(status)
+    }
+
+    pub fn is_failing_gating(&self) -> bool {
+        matches!(
+            self.update.test_gating_status,
+            Some(TestGatingStatus::Failed) | Some(TestGatingStatus::GreenwaveFailed)
+        )
+    }
+
+    pub fn days_in_testing(&self) -> Option<i64> {
+        days_since(self.update.date_testing.as_ref()?)
+    }
+
+    // days until the update is pushed to stable based on time in testing, if enabled
+    pub fn days_to_autopush(&self) -> Option<i64> {
+        if !self.update.autotime {
+            return None;
+        }
+
+        let stable_days = i64::from(self.update.stable_days?);
+        Some((stable_days - self.days_in_testing()?).max(0))
+    }
+
+    // karma that is still missing until the stable karma threshold is reached
+    pub fn karma_deficit(&self) -> i32 {
+        self.stable_karma()
+            .map(|stable| (stable - self.karma()).max(0))
+            .unwrap_or(0)
+    }
+
+    // missing karma relative to the days until the update is pushed to stable without it
+    fn karma_urgency(&self) -> i64 {
+        let deficit = i64::from(self.karma_deficit());
+        match self.days_to_autopush() {
+            Some(days) => deficit * 100 / (days + 1),
+            None => deficit,
+        }
+    }
+
+    // updates that will be pushed to stable soon without enough karma come first
+    fn priority(&self) -> (bool, i64, i32) {
+        (
+            self.has_stable_karma(),
+            self.days_to_autopush().unwrap_or(i64::MAX),
+            self.karma(),
+        )
+    }
+
+    pub fn update_type(&self) -> &'static str {
+        #[allow(unreachable_patterns)]
+        match self.update.update_type {
+            UpdateType::BugFix => "bugfix",
+            UpdateType::Enhancement => "enhancement",
+            UpdateType::NewPackage => "newpackage",
+            UpdateType::Security => "security",
+            _ => "unspecified",
+        }
+    }
+
+    pub fn status(&self) -> &'static str {
+        #[allow(unreachable_patterns)]
+        match self.update.status {
+            UpdateStatus::Pending => "pending",
+            UpdateStatus::Testing => "testing",
+            UpdateStatus::Stable => "stable",
+            UpdateStatus::Unpushed => "unpushed",
+            UpdateStatus::Obsolete => "obsolete",
+            _ => "unknown",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        #[allow(unreachable_patterns)]
+        match self.update.severity {
+            UpdateSeverity::Low => Severity::Low,
+            UpdateSeverity::Medium => Severity::Medium,
+            UpdateSeverity::High => Severity::High,
+            UpdateSeverity::Urgent => Severity::Urgent,
+            _ => Severity::Unspecified,
+        }
+    }
+
+    pub fn is_critpath(&self) -> bool {
+        self.update.critpath
+    }
+
+    pub fn fixes_any(&self, bugs: &HashSet<u32>) -> bool {
+        self.update.bugs.iter().any(|bug| bugs.contains(&bug.bug_id))
+    }
+
+    // installed builds need to be used for some time before feedback is meaningful
+    pub fn is_soaked(&self, soak: &Soak) -> bool {
+        match &self.install {
+            Some(install) => install.days() >= i64::from(soak.days) && install.boots >= soak.boots,
+            None => true,
+        }
+    }
+
+    // installed builds of the update actually came from updates-testing, and weren't built locally or from copr
+    pub fn is_from_testing(&self, repos: &HashMap<String, HashSet<String>>) -> bool {
+        let mut known = self
+            .builds
+            .iter()
+            .filter(|(_, state)| *state == InstallState::Installed)
+            .filter_map(|(nevr, _)| repos.get(&nevr.n))
+            .peekable();
+
+        // if only newer builds are installed, or nothing is known about where they came from, they weren't tested
+        known.peek().is_some()
+            && known.all(|repos| {
+                repos
+                    .iter()
+                    .any(|repo| repo.starts_with(testing_repo(&self.update.alias)))
+            })
+    }
+
+    // builds of excluded or version-locked packages will never be upgraded to anyway
+    pub fn drop_locked(&mut self, locked: &HashSet<String>) {
+        self.builds.retain(|(nevr, _)| !locked.contains(&nevr.n));
+    }
+
+    pub fn is_interesting(&self, interests: &[String]) -> bool {
+        self.builds
+            .iter()
+            .any(|(nevr, _)| interests.iter().any(|interest| interest == &nevr.n))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Soak {
+    // minimum number of days since installation before asking for feedback
+    pub days: u32,
+    // minimum number of boots since installation before asking for feedback
+    pub boots: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct Filters {
+    // skip updates which already have enough karma, or which are already requested for stable
+    pub skip_stable: bool,
+    // skip updates with failing gating tests, they might never be pushed to stable as-is
+    pub skip_failing: bool,
+    // only show updates for packages on the critical path
+    pub critpath_only: bool,
+    // only show updates of these types (all types if empty)
+    pub types: Vec<String>,
+    // only show updates with at least this severity
+    pub min_severity: Option<Severity>,
+    // hide updates which have been in testing for less than this many days
+    pub min_age: Option<i64>,
+    // hide updates which have been in testing for more than this many days
+    pub max_age: Option<i64>,
+    // show updates again after commenting on them in these cases
+    pub renotify: Vec<Renotify>,
+    // only show updates that don't have enough karma to be pushed to stable yet
+    pub needs_karma: bool,
+    // skip updates submitted by these users, i.e. bots or other accounts of the current user
+    pub excluded_authors: Vec<String>,
+    // skip updates for packages that the current user(s) maintain
+    pub maintained: Vec<String>,
+}
+
+impl Filters {
+    // returns why an update is filtered out, if it is
+    fn rejects(&self, evaluation: &Evaluation) -> Option<&'static str> {
+        if self.skip_stable && (evaluation.has_stable_karma() || evaluation.is_requested_stable()) {
+            return Some("already has stable karma or is requested for stable");
+        }
+
+        if self.skip_failing && evaluation.is_failing_gating() {
+            return Some("gating tests are failing");
+        }
+
+        if self.needs_karma && evaluation.karma_deficit() == 0 {
+            return Some("doesn't need more karma");
+        }
+
+        if self.excluded_authors.contains(&evaluation.update.user.name) {
+            return Some("submitted by an excluded author");
+        }
+
+        if evaluation
+            .builds
+            .iter()
+            .any(|(nevr, _)| self.maintained.contains(&nevr.n))
+        {
+            return Some("you are a maintainer of the package");
+        }
+
+        if self.critpath_only && !evaluation.is_critpath() {
+            return Some("not on the critical path");
+        }
+
+        if !self.types.is_empty() && !self.types.iter().any(|t| t == evaluation.update_type()) {
+            return Some("update type is filtered out");
+        }
+
+        if let Some(min_severity) = self.min_severity {
+            if evaluation.severity() < min_severity {
+                return Some("severity is too low");
+            }
+        }
+
+        if let Some(days) = evaluation.days_in_testing() {
+            if self.min_age.map(|min_age| days < min_age).unwrap_or(false) {
+                return Some("not in testing for long enough");
+            }
+            if self.max_age.map(|max_age| days > max_age).unwrap_or(false) {
+                return Some("in testing for too long");
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report<'a> {
+    // updates with installed builds, which are ready for feedback
+    pub installed: Vec<Evaluation<'a>>,
+    // updates for interesting packages, which are not installed yet
+    pub pending: Vec<Evaluation<'a>>,
+}
+
+impl<'a> Report<'a> {
+    pub fn bug_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .installed
+            .iter()
+            .chain(self.pending.iter())
+            .flat_map(|evaluation| evaluation.update.bugs.iter().map(|bug| bug.bug_id))
+            .collect();
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: Fn(&Evaluation) -> bool,
+    {
+        self.installed.retain(|evaluation| f(evaluation));
+        self.pending.retain(|evaluation| f(evaluation));
+    }
+
+    // records when installed updates were first seen, and hides them until they have been used long enough
+    pub fn track_installs(&mut self, cache: &Cache, soak: &Soak) {
+        let boot_id = boot_id();
+
+        for evaluation in &mut self.installed {
+            let builds = evaluation.installed_builds();
+            match cache.record_install(&evaluation.update.alias, &builds, boot_id.as_deref()) {
+                Ok(install) => evaluation.install = Some(install),
+                Err(error) => warn!(
+                    "Failed to record installation of {}: {}",
+                    &evaluation.update.alias, error
+                ),
+            }
+        }
+
+        self.installed.retain(|evaluation| {
+            let soaked = evaluation.is_soaked(soak);
+            if !soaked {
+                debug!(alias = %evaluation.update.alias, "Skipped: not installed for long enough");
+            }
+            soaked
+        });
+    }
+
+    pub fn sort(&mut self, order: SortOrder) {
+        match order {
+            SortOrder::Priority => {
+                self.installed.sort_by_key(Evaluation::priority);
+                self.pending.sort_by_key(Evaluation::priority);
+            },
+            SortOrder::Age => {
+                let age = |evaluation: &Evaluation| std::cmp::Reverse(evaluation.days_in_testing());
+                self.installed.sort_by_key(age);
+                self.pending.sort_by_key(age);
+            },
+            SortOrder::NeedsKarma => {
+                let urgency = |evaluation: &Evaluation| std::cmp::Reverse(evaluation.karma_urgency());
+                self.installed.sort_by_key(urgency);
+                self.pending.sort_by_key(urgency);
+            },
+        }
+    }
+
+    pub fn installed_packages(&self) -> Vec<&str> {
+        let mut packages: Vec<&str> = self
+            .installed
+            .iter()
+            .flat_map(|evaluation| evaluation.builds.iter().map(|(nevr, _)| nevr.n.as_str()))
+            .collect();
+
+        // sort and remove duplicates
+        packages.sort_unstable();
+        packages.dedup();
+        packages
+    }
+
+    pub fn pending_packages(&self, interests: &[String]) -> Vec<&str> {
+        let mut packages: Vec<&str> = self
+            .pending
+            .iter()
+            .flat_map(|evaluation| evaluation.builds.iter().map(|(nevr, _)| nevr.n.as_str()))
+            .filter(|package| interests.iter().any(|interest| interest == package))
+            .collect();
+
+        // sort and remove duplicates
+        packages.sort_unstable();
+        packages.dedup();
+        packages
+    }
+}
+
+pub fn days_since(date: &BodhiDate) -> Option<i64> {
+    let date = NaiveDateTime::parse_from_str(&date.to_string(), "%Y-%m-%d %H:%M:%S").ok()?;
+    Some((Utc::now().naive_utc() - date).num_days())
+}
+
+fn is_commented(update: &Update, usernames: &[String]) -> bool {
+    match &update.comments {
+        Some(comments) => comments.iter().any(|comment| usernames.contains(&comment.user.name)),
+        None => false,
+    }
+}
+
+// bodhi comments on updates when their builds are changed, and resets karma
+fn is_build_change(text: &str) -> bool {
+    text.contains("New build(s):") || text.contains("Removed build(s):") || text.contains("Karma has been reset")
+}
+
+fn build_list(update: &Update) -> String {
+    let mut nvrs: Vec<&str> = update.builds.iter().map(|build| build.nvr.as_str()).collect();
+    nvrs.sort_unstable();
+    nvrs.join(" ")
+}
+
+// checks whether builds were changed after the last comment of the current user(s); bodhi doesn't record when builds
+// were added, so this is based on when the update was modified, and when bodhi commented on changes to its builds
+fn builds_changed(update: &Update, last_comment: &str) -> bool {
+    // timestamps are formatted as "%Y-%m-%d %H:%M:%S", so they can be compared as strings
+    let modified = update.date_modified.as_ref().map(|date| date.to_string());
+    if modified
+        .map(|modified| modified.as_str() <= last_comment)
+        .unwrap_or(true)
+    {
+        return false;
+    }
+
+    update
+        .comments
+        .iter()
+        .flatten()
+        .filter(|comment| comment.timestamp.to_string().as_str() > last_comment)
+        .any(|comment| is_build_change(&comment.text))
+}
+
+// returns why an update that was already commented on needs feedback again, if it does
+fn renotify_reason(update: &Update, usernames: &[String], renotify: &[Renotify]) -> Option<&'static str> {
+    let comments = update.comments.as_ref()?;
+    let mine: Vec<_> = comments
+        .iter()
+        .filter(|comment| usernames.contains(&comment.user.name))
+        .collect();
+    let last_comment = mine.iter().map(|comment| comment.timestamp.to_string()).max()?;
+
+    if renotify.contains(&Renotify::NewBuilds) && builds_changed(update, &last_comment) {
+        return Some("builds were changed after your last comment");
+    }
+
+    if renotify.contains(&Renotify::NoKarma) && mine.iter().all(|comment| comment.karma == 0) {
+        return Some("your comments didn't have any karma");
+    }
+
+    None
+}
+
+// the same users and set of installed packages always result in the same fingerprint, it is stored in the cache, so it
+// needs to be stable across builds and toolchains
+fn fingerprint(packages: &[NEVR], usernames: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for username in usernames {
+        hasher.update(format!("{}\n", username));
+    }
+    hasher.update("\n");
+    for package in packages {
+        hasher.update(format!("{} {} {} {}\n", &package.n, package.e, &package.v, &package.r));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// comments don't change the modification date of updates, so they are part of the cache key
+fn modified(update: &Update) -> String {
+    let date = match &update.date_modified {
+        Some(date) => date.to_string(),
+        None => String::new(),
+    };
+    let comments = update.comments.as_ref().map(|c| c.len()).unwrap_or(0);
+    format!("{}+{}", date, comments)
+}
+
+// matches an update against installed packages, reusing cached results if nothing changed, and collecting new results
+// so they can be stored at once
+fn evaluate_cached<'a>(
+    update: &'a Update,
+    packages: &[NEVR],
+    usernames: &[String],
+    cache: Option<&Cache>,
+    fingerprint: &str,
+    evaluated: &mut Vec<(String, String, CachedEvaluation)>,
+) -> Result<(bool, Evaluation<'a>), String> {
+    let modified = modified(update);
+
+    if let Some(cached) = cache.and_then(|cache| cache.evaluation(&update.alias, &modified, fingerprint)) {
+        if cached.states.len() == update.builds.len() {
+            return Ok((cached.commented, Evaluation::from_cache(update, &cached.states)?));
+        }
+    }
+
+    let commented = is_commented(update, usernames);
+    let evaluation = Evaluation::new(update, packages)?;
+
+    if cache.is_some() {
+        let cached = CachedEvaluation {
+            commented,
+            states: evaluation.builds.iter().map(|(_, state)| *state).collect(),
+        };
+        evaluated.push((update.alias.clone(), modified, cached));
+    }
+
+    Ok((commented, evaluation))
+}
+
+#[instrument(skip_all)]
+pub fn evaluate<'a>(
+    packages: &[NEVR],
+    updates: &'a [Update],
+    usernames: &[String],
+    interests: &[String],
+    filters: &Filters,
+    cache: Option<&Cache>,
+) -> Result<Report<'a>, String> {
+    let mut report = Report::default();
+    let fingerprint = fingerprint(packages, usernames);
+    let mut evaluated = Vec::new();
+
+    for update in updates {
+        // filter out updates created by the current user(s), and updates that any of them commented on
+        if usernames.contains(&update.user.name) {
+            debug!(alias = %update.alias, "Skipped: submitted by you");
+            continue;
+        }
+
+        let evaluation = evaluate_cached(update, packages, usernames, cache, &fingerprint, &mut evaluated);
+        let (commented, evaluation) = match evaluation {
+            Ok(result) => result,
+            Err(error) => {
+                warn!(alias = %update.alias, "Skipping update: {}", error);
+                continue;
+            },
+        };
+
+        // filter out updates that were already commented on, unless they need feedback again
+        if commented {
+            match renotify_reason(update, usernames, &filters.renotify) {
+                Some(reason) => debug!(alias = %update.alias, "Shown again: {}", reason),
+                None => {
+                    debug!(alias = %update.alias, "Skipped: already commented on");
+                    continue;
+                },
+            }
+        }
+
+        if let Some(reason) = filters.rejects(&evaluation) {
+            debug!(alias = %update.alias, "Skipped: {}", reason);
+            continue;
+        }
+
+        if evaluation.is_installed() {
+            debug!(alias = %update.alias, "Installed, ready for feedback");
+            report.installed.push(evaluation);
+        } else if evaluation.is_older() && evaluation.is_interesting(interests) {
+            debug!(alias = %update.alias, "Available for interesting packages");
+            report.pending.push(evaluation);
+        }
+    }
+
+    if let Some(cache) = cache {
+        if let Err(error) = cache.store_evaluations(&fingerprint, &evaluated) {
+            warn!("Failed to cache evaluations: {}", error);
+        }
+    }
+
+    // deduplicate pending updates
+    report.pending.sort_by(|a, b| a.update.alias.cmp(&b.update.alias));
+    report.pending.dedup_by(|a, b| a.update.alias == b.update.alias);
+
+    // sort updates that are most in need of feedback first
+    report.sort(SortOrder::Priority);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{json, Value};
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/update.json");
+
+    fn nevr(n: &str, e: u32, v: &str, r: &str) -> NEVR {
+        NEVR {
+            n: n.to_owned(),
+            e,
+            v: v.to_owned(),
+            r: r.to_owned(),
+        }
+    }
+
+    fn update(alias: &str, builds: &[&str]) -> Value {
+        let mut update: Value = serde_json::from_str(FIXTURE).unwrap();
+        update["alias"] = json!(alias);
+        update["builds"] = builds
+            .iter()
+            .map(|nvr| json!({"epoch": 0, "nvr": nvr, "release_id": 35, "signed": true, "type": "rpm"}))
+            .collect();
+        update
+    }
+
+    fn comment(update: &mut Value, user: &str, karma: i32) {
+        let mut comment = update["comments"][0].clone();
+        comment["user"]["name"] = json!(user);
+        comment["karma"] = json!(karma);
+        comment["timestamp"] = json!("2023-12-03 18:30:00");
+        update["comments"].as_array_mut().unwrap().push(comment);
+    }
+
+    fn parse(updates: Vec<Value>) -> Vec<Update> {
+        updates
+            .into_iter()
+            .map(|update| serde_json::from_value(update).unwrap())
+            .collect()
+    }
+
+    fn aliases<'a>(evaluations: &'a [Evaluation]) -> Vec<&'a str> {
+        evaluations
+            .iter()
+            .map(|evaluation| evaluation.update.alias.as_str())
+            .collect()
+    }
+
+    fn installed() -> Vec<NEVR> {
+        vec![
+            nevr("kernel", 0, "6.6.4", "200.fc39"),
+            nevr("mesa", 0, "23.3.0", "1.fc39"),
+            nevr("systemd", 0, "254.5", "2.fc39"),
+            nevr("vim", 0, "9.0.2120", "1.fc39"),
+        ]
+    }
+
+    #[test]
+    fn build_states() {
+        let updates = parse(vec![update(
+            "FEDORA-2023-0000000001",
+            &[
+                "mesa-23.3.0-1.fc39",
+                "kernel-6.6.3-200.fc39",
+                "systemd-254.7-1.fc39",
+                "fish-3.6.4-1.fc39",
+            ],
+        )]);
+
+        let evaluation = Evaluation::new(&updates[0], &installed()).unwrap();
+        let states: Vec<InstallState> = evaluation.builds.iter().map(|(_, state)| *state).collect();
+        assert_eq!(
+            states,
+            vec![
+                InstallState::Installed,
+                InstallState::Newer,
+                InstallState::Older,
+                InstallState::NotInstalled,
+            ]
+        );
+
+        assert!(evaluation.is_installed());
+        assert!(evaluation.is_older());
+        assert!(!evaluation.is_fully_installed());
+        assert_eq!(evaluation.installed_builds(), "mesa-23.3.0-1.fc39");
+    }
+
+    #[test]
+    fn only_newer_builds() {
+        let updates = parse(vec![update("FEDORA-2023-0000000001", &["kernel-6.6.3-200.fc39"])]);
+
+        let evaluation = Evaluation::new(&updates[0], &installed()).unwrap();
+        assert!(evaluation.is_installed());
+        assert!(!evaluation.is_fully_installed());
+        assert_eq!(evaluation.installed_builds(), "");
+
+        // newer builds weren't installed from this update
+        let mut repos = HashMap::new();
+        repos.insert(
+            String::from("kernel"),
+            std::iter::once(String::from("updates-testing")).collect(),
+        );
+        assert!(!evaluation.is_from_testing(&repos));
+    }
+
+    #[test]
+    fn installed_and_pending() {
+        let updates = parse(vec![
+            // installed
+            update("FEDORA-2023-0000000001", &["mesa-23.3.0-1.fc39"]),
+            // only a newer build is installed, which still counts as installed
+            update("FEDORA-2023-0000000002", &["kernel-6.6.3-200.fc39"]),
+            // older version of an interesting package is installed
+            update("FEDORA-2023-0000000003", &["systemd-254.7-1.fc39"]),
+            // older version of a package that is not interesting is installed
+            update("FEDORA-2023-0000000004", &["vim-9.0.2136-1.fc39"]),
+            // not installed at all
+            update("FEDORA-2023-0000000005", &["fish-3.6.4-1.fc39"]),
+        ]);
+
+        let interests = vec![String::from("systemd"), String::from("fish")];
+        let report = evaluate(&installed(), &updates, &[], &interests, &Filters::default(), None).unwrap();
+
+        let mut installed = aliases(&report.installed);
+        installed.sort_unstable();
+        assert_eq!(installed, vec!["FEDORA-2023-0000000001", "FEDORA-2023-0000000002"]);
+        assert_eq!(aliases(&report.pending), vec!["FEDORA-2023-0000000003"]);
+        assert_eq!(report.pending_packages(&interests), vec!["systemd"]);
+    }
+
+    #[test]
+    fn own_updates_and_comments() {
+        let mut submitted = update("FEDORA-2023-0000000001", &["mesa-23.3.0-1.fc39"]);
+        submitted["user"]["name"] = json!("tester");
+
+        let mut commented = update("FEDORA-2023-0000000002", &["systemd-254.5-2.fc39"]);
+        comment(&mut commented, "tester", 1);
+
+        let mut other = update("FEDORA-2023-0000000003", &["kernel-6.6.4-200.fc39"]);
+        comment(&mut other, "someone", 1);
+
+        let updates = parse(vec![submitted, commented, other]);
+        let usernames = vec![String::from("tester")];
+
+        let report = evaluate(&installed(), &updates, &usernames, &[], &Filters::default(), None).unwrap();
+        assert_eq!(aliases(&report.installed), vec!["FEDORA-2023-0000000003"]);
+
+        // comments without karma can be shown again
+        let mut neutral = update("FEDORA-2023-0000000004", &["mesa-23.3.0-1.fc39"]);
+        comment(&mut neutral, "tester", 0);
+        let updates = parse(vec![neutral]);
+
+        let filters = Filters {
+            renotify: vec![Renotify::NoKarma],
+            ..Filters::default()
+        };
+        let report = evaluate(&installed(), &updates, &usernames, &[], &filters, None).unwrap();
+        assert_eq!(aliases(&report.installed), vec!["FEDORA-2023-0000000004"]);
+    }
+
+    #[test]
+    fn new_builds() {
+        let mut edited = update("FEDORA-2023-0000000001", &["mesa-23.3.0-1.fc39"]);
+        comment(&mut edited, "tester", 1);
+        edited["date_modified"] = json!("2023-12-04 09:00:00");
+
+        // the notes were edited after the comment
+        let updates = parse(vec![edited.clone()]);
+        let usernames = vec![String::from("tester")];
+        let filters = Filters {
+            renotify: vec![Renotify::NewBuilds],
+            ..Filters::default()
+        };
+        let report = evaluate(&installed(), &updates, &usernames, &[], &filters, None).unwrap();
+        assert!(report.installed.is_empty());
+
+        // the builds were changed after the comment
+        let mut change = edited["comments"][0].clone();
+        change["text"] = json!("This update has been submitted for testing by packager. Karma has been reset.");
+        change["timestamp"] = json!("2023-12-04 09:00:00");
+        edited["comments"].as_array_mut().unwrap().push(change);
+
+        let updates = parse(vec![edited]);
+        let report = evaluate(&installed(), &updates, &usernames, &[], &filters, None).unwrap();
+        assert_eq!(aliases(&report.installed), vec!["FEDORA-2023-0000000001"]);
+    }
+
+    #[test]
+    fn filters() {
+        let mut stable = update("FEDORA-2023-0000000001", &["mesa-23.3.0-1.fc39"]);
+        stable["karma"] = json!(3);
+
+        let mut bot = update("FEDORA-2023-0000000002", &["systemd-254.5-2.fc39"]);
+        bot["user"]["name"] = json!("packit");
+
+        let mut security = update("FEDORA-2023-0000000003", &["kernel-6.6.4-200.fc39"]);
+        security["type"] = json!("security");
+        security["severity"] = json!("high");
+
+        let maintained = update("FEDORA-2023-0000000004", &["vim-9.0.2120-1.fc39"]);
+
+        let updates = parse(vec![stable, bot, security, maintained]);
+
+        let all = evaluate(&installed(), &updates, &[], &[], &Filters::default(), None).unwrap();
+        assert_eq!(all.installed.len(), 4);
+
+        let filters = Filters {
+            skip_stable: true,
+            excluded_authors: vec![String::from("packit")],
+            maintained: vec![String::from("vim")],
+            ..Filters::default()
+        };
+        let report = evaluate(&installed(), &updates, &[], &[], &filters, None).unwrap();
+        assert_eq!(aliases(&report.installed), vec!["FEDORA-2023-0000000003"]);
+
+        let filters = Filters {
+            types: vec![String::from("enhancement")],
+            ..Filters::default()
+        };
+        let report = evaluate(&installed(), &updates, &[], &[], &filters, None).unwrap();
+        assert!(!aliases(&report.installed).contains(&"FEDORA-2023-0000000003"));
+
+        let filters = Filters {
+            min_severity: Some(Severity::Medium),
+            ..Filters::default()
+        };
+        let report = evaluate(&installed(), &updates, &[], &[], &filters, None).unwrap();
+        assert_eq!(aliases(&report.installed), vec!["FEDORA-2023-0000000003"]);
+    }
+
+    #[test]
+    fn locked_builds() {
+        let updates = parse(vec![update(
+            "FEDORA-2023-0000000001",
+            &["kernel-6.6.5-200.fc39", "systemd-254.7-1.fc39"],
+        )]);
+
+        let mut evaluation = Evaluation::new(&updates[0], &installed()).unwrap();
+        evaluation.drop_locked(&std::iter::once(String::from("kernel")).collect());
+        assert_eq!(evaluation.builds.len(), 1);
+        assert!(evaluation.is_older());
+
+        evaluation.drop_locked(&std::iter::once(String::from("systemd")).collect());
+        assert!(!evaluation.is_older());
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::time::Duration;
+
+use bodhi::error::QueryError;
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use tracing::warn;
+
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_BACKOFF: u64 = 2;
+// seconds until stalled requests are aborted (and retried)
+pub const DEFAULT_TIMEOUT: u64 = 60;
+
+// upper bound for the exponential backoff between attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+// seconds to wait if the server asked to slow down without saying for how long
+const DEFAULT_RATE_LIMIT_WAIT: u64 = 30;
+// waiting longer than this is not worth it, the next run will try again
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: DEFAULT_RETRIES,
+            backoff: Duration::from_secs(DEFAULT_BACKOFF),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT),
+        }
+    }
+}
+
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+
+    // set if the server asked to slow down (HTTP 429), to the time it asked to wait for
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// the Retry-After header is either a number of seconds, or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(value.trim()).ok()?;
+            (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+        },
+    }
+}
+
+pub fn retry_after(response: &reqwest::Response) -> Duration {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+        .unwrap_or(Duration::from_secs(DEFAULT_RATE_LIMIT_WAIT))
+}
+
+// errors of single requests, HTTP 429 is kept apart so the time the server asked to wait for is honored
+#[derive(Debug)]
+pub enum RequestError {
+    Request(reqwest::Error),
+    RateLimited(Duration),
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Request(error) => write!(f, "{}", error),
+            RequestError::RateLimited(wait) => {
+                write!(f, "The server asked us to slow down (retry after {}s)", wait.as_secs())
+            },
+        }
+    }
+}
+
+impl Transient for RequestError {
+    fn is_transient(&self) -> bool {
+        match self {
+            RequestError::Request(error) => error.is_transient(),
+            RequestError::RateLimited(_) => true,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            RequestError::RateLimited(wait) => Some(*wait),
+            RequestError::Request(_) => None,
+        }
+    }
+}
+
+// sends a request, and turns error responses into errors
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, RequestError> {
+    let response = request.send().await.map_err(RequestError::Request)?;
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(RequestError::RateLimited(retry_after(&response)));
+    }
+
+    response.error_for_status().map_err(RequestError::Request)
+}
+
+impl Transient for QueryError {
+    fn is_transient(&self) -> bool {
+        match self {
+            // connection resets, timeouts, and 5xx responses from proxies
+            QueryError::RequestError { error } => {
+                error.is_connect()
+                    || error.is_timeout()
+                    || error.is_request()
+                    || error.status().map(|s| s.is_server_error()).unwrap_or(false)
+            },
+            // error pages returned by the server (malformed data won't get better by asking again)
+            QueryError::BodhiError { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+pub async fn retry<T, E, F, Fut>(policy: RetryPolicy, what: &str, f: F) -> Result<T, String>
+where
+    E: Transient + Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_errors(policy, what, f)
+        .await
+        .map_err(|error| format!("{} failed: {}", what, error))
+}
+
+// like retry, but returns the last error itself, so callers can still tell whether it was transient
+pub async fn retry_errors<T, E, F, Fut>(policy: RetryPolicy, what: &str, f: F) -> Result<T, E>
+where
+    E: Transient + Display,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let attempts = policy.attempts.max(1);
+    let mut backoff = policy.backoff;
+    let mut attempt = 1;
+
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < attempts && error.retry_after().is_some() => {
+                let wait = error.retry_after().unwrap_or(backoff);
+                if wait > MAX_RATE_LIMIT_WAIT {
+                    return Err(error);
+                }
+
+                warn!(
+                    "{} was rate-limited (attempt {} of {}), retrying in {}s",
+                    what,
+                    attempt,
+                    attempts,
+                    wait.as_secs()
+                );
+
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            },
+            Err(error) if attempt < attempts && error.is_transient() => {
+                warn!(
+                    "{} failed (attempt {} of {}), retrying in {}s: {}",
+                    what,
+                    attempt,
+                    attempts,
+                    backoff.as_secs(),
+                    error
+                );
+
+                tokio::time::sleep(backoff).await;
+                backoff = backoff.checked_mul(2).unwrap_or(MAX_BACKOFF).min(MAX_BACKOFF);
+                attempt += 1;
+            },
+            Err(error) => return Err(error),
+        }
+    }
+}
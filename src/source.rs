@@ -0,0 +1,801 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bodhi::data::{Comment, FedoraRelease, Update};
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressBar;
+use reqwest::header::{HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::cache::{Cache, CachedResponse, UpdateCache};
+use crate::http;
+use crate::nvr::parse_nvr;
+use crate::overrides::BuildrootOverride;
+use crate::progress;
+use crate::releases::ReleaseInfo;
+use crate::retry::{retry, retry_after, RequestError, RetryPolicy, Transient};
+
+const DEFAULT_BODHI_URL: &str = "https://bodhi.fedoraproject.org";
+
+static BODHI_URL: OnceLock<String> = OnceLock::new();
+
+// i.e. for staging or a local development instance, this needs to be set before anything is queried
+pub fn set_bodhi_url(url: &str) {
+    if BODHI_URL.set(url.trim_end_matches('/').to_owned()).is_err() {
+        warn!("The bodhi URL was already set");
+    }
+}
+
+pub fn bodhi_url() -> &'static str {
+    BODHI_URL.get().map(String::as_str).unwrap_or(DEFAULT_BODHI_URL)
+}
+
+const ROWS_PER_PAGE: u32 = 50;
+const CONCURRENT_PAGES: usize = 4;
+
+// bodhi splits comma-separated package lists, but very long query strings are rejected;
+// neither value is tuned by measurements, which is why package queries are only used when requested
+const PACKAGES_PER_QUERY: usize = 25;
+const CONCURRENT_QUERIES: usize = 8;
+
+// limit for requests in flight at the same time, shared by all concurrent queries
+const MAX_REQUESTS: usize = 8;
+
+// connectivity checks should not hold up the whole run
+const REACHABLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QueryStrategy {
+    // fetch all updates that are in testing
+    Full,
+    // only fetch updates for installed and interesting packages
+    Packages,
+}
+
+impl QueryStrategy {
+    pub fn parse(strategy: &str) -> Result<Self, String> {
+        match strategy {
+            "full" => Ok(QueryStrategy::Full),
+            "packages" => Ok(QueryStrategy::Packages),
+            _ => Err(format!("Unknown query strategy: {}", strategy)),
+        }
+    }
+}
+
+impl Transient for reqwest::Error {
+    fn is_transient(&self) -> bool {
+        self.is_connect()
+            || self.is_timeout()
+            || self.is_request()
+            || self.status().map(|s| s.is_server_error()).unwrap_or(false)
+    }
+}
+
+// bodhi is considered offline if requests fail, waiting is only worth it if it asked to slow down
+struct Unreachable(RequestError);
+
+impl Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Transient for Unreachable {
+    fn is_transient(&self) -> bool {
+        false
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.0.retry_after()
+    }
+}
+
+// responses are parsed separately from fetching them, so the raw response can be cached
+#[derive(Debug)]
+enum FetchError {
+    Request(reqwest::Error),
+    Parse(serde_json::Error),
+    // HTTP 429, with the time bodhi asked to wait for
+    RateLimited(Duration),
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Request(error) => write!(f, "{}", error),
+            FetchError::Parse(error) => write!(f, "Invalid response: {}", error),
+            FetchError::RateLimited(wait) => {
+                write!(f, "The server asked us to slow down (retry after {}s)", wait.as_secs())
+            },
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(error: reqwest::Error) -> Self {
+        FetchError::Request(error)
+    }
+}
+
+impl Transient for FetchError {
+    fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Request(error) => error.is_transient(),
+            // malformed data won't get better by asking again
+            FetchError::Parse(_) => false,
+            FetchError::RateLimited(_) => true,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FetchError::RateLimited(wait) => Some(*wait),
+            _ => None,
+        }
+    }
+}
+
+fn header(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+#[async_trait]
+pub trait UpdateSource: Sync {
+    // returns all updates for the given release that are currently in testing
+    async fn testing_updates(&self, release: &FedoraRelease) -> Result<Vec<Update>, String>;
+
+    // returns the updates for the given packages that are currently in testing
+    async fn package_updates(&self, release: &FedoraRelease, packages: &[String]) -> Result<Vec<Update>, String>;
+
+    // returns updates submitted by a user that are pending or in testing, or were pushed to stable since a date
+    async fn user_updates(&self, username: &str, since: &str) -> Result<Vec<Update>, String>;
+
+    // returns a single update
+    async fn update(&self, alias: &str) -> Result<Update, String>;
+}
+
+struct Incremental {
+    usernames: Vec<String>,
+    // maximum age of the cache (in seconds) before a full refresh is done
+    max_age: i64,
+}
+
+pub struct BodhiSource {
+    client: reqwest::Client,
+    url: String,
+    policy: RetryPolicy,
+    incremental: Option<Incremental>,
+    read_only: bool,
+    requests: Semaphore,
+    // set when bodhi rate-limited a request, so concurrent requests wait as well
+    slow_down: Mutex<Option<Instant>>,
+    // opened on first use and shared by all requests
+    cache: OnceLock<Option<Mutex<Cache>>>,
+    // pages that were already parsed, which don't need to be parsed again if they didn't change
+    parsed: Mutex<HashMap<String, Value>>,
+}
+
+// returns the items of a page, the number of pages, and the total number of items
+fn page_items(mut page: Value, key: &str) -> Result<(Vec<Value>, u32, Option<u64>), String> {
+    let pages = page["pages"].as_u64().unwrap_or(1) as u32;
+    let total = page["total"].as_u64();
+
+    match page.get_mut(key).map(Value::take) {
+        Some(Value::Array(items)) => Ok((items, pages, total)),
+        _ => Err(format!("Unexpected response from bodhi: missing \"{}\"", key)),
+    }
+}
+
+// updates which can't be parsed are skipped instead of failing the whole query
+fn parse_updates(values: &[Value]) -> Vec<Update> {
+    values
+        .iter()
+        .filter_map(|value| match Update::deserialize(value) {
+            Ok(update) => Some(update),
+            Err(error) => {
+                warn!(
+                    "Failed to parse update {} from bodhi: {}",
+                    alias_of(value).unwrap_or("(unknown)"),
+                    error
+                );
+                None
+            },
+        })
+        .collect()
+}
+
+// bodhi leaves out the comments of updates in some responses, or doesn't include all of them, so complete lists are
+// fetched once and cached until the update is modified; comments don't change the modification date, but new ones
+// show up in embedded lists, which are then longer than the cached one
+fn cached_comments(update: &Update, cached: Option<Vec<Comment>>) -> Option<Vec<Comment>> {
+    let mut cached = cached?;
+    let embedded = update.comments.as_deref().unwrap_or_default();
+
+    if embedded.len() > cached.len() {
+        return None;
+    }
+
+    for comment in embedded {
+        if !cached.iter().any(|other| other.id == comment.id) {
+            cached.push(comment.clone());
+        }
+    }
+
+    Some(cached)
+}
+
+fn date_modified(update: &Update) -> String {
+    update
+        .date_modified
+        .as_ref()
+        .map(|date| date.to_string())
+        .unwrap_or_default()
+}
+
+fn parse_comments(values: Vec<Value>) -> Vec<Comment> {
+    values
+        .into_iter()
+        .filter_map(|comment| Comment::deserialize(comment).ok())
+        .collect()
+}
+
+fn alias_of(update: &Value) -> Option<&str> {
+    update["alias"].as_str()
+}
+
+fn is_testing(update: &Value) -> bool {
+    update["status"].as_str() == Some("testing")
+}
+
+impl BodhiSource {
+    pub fn new(policy: RetryPolicy) -> Result<Self, String> {
+        let client = match http::client_builder().timeout(policy.timeout).build() {
+            Ok(client) => client,
+            Err(error) => {
+                return Err(format!("{}", error));
+            },
+        };
+
+        Ok(BodhiSource {
+            client,
+            url: String::from(bodhi_url()),
+            policy,
+            incremental: None,
+            read_only: false,
+            requests: Semaphore::new(MAX_REQUESTS),
+            slow_down: Mutex::new(None),
+            cache: OnceLock::new(),
+            parsed: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // use cached updates, but don't update the cache
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    // cheap request to check whether bodhi can be reached at all, only retried if bodhi asked to slow down
+    pub async fn is_reachable(&self) -> bool {
+        let request = || async move {
+            match self.client.head(&self.url).timeout(REACHABLE_TIMEOUT).send().await {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Err(Unreachable(RequestError::RateLimited(retry_after(&response))))
+                },
+                // any other response means that bodhi is reachable
+                Ok(_) => Ok(()),
+                Err(error) => Err(Unreachable(RequestError::Request(error))),
+            }
+        };
+
+        match retry(self.policy, "Checking whether bodhi is reachable", request).await {
+            Ok(()) => true,
+            Err(error) => {
+                debug!("{}", error);
+                false
+            },
+        }
+    }
+
+    // sqlite calls block, so the runtime is told to move other tasks off this thread while the cache is accessed
+    fn with_cache<T>(&self, f: impl FnOnce(&mut Cache) -> T) -> Option<T> {
+        tokio::task::block_in_place(|| {
+            let cache = self.cache.get_or_init(|| {
+                let cache = if self.read_only {
+                    Cache::open_read_only()
+                } else {
+                    Cache::open()
+                };
+
+                match cache {
+                    Ok(cache) => Some(Mutex::new(cache)),
+                    Err(error) => {
+                        warn!("Failed to open cache: {}", error);
+                        None
+                    },
+                }
+            });
+
+            let mut cache = cache.as_ref()?.lock().unwrap_or_else(PoisonError::into_inner);
+            Some(f(&mut cache))
+        })
+    }
+
+    // only fetch updates that changed since the last run, and merge them with cached data
+    pub fn incremental(mut self, usernames: &[String], max_age: i64) -> Self {
+        self.incremental = Some(Incremental {
+            usernames: usernames.to_vec(),
+            max_age,
+        });
+        self
+    }
+
+    // waits until bodhi is ready for more requests, if it asked to slow down
+    async fn pace(&self) {
+        let until = *self.slow_down.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(until) = until {
+            tokio::time::sleep_until(until).await;
+        }
+    }
+
+    fn rate_limited(&self, response: &reqwest::Response) -> FetchError {
+        let wait = retry_after(response);
+        let until = Instant::now() + wait;
+
+        let mut slow_down = self.slow_down.lock().unwrap_or_else(PoisonError::into_inner);
+        if slow_down.map(|current| current < until).unwrap_or(true) {
+            *slow_down = Some(until);
+        }
+
+        FetchError::RateLimited(wait)
+    }
+
+    // sends a conditional request if the response was cached, and only downloads pages that changed
+    async fn fetch_page(&self, path: &str, params: &[(&str, String)], page: u32) -> Result<Value, FetchError> {
+        // the semaphore is never closed
+        let _permit = self.requests.acquire().await;
+        self.pace().await;
+
+        let build = || {
+            self.client
+                .get(format!("{}/{}/", self.url, path))
+                .query(params)
+                .query(&[("page", page), ("rows_per_page", ROWS_PER_PAGE)])
+                .build()
+        };
+
+        let mut request = build()?;
+        let url = request.url().to_string();
+        debug!("Querying {}", url);
+
+        let validators = self.with_cache(|cache| cache.response_validators(&url)).flatten();
+
+        if let Some((etag, last_modified)) = &validators {
+            let headers = request.headers_mut();
+            if let Some(etag) = etag.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = last_modified.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(IF_MODIFIED_SINCE, modified);
+            }
+        }
+
+        let mut response = self.client.execute(request).await?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response));
+        }
+
+        if response.status() == StatusCode::NOT_MODIFIED && validators.is_some() {
+            if let Some(value) = self.parsed.lock().unwrap_or_else(PoisonError::into_inner).get(&url) {
+                return Ok(value.clone());
+            }
+
+            let body = self.with_cache(|cache| cache.response_body(&url)).flatten();
+            if let Some(Ok(value)) = body.as_deref().map(serde_json::from_str::<Value>) {
+                debug!("Not modified since the last run: {}", url);
+                if let Some(Err(error)) = self.with_cache(|cache| cache.touch_response(&url)) {
+                    warn!("Failed to update cached response: {}", error);
+                }
+                self.remember(&url, &value);
+                return Ok(value);
+            }
+
+            // the cached body is gone or broken, so the page is downloaded again without validators
+            warn!("Cached response for {} is unusable, downloading it again", url);
+            if let Some(Err(error)) = self.with_cache(|cache| cache.remove_response(&url)) {
+                warn!("Failed to remove cached response: {}", error);
+            }
+
+            response = self.client.execute(build()?).await?;
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                return Err(self.rate_limited(&response));
+            }
+        }
+
+        let response = response.error_for_status()?;
+        let etag = header(&response, ETAG);
+        let last_modified = header(&response, LAST_MODIFIED);
+        let body = response.text().await?;
+        let value: Value = serde_json::from_str(&body).map_err(FetchError::Parse)?;
+
+        // responses without validators can't be revalidated
+        if etag.is_some() || last_modified.is_some() {
+            let cached = CachedResponse {
+                etag,
+                last_modified,
+                body,
+            };
+            if let Some(Err(error)) = self.with_cache(|cache| cache.store_response(&url, &cached)) {
+                warn!("Failed to store cached response: {}", error);
+            }
+            self.remember(&url, &value);
+        }
+
+        Ok(value)
+    }
+
+    fn remember(&self, url: &str, value: &Value) {
+        self.parsed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(url.to_owned(), value.clone());
+    }
+
+    async fn fetch_update(&self, alias: &str) -> Result<Value, FetchError> {
+        let _permit = self.requests.acquire().await;
+        self.pace().await;
+
+        let response = self
+            .client
+            .get(format!("{}/updates/{}", self.url, alias))
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(self.rate_limited(&response));
+        }
+
+        Ok(response.error_for_status()?.json().await?)
+    }
+
+    async fn fetch_all(&self, path: &str, params: &[(&str, String)]) -> Result<Vec<Value>, String> {
+        Ok(self.fetch_counted(path, params).await?.0)
+    }
+
+    // also returns the total number of items that bodhi reported
+    async fn fetch_counted(&self, path: &str, params: &[(&str, String)]) -> Result<(Vec<Value>, Option<u64>), String> {
+        let first = retry(self.policy, &format!("Querying bodhi for {} (page 1)", path), || {
+            self.fetch_page(path, params, 1)
+        })
+        .await?;
+
+        let (mut items, pages, total) = page_items(first, path)?;
+
+        // cold runs can download dozens of pages, which takes a while on slow connections
+        let progress = if pages > 1 {
+            progress::pages(format!("Querying bodhi for {}", path), pages as u64)
+        } else {
+            ProgressBar::hidden()
+        };
+        progress.inc(1);
+        let progress = &progress;
+
+        // the number of pages is known after the first request, fetch the rest concurrently
+        let rest: Vec<Result<Value, String>> = stream::iter(2..=pages)
+            .map(|page| async move {
+                let what = format!("Querying bodhi for {} (page {} of {})", path, page, pages);
+                let result = retry(self.policy, &what, || self.fetch_page(path, params, page)).await;
+                progress.inc(1);
+                result
+            })
+            .buffered(CONCURRENT_PAGES)
+            .collect()
+            .await;
+        progress.finish_and_clear();
+
+        for page in rest {
+            let (more, _, _) = page_items(page?, path)?;
+            items.extend(more);
+        }
+
+        Ok((items, total))
+    }
+
+    async fn fetch_changes(
+        &self,
+        release: &FedoraRelease,
+        incremental: &Incremental,
+        since: &str,
+    ) -> Result<Vec<Value>, String> {
+        let base = vec![("releases", release.to_string()), ("content_type", String::from("rpm"))];
+
+        let mut modified = base.clone();
+        modified.push(("modified_since", since.to_owned()));
+
+        let mut pushed = base.clone();
+        pushed.push(("pushed_since", since.to_owned()));
+
+        // comments don't change the modification date of an update, bodhi accepts comma-separated lists of users
+        let comments = vec![("user", incremental.usernames.join(",")), ("since", since.to_owned())];
+
+        let (modified, pushed, comments) = futures::try_join!(
+            self.fetch_all("updates", &modified),
+            self.fetch_all("updates", &pushed),
+            self.fetch_all("comments", &comments),
+        )?;
+
+        let mut changes = modified;
+        changes.extend(pushed);
+
+        // fetch updates that were commented on since the last run again
+        let mut aliases: Vec<&str> = comments
+            .iter()
+            .filter_map(|comment| comment["update_alias"].as_str())
+            .collect();
+        aliases.sort_unstable();
+        aliases.dedup();
+
+        if !aliases.is_empty() {
+            let mut commented = base;
+            commented.push(("alias", aliases.join(",")));
+            changes.extend(self.fetch_all("updates", &commented).await?);
+        }
+
+        Ok(changes)
+    }
+
+    // returns buildroot overrides of a user that haven't expired yet
+    pub async fn overrides(&self, username: &str) -> Result<Vec<BuildrootOverride>, String> {
+        let params = vec![("user", username.to_owned()), ("expired", String::from("false"))];
+
+        self.fetch_all("overrides", &params)
+            .await?
+            .into_iter()
+            .map(|value| match BuildrootOverride::deserialize(value) {
+                Ok(buildroot_override) => Ok(buildroot_override),
+                Err(error) => Err(format!("Failed to parse buildroot override from bodhi: {}", error)),
+            })
+            .collect()
+    }
+
+    // completes the comments of updates for the given packages if they are missing or incomplete, so that updates
+    // which were already commented on are recognized reliably
+    pub async fn complete_comments(&self, updates: &mut [Update], packages: &[String]) {
+        let mut incomplete = Vec::new();
+
+        for update in updates.iter_mut().filter(|update| {
+            update.builds.iter().any(|build| match parse_nvr(&build.nvr) {
+                Ok((n, _, _)) => packages.iter().any(|package| package == n),
+                Err(_) => false,
+            })
+        }) {
+            let modified = date_modified(update);
+            let cached = self
+                .with_cache(|cache| cache.comments(&update.alias, &modified))
+                .flatten()
+                .map(parse_comments);
+
+            match cached_comments(update, cached) {
+                Some(comments) => update.comments = Some(comments),
+                None => incomplete.push(update),
+            }
+        }
+
+        let fetched: Vec<(&mut Update, Result<(Vec<Value>, Option<u64>), String>)> = stream::iter(incomplete)
+            .map(|update| async move {
+                let params = vec![("updates", update.alias.clone())];
+                let result = self.fetch_counted("comments", &params).await;
+                (update, result)
+            })
+            .buffer_unordered(CONCURRENT_QUERIES)
+            .collect()
+            .await;
+
+        for (update, result) in fetched {
+            let (comments, total) = match result {
+                Ok(result) => result,
+                Err(error) => {
+                    warn!("Failed to query comments of {}: {}", &update.alias, error);
+                    continue;
+                },
+            };
+
+            // comments can be added while the pages are fetched, incomplete lists are not cached
+            let received = comments.len() as u64;
+            if total.map(|total| received >= total).unwrap_or(false) {
+                let modified = date_modified(update);
+                if let Some(Err(error)) =
+                    self.with_cache(|cache| cache.store_comments(&update.alias, &modified, &comments))
+                {
+                    warn!("Failed to store cached comments: {}", error);
+                }
+            } else {
+                debug!(alias = %update.alias, "Received {} comments, but bodhi reported {:?}", received, total);
+            }
+
+            let comments = parse_comments(comments);
+            debug!(alias = %update.alias, "Fetched {} comments", comments.len());
+            update.comments = Some(comments);
+        }
+    }
+
+    // returns releases that are not archived yet
+    pub async fn releases(&self) -> Result<Vec<ReleaseInfo>, String> {
+        let params = vec![("exclude_archived", String::from("true"))];
+
+        self.fetch_all("releases", &params)
+            .await?
+            .into_iter()
+            .map(|value| match ReleaseInfo::deserialize(value) {
+                Ok(release) => Ok(release),
+                Err(error) => Err(format!("Failed to parse release from bodhi: {}", error)),
+            })
+            .collect()
+    }
+
+    // returns updates with other content than RPMs in testing, i.e. "module" updates for F38M
+    pub async fn content_updates(&self, release: &FedoraRelease, content_type: &str) -> Result<Vec<Update>, String> {
+        let params = vec![
+            ("releases", release.to_string()),
+            ("content_type", content_type.to_owned()),
+            ("status", String::from("testing")),
+        ];
+
+        Ok(parse_updates(&self.fetch_all("updates", &params).await?))
+    }
+
+    // returns updates with any status that contain the given builds
+    pub async fn build_updates(&self, nvrs: &[String]) -> Result<Vec<Update>, String> {
+        let mut updates: BTreeMap<String, Value> = BTreeMap::new();
+
+        for chunk in nvrs.chunks(PACKAGES_PER_QUERY) {
+            let params = vec![("builds", chunk.join(","))];
+
+            for update in self.fetch_all("updates", &params).await? {
+                if let Some(alias) = alias_of(&update).map(String::from) {
+                    updates.insert(alias, update);
+                }
+            }
+        }
+
+        Ok(parse_updates(&updates.into_values().collect::<Vec<Value>>()))
+    }
+
+    async fn fetch_testing(&self, release: &FedoraRelease) -> Result<Vec<Value>, String> {
+        let params = vec![
+            ("releases", release.to_string()),
+            ("content_type", String::from("rpm")),
+            ("status", String::from("testing")),
+        ];
+
+        self.fetch_all("updates", &params).await
+    }
+}
+
+#[async_trait]
+impl UpdateSource for BodhiSource {
+    async fn testing_updates(&self, release: &FedoraRelease) -> Result<Vec<Update>, String> {
+        let incremental = match &self.incremental {
+            Some(incremental) => incremental,
+            None => return Ok(parse_updates(&self.fetch_testing(release).await?)),
+        };
+
+        let cached = match self.with_cache(|cache| cache.load_updates(release)) {
+            Some(Ok(cached)) => cached.filter(|cache| !cache.is_stale(incremental.max_age)),
+            Some(Err(error)) => {
+                warn!("Failed to load cached updates: {}", error);
+                None
+            },
+            None => None,
+        };
+
+        let cache = match cached {
+            Some(mut cache) => {
+                let changes = self.fetch_changes(release, incremental, &cache.since()).await?;
+
+                let mut merged: BTreeMap<String, Value> = BTreeMap::new();
+                for update in cache.updates.drain(..).chain(changes) {
+                    if let Some(alias) = alias_of(&update).map(String::from) {
+                        // updates that left testing are dropped from the cache
+                        if is_testing(&update) {
+                            merged.insert(alias, update);
+                        } else {
+                            merged.remove(&alias);
+                        }
+                    }
+                }
+
+                cache.updates = merged.into_values().collect();
+                cache.last_run = chrono::Utc::now().timestamp();
+                cache
+            },
+            None => UpdateCache::new(self.fetch_testing(release).await?),
+        };
+
+        let updates = parse_updates(&cache.updates);
+
+        if let Some(Err(error)) = self.with_cache(|c| c.store_updates(release, &cache)) {
+            warn!("Failed to store cached updates: {}", error);
+        }
+
+        Ok(updates)
+    }
+
+    async fn package_updates(&self, release: &FedoraRelease, packages: &[String]) -> Result<Vec<Update>, String> {
+        let results: Vec<Result<Vec<Value>, String>> = stream::iter(packages.chunks(PACKAGES_PER_QUERY))
+            .map(|chunk| {
+                let params = vec![
+                    ("releases", release.to_string()),
+                    ("content_type", String::from("rpm")),
+                    ("status", String::from("testing")),
+                    ("packages", chunk.join(",")),
+                ];
+                async move { self.fetch_all("updates", &params).await }
+            })
+            .buffer_unordered(CONCURRENT_QUERIES)
+            .collect()
+            .await;
+
+        // updates with builds of multiple packages can be returned by more than one query
+        let mut merged: BTreeMap<String, Value> = BTreeMap::new();
+        for result in results {
+            for update in result? {
+                if let Some(alias) = alias_of(&update).map(String::from) {
+                    merged.insert(alias, update);
+                }
+            }
+        }
+
+        Ok(parse_updates(&merged.into_values().collect::<Vec<Value>>()))
+    }
+
+    async fn user_updates(&self, username: &str, since: &str) -> Result<Vec<Update>, String> {
+        let params = |status: &str| vec![("user", username.to_owned()), ("status", status.to_owned())];
+
+        let pending = params("pending");
+        let testing = params("testing");
+        let mut stable = params("stable");
+        stable.push(("pushed_since", since.to_owned()));
+
+        let (pending, testing, stable) = futures::try_join!(
+            self.fetch_all("updates", &pending),
+            self.fetch_all("updates", &testing),
+            self.fetch_all("updates", &stable),
+        )?;
+
+        let mut updates = pending;
+        updates.extend(testing);
+        updates.extend(stable);
+
+        Ok(parse_updates(&updates))
+    }
+
+    async fn update(&self, alias: &str) -> Result<Update, String> {
+        let mut response = retry(self.policy, &format!("Querying bodhi for {}", alias), || {
+            self.fetch_update(alias)
+        })
+        .await?;
+
+        match response.get_mut("update").map(Value::take) {
+            Some(update) => match Update::deserialize(update) {
+                Ok(update) => Ok(update),
+                Err(error) => Err(format!("Failed to parse update from bodhi: {}", error)),
+            },
+            None => Err(String::from("Unexpected response from bodhi: missing \"update\"")),
+        }
+    }
+}
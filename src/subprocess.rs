@@ -0,0 +1,72 @@
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// seconds until dnf and rpm are stopped, i.e. when they are waiting for the rpm database lock forever
+pub const DEFAULT_TIMEOUT: u64 = 300;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+pub fn set_timeout(timeout: Duration) {
+    let _ = TIMEOUT.set(timeout);
+}
+
+fn timeout() -> Duration {
+    TIMEOUT.get().copied().unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT))
+}
+
+fn read_all(pipe: Option<impl Read + Send + 'static>) -> Option<thread::JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            let _ = pipe.read_to_end(&mut buffer);
+            buffer
+        })
+    })
+}
+
+// like Command::output, but the child process is killed if it doesn't finish in time
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let timeout = timeout();
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // the pipes need to be read while waiting, otherwise large outputs block the child
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait()? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "{} did not finish within {} seconds and was stopped",
+                        program,
+                        timeout.as_secs()
+                    ),
+                ));
+            },
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.and_then(|reader| reader.join().ok()).unwrap_or_default(),
+        stderr: stderr.and_then(|reader| reader.join().ok()).unwrap_or_default(),
+    })
+}
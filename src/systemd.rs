@@ -0,0 +1,135 @@
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+use std::path::PathBuf;
+use std::process::Command;
+
+const UNIT_NAME: &str = "fedora-update-notifier";
+
+pub const DEFAULT_ON_CALENDAR: &str = "*-*-* 00/6:00:00";
+
+fn unit_dir() -> Result<PathBuf, String> {
+    match dirs::config_dir() {
+        Some(path) => Ok(path.join("systemd/user")),
+        None => Err(String::from("Unable to determine $XDG_CONFIG_HOME.")),
+    }
+}
+
+fn systemctl(args: &[&str]) -> Result<(), String> {
+    let status = match Command::new("systemctl").arg("--user").args(args).status() {
+        Ok(status) => status,
+        Err(error) => {
+            return Err(format!("{}", error));
+        },
+    };
+
+    if !status.success() {
+        return Err(format!("Failed to run systemctl --user {}.", args.join(" ")));
+    }
+
+    Ok(())
+}
+
+fn service_unit(exec: &str) -> String {
+    format!(
+        r#"[Unit]
+Description=Check for installed fedora updates awaiting feedback
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=oneshot
+NotifyAccess=main
+SuccessExitStatus=2 3 4 11 12
+ExecStart={}
+"#,
+        exec
+    )
+}
+
+fn success_exit_status(unit: &str) -> Option<&str> {
+    unit.lines().find_map(|line| line.strip_prefix("SuccessExitStatus="))
+}
+
+// units written by older versions don't know about newer exit statuses, so systemd would consider those runs failed;
+// returns None if the service is not installed
+pub fn is_service_stale() -> Option<bool> {
+    let path = unit_dir().ok()?.join(format!("{}.service", UNIT_NAME));
+    let installed = read_to_string(path).ok()?;
+    let current = service_unit("");
+
+    Some(success_exit_status(&installed) != success_exit_status(&current))
+}
+
+fn timer_unit(on_calendar: &str) -> String {
+    format!(
+        r#"[Unit]
+Description=Periodically check for installed fedora updates awaiting feedback
+
+[Timer]
+OnCalendar={}
+Persistent=true
+RandomizedDelaySec=5min
+
+[Install]
+WantedBy=timers.target
+"#,
+        on_calendar
+    )
+}
+
+pub fn install_service(on_calendar: &str) -> Result<(), String> {
+    let exec = match std::env::current_exe() {
+        Ok(path) => path,
+        Err(error) => {
+            return Err(format!("Unable to determine path of the executable: {}", error));
+        },
+    };
+
+    let dir = unit_dir()?;
+    if let Err(error) = create_dir_all(&dir) {
+        return Err(format!("Unable to create {}: {}", dir.display(), error));
+    }
+
+    let service_path = dir.join(format!("{}.service", UNIT_NAME));
+    let timer_path = dir.join(format!("{}.timer", UNIT_NAME));
+
+    if let Err(error) = write(&service_path, service_unit(&exec.to_string_lossy())) {
+        return Err(format!("Unable to write {}: {}", service_path.display(), error));
+    }
+
+    if let Err(error) = write(&timer_path, timer_unit(on_calendar)) {
+        return Err(format!("Unable to write {}: {}", timer_path.display(), error));
+    }
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", &format!("{}.timer", UNIT_NAME)])?;
+
+    println!("Installed {}", service_path.display());
+    println!("Installed {}", timer_path.display());
+    println!("Enabled {}.timer (OnCalendar={})", UNIT_NAME, on_calendar);
+
+    Ok(())
+}
+
+pub fn uninstall_service() -> Result<(), String> {
+    let dir = unit_dir()?;
+
+    let service_path = dir.join(format!("{}.service", UNIT_NAME));
+    let timer_path = dir.join(format!("{}.timer", UNIT_NAME));
+
+    if timer_path.exists() {
+        systemctl(&["disable", "--now", &format!("{}.timer", UNIT_NAME)])?;
+    }
+
+    for path in &[timer_path, service_path] {
+        if path.exists() {
+            if let Err(error) = remove_file(path) {
+                return Err(format!("Unable to remove {}: {}", path.display(), error));
+            }
+            println!("Removed {}", path.display());
+        }
+    }
+
+    systemctl(&["daemon-reload"])?;
+
+    Ok(())
+}
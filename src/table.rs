@@ -0,0 +1,102 @@
+use std::io::IsTerminal;
+
+use crossterm::style::{Color, Stylize};
+
+use crate::journal;
+
+// colors are only used in terminals, and not at all if NO_COLOR is set (see https://no-color.org)
+pub fn use_color() -> bool {
+    let no_color = std::env::var_os("NO_COLOR")
+        .map(|value| !value.is_empty())
+        .unwrap_or(false);
+    !no_color && !journal::is_journal() && std::io::stdout().is_terminal()
+}
+
+#[derive(Debug)]
+pub struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Cell {
+            text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn colored(text: impl Into<String>, color: Option<Color>) -> Self {
+        Cell {
+            text: text.into(),
+            color,
+        }
+    }
+}
+
+// columns are aligned by the number of characters, colors don't take up any space
+#[derive(Debug)]
+pub struct Table {
+    header: Vec<Cell>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(header: &[&str]) -> Self {
+        Table {
+            header: header.iter().map(|title| Cell::new(*title)).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.header.iter().map(|cell| cell.text.chars().count()).collect();
+
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.text.chars().count());
+            }
+        }
+
+        widths
+    }
+
+    pub fn print(&self) {
+        let color = use_color();
+        let widths = self.widths();
+
+        println!("{}", format_row(&self.header, &widths, color, true));
+        for row in &self.rows {
+            println!("{}", format_row(row, &widths, color, false));
+        }
+    }
+}
+
+fn format_row(row: &[Cell], widths: &[usize], color: bool, bold: bool) -> String {
+    let mut line = String::new();
+
+    for (index, (cell, width)) in row.iter().zip(widths).enumerate() {
+        if index > 0 {
+            line.push_str("  ");
+        }
+
+        // the last column is not padded, so lines don't end with whitespace
+        let text = if index + 1 < row.len() {
+            format!("{:<width$}", &cell.text, width = width)
+        } else {
+            cell.text.clone()
+        };
+
+        match (cell.color, color) {
+            (_, true) if bold => line.push_str(&text.bold().to_string()),
+            (Some(fg), true) => line.push_str(&text.with(fg).to_string()),
+            _ => line.push_str(&text),
+        }
+    }
+
+    line
+}
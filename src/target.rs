@@ -0,0 +1,256 @@
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+use std::process::Command;
+
+use bodhi::data::FedoraRelease;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::backend::{ChrootBackend, ContainerBackend, PackageBackend, RemoteBackend};
+use crate::subprocess;
+
+const MOCK_DIR: &str = "/var/lib/mock";
+
+// where packages are installed, i.e. the local system, a toolbox container, a mock chroot, or a remote host
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Source {
+    pub kind: String,
+    pub name: String,
+}
+
+fn local_hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|hostname| hostname.trim().to_owned())
+        .unwrap_or_else(|_| String::from("localhost"))
+}
+
+impl Source {
+    pub fn new(kind: &str, name: &str) -> Self {
+        Source {
+            kind: kind.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn local() -> Self {
+        Source::new("local", &local_hostname())
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.kind == "local"
+    }
+}
+
+// i.e. "toolbox fedora-toolbox-39"
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", &self.kind, &self.name)
+    }
+}
+
+// other systems whose installed packages are checked in addition to the local system
+pub struct Target {
+    pub source: Source,
+    pub release: FedoraRelease,
+    pub backend: Box<dyn PackageBackend>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HostConfig {
+    // SSH destination, i.e. "user@server.example.com"
+    pub host: String,
+    // label for the host in output, defaults to the SSH destination
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+fn os_release_value<'a>(os_release: &'a str, key: &str) -> Option<&'a str> {
+    os_release.lines().find_map(|line| match line.split_once('=') {
+        Some((k, value)) if k.trim() == key => Some(value.trim().trim_matches('"')),
+        _ => None,
+    })
+}
+
+// RHEL, CentOS Stream, and rebuilds like AlmaLinux or Rocky Linux, which can use EPEL
+pub fn is_enterprise_linux(os_release: &str) -> bool {
+    let id = os_release_value(os_release, "ID").unwrap_or_default();
+    let id_like = os_release_value(os_release, "ID_LIKE").unwrap_or_default();
+    id == "rhel" || id_like.split_whitespace().any(|like| like == "rhel")
+}
+
+pub fn epel_release(major: &str) -> Result<FedoraRelease, String> {
+    FedoraRelease::try_from(format!("EPEL-{}", major).as_str()).map_err(|error| error.to_string())
+}
+
+// determines the Fedora (or EPEL) release from the contents of /etc/os-release
+pub fn release_from_os_release(os_release: &str) -> Result<FedoraRelease, String> {
+    let id = os_release_value(os_release, "ID");
+    let version_id = os_release_value(os_release, "VERSION_ID");
+
+    match (id, version_id) {
+        (Some("fedora"), Some(version_id)) => {
+            FedoraRelease::try_from(format!("F{}", version_id).as_str()).map_err(|error| error.to_string())
+        },
+        // VERSION_ID is "9.3" on RHEL, but only "9" on CentOS Stream
+        (Some(_), Some(version_id)) if is_enterprise_linux(os_release) => {
+            epel_release(version_id.split('.').next().unwrap_or_default())
+        },
+        (Some(id), _) => Err(format!("Not a Fedora or Enterprise Linux system: {}", id)),
+        _ => Err(String::from("Unable to determine release from /etc/os-release.")),
+    }
+}
+
+fn is_toolbox(container: &Value) -> bool {
+    let labels = &container["Labels"];
+    labels["com.github.containers.toolbox"].as_str() == Some("true") || labels["manager"].as_str() == Some("distrobox")
+}
+
+// enumerates toolbox and distrobox containers, and starts stopped ones until their packages were queried
+pub fn toolboxes() -> Result<Vec<Target>, String> {
+    let output = match subprocess::output(Command::new("podman").args(["ps", "--all", "--format", "json"])) {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => {
+            return Err(String::from("Failed to list podman containers."));
+        },
+        Err(error) => {
+            return Err(format!("Failed to run podman: {}", error));
+        },
+    };
+
+    let containers: Vec<Value> = match serde_json::from_slice(&output.stdout) {
+        Ok(containers) => containers,
+        Err(error) => {
+            return Err(format!("Failed to parse podman output: {}", error));
+        },
+    };
+
+    let mut targets = Vec::new();
+
+    for container in containers.iter().filter(|container| is_toolbox(container)) {
+        let name = match container["Names"][0].as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+
+        // toolbox containers are usually stopped, and are stopped again after the query
+        let running = container["State"].as_str() == Some("running");
+        if !running {
+            match subprocess::output(Command::new("podman").args(["start", name])) {
+                Ok(output) if output.status.success() => {},
+                Ok(output) => {
+                    warn!(
+                        "Skipping container {}: {}",
+                        name,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                    continue;
+                },
+                Err(error) => {
+                    warn!("Skipping container {}: {}", name, error);
+                    continue;
+                },
+            }
+        }
+
+        let backend = ContainerBackend::new(name, !running);
+        let release = match backend
+            .exec(&["cat", "/etc/os-release"])
+            .and_then(|os_release| release_from_os_release(&os_release))
+        {
+            Ok(release) => release,
+            Err(error) => {
+                warn!("Skipping container {}: {}", name, error);
+                backend.stop();
+                continue;
+            },
+        };
+
+        targets.push(Target {
+            source: Source::new("toolbox", name),
+            release,
+            backend: Box::new(backend),
+        });
+    }
+
+    Ok(targets)
+}
+
+// enumerates the chroots of mock (i.e. "fedora-40-x86_64"), chroots that were never initialized are skipped
+pub fn mock_chroots() -> Result<Vec<Target>, String> {
+    let entries = match read_dir(MOCK_DIR) {
+        Ok(entries) => entries,
+        Err(error) => {
+            return Err(format!("Failed to read {}: {}", MOCK_DIR, error));
+        },
+    };
+
+    let mut roots: Vec<(String, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                entry.path().join("root"),
+            )
+        })
+        .filter(|(_, root)| root.join("var/lib/rpm").is_dir() || root.join("usr/lib/sysimage/rpm").is_dir())
+        .collect();
+    roots.sort();
+
+    let mut targets = Vec::new();
+
+    for (name, root) in roots {
+        // /etc/os-release is a relative symlink, which is resolved inside the chroot as well
+        let release = match read_to_string(root.join("etc/os-release"))
+            .map_err(|error| error.to_string())
+            .and_then(|os_release| release_from_os_release(&os_release))
+        {
+            Ok(release) => release,
+            Err(error) => {
+                warn!("Skipping mock chroot {}: {}", name, error);
+                continue;
+            },
+        };
+
+        targets.push(Target {
+            source: Source::new("mock", &name),
+            release,
+            backend: Box::new(ChrootBackend::new(&root)),
+        });
+    }
+
+    Ok(targets)
+}
+
+// connects to remote hosts over SSH, hosts that can't be reached are skipped
+pub fn hosts(configs: &[HostConfig]) -> Vec<Target> {
+    let mut targets = Vec::new();
+
+    for config in configs {
+        let name = config.name.clone().unwrap_or_else(|| config.host.clone());
+        let backend = RemoteBackend::ssh(&name, &config.host, config.port);
+
+        let release = match backend
+            .exec(&["cat", "/etc/os-release"])
+            .and_then(|os_release| release_from_os_release(&os_release))
+        {
+            Ok(release) => release,
+            Err(error) => {
+                warn!("Skipping host {}: {}", name, error);
+                continue;
+            },
+        };
+
+        targets.push(Target {
+            source: Source::new("host", &name),
+            release,
+            backend: Box::new(backend),
+        });
+    }
+
+    targets
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeSet;
+
+use minijinja::{context, Environment};
+use serde::{Deserialize, Serialize};
+
+use crate::output::{Summary, UpdateSummary};
+
+// user-provided templates in minijinja (jinja2) syntax, which replace the built-in formatting
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TemplateConfig {
+    // notification summary and body, with the fields of the webhook payload
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    // terminal output, with the fields of the JSON output
+    #[serde(default)]
+    pub text: Option<String>,
+    // bodhi URLs for updates, with "release", "packages", and the default "url"
+    #[serde(default)]
+    pub feedback_url: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Templates {
+    config: TemplateConfig,
+    env: Environment<'static>,
+}
+
+impl Templates {
+    pub fn new(config: TemplateConfig) -> Result<Self, String> {
+        let env = Environment::new();
+
+        // syntax errors are reported right away, and not only once something needs to be rendered
+        for (name, source) in [
+            ("summary", &config.summary),
+            ("body", &config.body),
+            ("text", &config.text),
+            ("feedback_url", &config.feedback_url),
+        ] {
+            if let Some(source) = source {
+                if let Err(error) = env.template_from_str(source) {
+                    return Err(format!("Invalid {} template: {}", name, error));
+                }
+            }
+        }
+
+        Ok(Templates { config, env })
+    }
+
+    fn render<S: Serialize>(&self, source: &str, ctx: S) -> Result<String, String> {
+        match self.env.render_str(source, ctx) {
+            Ok(rendered) => Ok(rendered),
+            Err(error) => Err(format!("Failed to render template: {}", error)),
+        }
+    }
+
+    pub fn has_notification(&self) -> bool {
+        self.config.summary.is_some() || self.config.body.is_some()
+    }
+
+    pub fn summary<S: Serialize>(&self, message: &S) -> Result<Option<String>, String> {
+        match &self.config.summary {
+            Some(source) => Ok(Some(self.render(source, message)?.trim().to_owned())),
+            None => Ok(None),
+        }
+    }
+
+    pub fn body<S: Serialize>(&self, message: &S) -> Result<Option<String>, String> {
+        match &self.config.body {
+            Some(source) => Ok(Some(self.render(source, message)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn text(&self, summary: &Summary) -> Result<Option<String>, String> {
+        match &self.config.text {
+            Some(source) => Ok(Some(self.render(source, summary)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn url(&self, release: &str, url: &mut Option<String>, updates: &[UpdateSummary]) -> Result<(), String> {
+        let (source, default) = match (&self.config.feedback_url, url.as_ref()) {
+            (Some(source), Some(default)) => (source, default),
+            _ => return Ok(()),
+        };
+
+        let packages: BTreeSet<&str> = updates
+            .iter()
+            .flat_map(|update| &update.packages)
+            .map(String::as_str)
+            .collect();
+
+        let rendered = self.render(source, context! { release, packages, url => default })?;
+        *url = Some(rendered.trim().to_owned());
+        Ok(())
+    }
+
+    // replaces the bodhi URLs in a summary (and the summaries of other systems)
+    pub fn apply_urls(&self, summary: &mut Summary) -> Result<(), String> {
+        self.url(&summary.release, &mut summary.feedback_url, &summary.feedback)?;
+        self.url(&summary.release, &mut summary.interesting_url, &summary.pending)?;
+
+        for target in &mut summary.targets {
+            self.apply_urls(&mut target.summary)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use ksni::menu::{CheckmarkItem, StandardItem};
+use ksni::{MenuItem, ToolTip, TrayService};
+use tokio::sync::Notify;
+use tracing::warn;
+
+// status icon (StatusNotifierItem) with the number of updates that are ready for feedback
+pub struct Tray {
+    count: usize,
+    feedback_url: Option<String>,
+    refresh: Arc<Notify>,
+    paused: Arc<AtomicBool>,
+}
+
+fn open(url: &str) {
+    if let Err(error) = Command::new("xdg-open").arg(url).spawn() {
+        warn!("Failed to open browser: {}", error);
+    }
+}
+
+impl Tray {
+    fn text(&self) -> String {
+        match self.count {
+            0 => String::from("No updates are ready for feedback"),
+            1 => String::from("1 update is ready for feedback"),
+            count => format!("{} updates are ready for feedback", count),
+        }
+    }
+}
+
+impl ksni::Tray for Tray {
+    fn id(&self) -> String {
+        String::from("fedora-update-notifier")
+    }
+
+    fn title(&self) -> String {
+        String::from("Fedora Update Notifier")
+    }
+
+    fn icon_name(&self) -> String {
+        if self.count > 0 {
+            String::from("software-update-available")
+        } else {
+            String::from("system-software-update")
+        }
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        ToolTip {
+            title: self.text(),
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        if let Some(url) = &self.feedback_url {
+            open(url);
+        }
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        vec![
+            StandardItem {
+                label: self.text(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: String::from("Open in bodhi"),
+                enabled: self.feedback_url.is_some(),
+                activate: Box::new(|tray: &mut Self| {
+                    if let Some(url) = &tray.feedback_url {
+                        open(url);
+                    }
+                }),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: String::from("Check now"),
+                activate: Box::new(|tray: &mut Self| tray.refresh.notify_one()),
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: String::from("Pause notifications"),
+                checked: self.paused.load(Ordering::Relaxed),
+                activate: Box::new(|tray: &mut Self| {
+                    tray.paused.fetch_xor(true, Ordering::Relaxed);
+                }),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+pub struct TrayHandle {
+    handle: ksni::Handle<Tray>,
+}
+
+impl TrayHandle {
+    pub fn update(&self, count: usize, feedback_url: Option<String>) {
+        self.handle.update(|tray: &mut Tray| {
+            tray.count = count;
+            tray.feedback_url = feedback_url;
+        });
+    }
+}
+
+// the tray runs on its own thread, and is removed when the process exits
+pub fn spawn(refresh: Arc<Notify>, paused: Arc<AtomicBool>) -> TrayHandle {
+    let service = TrayService::new(Tray {
+        count: 0,
+        feedback_url: None,
+        refresh,
+        paused,
+    });
+
+    let handle = service.handle();
+    service.spawn();
+
+    TrayHandle { handle }
+}
@@ -0,0 +1,317 @@
+use std::io::{self, Stdout};
+use std::process::Command;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{Frame, Terminal};
+
+use crate::cache::Cache;
+use crate::feedback::{queue, submit, Feedback, SubmitError};
+use crate::output::{Summary, UpdateSummary};
+
+// snoozed updates are hidden for this long
+const SNOOZE_DAYS: i64 = 7;
+
+type Backend = CrosstermBackend<Stdout>;
+
+struct Entry<'a> {
+    section: &'static str,
+    update: &'a UpdateSummary,
+}
+
+struct App<'a> {
+    entries: Vec<Entry<'a>>,
+    state: TableState,
+    scroll: u16,
+    status: String,
+}
+
+impl<'a> App<'a> {
+    fn new(summary: &'a Summary, logged_in: bool) -> Self {
+        let mut entries: Vec<Entry> = Vec::new();
+        entries.extend(summary.feedback.iter().map(|update| Entry {
+            section: "feedback",
+            update,
+        }));
+        entries.extend(summary.pending.iter().map(|update| Entry {
+            section: "interesting",
+            update,
+        }));
+
+        let mut state = TableState::default();
+        if !entries.is_empty() {
+            state.select(Some(0));
+        }
+
+        let status = if logged_in {
+            "↑/↓: select, PgUp/PgDn: scroll notes, o: open, i: ignore, s: snooze, +/0/-: karma, q: quit"
+        } else {
+            "↑/↓: select, PgUp/PgDn: scroll notes, o: open, i: ignore, s: snooze, q: quit (log in to submit karma)"
+        };
+
+        App {
+            entries,
+            state,
+            scroll: 0,
+            status: status.to_owned(),
+        }
+    }
+
+    fn selected(&self) -> Option<&'a UpdateSummary> {
+        self.state
+            .selected()
+            .and_then(|index| self.entries.get(index))
+            .map(|entry| entry.update)
+    }
+
+    fn select(&mut self, offset: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, self.entries.len() as isize - 1);
+        self.state.select(Some(next as usize));
+        self.scroll = 0;
+    }
+
+    // removes the selected update from the list, i.e. after it was ignored or snoozed
+    fn remove_selected(&mut self) {
+        if let Some(index) = self.state.selected() {
+            self.entries.remove(index);
+            self.state.select(match self.entries.len() {
+                0 => None,
+                len => Some(index.min(len - 1)),
+            });
+            self.scroll = 0;
+        }
+    }
+}
+
+fn details(update: &UpdateSummary) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(format!("{} ({})", &update.alias, &update.url)),
+        Line::from(format!("Submitter: {}", &update.submitter)),
+        Line::from(format!("Status: {}", update.karma_info())),
+        Line::from("Builds:"),
+    ];
+    lines.extend(update.builds.iter().map(|build| Line::from(format!("  - {}", build))));
+
+    if !update.notes.trim().is_empty() {
+        lines.push(Line::from("Notes:"));
+        lines.extend(
+            update
+                .notes
+                .trim()
+                .lines()
+                .map(|line| Line::from(format!("  {}", line))),
+        );
+    }
+
+    if !update.karma_feedback.is_empty() {
+        lines.push(Line::from("Karma:"));
+        for feedback in &update.karma_feedback {
+            lines.push(Line::from(format!("  {:+} from {}", feedback.karma, &feedback.user)));
+        }
+    }
+
+    lines
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Min(3), Constraint::Length(1)])
+        .split(frame.size());
+
+    let rows: Vec<Row> = app
+        .entries
+        .iter()
+        .map(|entry| {
+            let update = entry.update;
+            let karma = match update.stable_karma {
+                Some(stable_karma) => format!("{}/{}", update.karma, stable_karma),
+                None => update.karma.to_string(),
+            };
+            let age = match update.days_in_testing {
+                Some(days) => format!("{}d", days),
+                None => String::from("-"),
+            };
+
+            Row::new(vec![
+                update.alias.clone(),
+                entry.section.to_owned(),
+                karma,
+                age,
+                update.update_type.clone(),
+                update.packages.join(", "),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Length(26),
+        Constraint::Length(12),
+        Constraint::Length(7),
+        Constraint::Length(5),
+        Constraint::Length(12),
+        Constraint::Min(10),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(vec!["Update", "Section", "Karma", "Age", "Type", "Packages"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title("Updates"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, chunks[0], &mut app.state);
+
+    let lines = match app.selected() {
+        Some(update) => details(update),
+        None => vec![Line::from("There are no updates that need feedback.")],
+    };
+    let notes = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Details"))
+        .wrap(Wrap { trim: false })
+        .scroll((app.scroll, 0));
+    frame.render_widget(notes, chunks[1]);
+
+    frame.render_widget(Paragraph::new(app.status.as_str()), chunks[2]);
+}
+
+fn setup() -> Result<Terminal<Backend>, io::Error> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(stdout))
+}
+
+fn restore(terminal: &mut Terminal<Backend>) {
+    let _ = disable_raw_mode();
+    let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+    let _ = terminal.show_cursor();
+}
+
+async fn event_loop(
+    terminal: &mut Terminal<Backend>,
+    app: &mut App<'_>,
+    cache: Option<&Cache>,
+    access_token: Option<&str>,
+) -> Result<(), String> {
+    loop {
+        if let Err(error) = terminal.draw(|frame| draw(frame, app)) {
+            return Err(format!("Failed to draw terminal: {}", error));
+        }
+
+        // reading events blocks until a key is pressed
+        let event = match tokio::task::spawn_blocking(event::read).await {
+            Ok(Ok(event)) => event,
+            Ok(Err(error)) => return Err(format!("Failed to read terminal input: {}", error)),
+            Err(error) => return Err(format!("Failed to read terminal input: {}", error)),
+        };
+
+        let key = match event {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => app.select(1),
+            KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+            KeyCode::PageDown => app.scroll = app.scroll.saturating_add(10),
+            KeyCode::PageUp => app.scroll = app.scroll.saturating_sub(10),
+            KeyCode::Char('o') => {
+                if let Some(update) = app.selected() {
+                    app.status = match Command::new("xdg-open").arg(&update.url).output() {
+                        Ok(_) => format!("Opened {} in the browser.", &update.alias),
+                        Err(error) => format!("Failed to open browser: {}", error),
+                    };
+                }
+            },
+            KeyCode::Char(c @ ('i' | 's')) => {
+                let (update, cache) = match (app.selected(), cache) {
+                    (Some(update), Some(cache)) => (update, cache),
+                    (Some(_), None) => {
+                        app.status = String::from("Updates can't be hidden without a cache.");
+                        continue;
+                    },
+                    _ => continue,
+                };
+
+                let (until, message) = if c == 'i' {
+                    (None, format!("Ignored {}.", &update.alias))
+                } else {
+                    let until = chrono::Utc::now() + chrono::Duration::days(SNOOZE_DAYS);
+                    (
+                        Some(until.timestamp()),
+                        format!("Snoozed {} for {} days.", &update.alias, SNOOZE_DAYS),
+                    )
+                };
+
+                app.status = match cache.dismiss(&update.alias, until) {
+                    Ok(()) => {
+                        app.remove_selected();
+                        message
+                    },
+                    Err(error) => error,
+                };
+            },
+            KeyCode::Char(c @ ('+' | '0' | '-')) => {
+                let (update, access_token) = match (app.selected(), access_token) {
+                    (Some(update), Some(access_token)) => (update, access_token),
+                    (Some(_), None) => {
+                        app.status = String::from("Not logged in, run \"fedora-update-notifier login\" first.");
+                        continue;
+                    },
+                    _ => continue,
+                };
+
+                let feedback = Feedback {
+                    text: String::new(),
+                    karma: match c {
+                        '+' => 1,
+                        '-' => -1,
+                        _ => 0,
+                    },
+                    testcases: Vec::new(),
+                };
+
+                app.status = format!("Submitting feedback for {} ...", &update.alias);
+                let _ = terminal.draw(|frame| draw(frame, app));
+
+                app.status = match submit(access_token, &update.alias, &feedback).await {
+                    Ok(()) => format!("Submitted {:+} karma for {}.", feedback.karma, &update.alias),
+                    // submitted again by the next run
+                    Err(SubmitError::Unavailable(_)) => match queue(&update.alias, &feedback) {
+                        Ok(()) => format!("Bodhi is not reachable, queued karma for {}.", &update.alias),
+                        Err(error) => error,
+                    },
+                    Err(error) => error.to_string(),
+                };
+            },
+            _ => {},
+        }
+    }
+}
+
+// interactive list of updates that need feedback, with the same contents as the terminal output
+pub async fn run(summary: &Summary, cache: Option<&Cache>, access_token: Option<&str>) -> Result<(), String> {
+    let mut app = App::new(summary, access_token.is_some());
+
+    let mut terminal = match setup() {
+        Ok(terminal) => terminal,
+        Err(error) => return Err(format!("Failed to set up terminal: {}", error)),
+    };
+
+    let result = event_loop(&mut terminal, &mut app, cache, access_token).await;
+    restore(&mut terminal);
+    result
+}
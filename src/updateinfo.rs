@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+use bodhi::data::Update;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::dnf::{dnf, TESTING_REPO};
+use crate::nvr::{install_state, InstallState, NEVR};
+
+#[derive(Debug, Deserialize)]
+pub struct Advisory {
+    pub name: String,
+    pub nevra: String,
+}
+
+// returns the advisories from updates-testing that would upgrade installed packages, from the metadata that dnf
+// already downloaded, so this works without network access
+pub fn local_advisories() -> Result<Vec<Advisory>, String> {
+    let output = dnf(&[
+        "updateinfo",
+        "list",
+        "--cacheonly",
+        &format!("--enablerepo={}", TESTING_REPO),
+        "--updates",
+        "--json",
+    ])?;
+
+    match serde_json::from_str(&output) {
+        Ok(advisories) => Ok(advisories),
+        Err(error) => Err(format!("Failed to parse advisories from dnf: {}", error)),
+    }
+}
+
+// compares the advisories from the local metadata with the updates that were matched against installed packages
+pub fn cross_check(advisories: &[Advisory], packages: &[NEVR], updates: &[Update]) -> Vec<String> {
+    let matched: HashSet<&str> = updates
+        .iter()
+        .filter(|update| {
+            update.builds.iter().any(|build| match NEVR::from_build(build) {
+                Ok(nevr) => install_state(packages, &nevr) != InstallState::NotInstalled,
+                Err(_) => false,
+            })
+        })
+        .map(|update| update.alias.as_str())
+        .collect();
+
+    let mut discrepancies: Vec<String> = Vec::new();
+    let mut reported: HashSet<&str> = HashSet::new();
+
+    for advisory in advisories {
+        if !matched.contains(advisory.name.as_str()) && reported.insert(&advisory.name) {
+            discrepancies.push(format!(
+                "{} (for {}) is available in the local {} metadata, but was not matched from bodhi.",
+                &advisory.name, &advisory.nevra, TESTING_REPO
+            ));
+        }
+    }
+
+    // updates that were pushed after dnf refreshed its metadata are expected to be missing locally
+    let local: HashSet<&str> = advisories.iter().map(|advisory| advisory.name.as_str()).collect();
+    for alias in matched.iter().filter(|alias| !local.contains(*alias)) {
+        debug!(alias = %alias, "Not available in the local {} metadata", TESTING_REPO);
+    }
+
+    discrepancies.sort();
+    discrepancies
+}
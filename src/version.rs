@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+
+// port of rpmvercmp from rpm's lib/rpmvercmp.c
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let one = a.as_bytes();
+    let two = b.as_bytes();
+
+    let mut i = 0;
+    let mut j = 0;
+
+    let is_separator = |c: u8| !c.is_ascii_alphanumeric() && c != b'~' && c != b'^';
+
+    while i < one.len() || j < two.len() {
+        while i < one.len() && is_separator(one[i]) {
+            i += 1;
+        }
+        while j < two.len() && is_separator(two[j]) {
+            j += 1;
+        }
+
+        let x = one.get(i).copied();
+        let y = two.get(j).copied();
+
+        // tilde sorts before everything else, even the end of the string
+        if x == Some(b'~') || y == Some(b'~') {
+            if x != Some(b'~') {
+                return Ordering::Greater;
+            }
+            if y != Some(b'~') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // caret sorts after the end of the string, but before everything else
+        if x == Some(b'^') || y == Some(b'^') {
+            if x.is_none() {
+                return Ordering::Less;
+            }
+            if y.is_none() {
+                return Ordering::Greater;
+            }
+            if x != Some(b'^') {
+                return Ordering::Greater;
+            }
+            if y != Some(b'^') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        if i >= one.len() || j >= two.len() {
+            break;
+        }
+
+        let start_one = i;
+        let start_two = j;
+
+        let numeric = one[i].is_ascii_digit();
+        if numeric {
+            while i < one.len() && one[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < two.len() && two[j].is_ascii_digit() {
+                j += 1;
+            }
+        } else {
+            while i < one.len() && one[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            while j < two.len() && two[j].is_ascii_alphabetic() {
+                j += 1;
+            }
+        }
+
+        let seg_one = &one[start_one..i];
+        let seg_two = &two[start_two..j];
+
+        // segments of different types: numeric segments are always newer
+        if seg_two.is_empty() {
+            return if numeric { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let ordering = if numeric {
+            let seg_one = trim_zeros(seg_one);
+            let seg_two = trim_zeros(seg_two);
+            seg_one.len().cmp(&seg_two.len()).then_with(|| seg_one.cmp(seg_two))
+        } else {
+            seg_one.cmp(seg_two)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    match (i >= one.len(), j >= two.len()) {
+        (true, true) => Ordering::Equal,
+        (false, _) => Ordering::Greater,
+        (true, false) => Ordering::Less,
+    }
+}
+
+fn trim_zeros(segment: &[u8]) -> &[u8] {
+    let start = segment.iter().position(|c| *c != b'0').unwrap_or(segment.len());
+    &segment[start..]
+}
+
+pub fn evrcmp(a: (u32, &str, &str), b: (u32, &str, &str)) -> Ordering {
+    let (e1, v1, r1) = a;
+    let (e2, v2, r2) = b;
+
+    e1.cmp(&e2)
+        .then_with(|| rpmvercmp(v1, v2))
+        .then_with(|| rpmvercmp(r1, r2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        // test cases from rpm's tests/rpmvercmp.at
+        let cases = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "2.0", Ordering::Less),
+            ("2.0.1", "2.0.1", Ordering::Equal),
+            ("2.0", "2.0.1", Ordering::Less),
+            ("2.0.1a", "2.0.1a", Ordering::Equal),
+            ("2.0.1a", "2.0.1", Ordering::Greater),
+            ("5.5p1", "5.5p2", Ordering::Less),
+            ("5.5p10", "5.5p1", Ordering::Greater),
+            ("10xyz", "10.1xyz", Ordering::Less),
+            ("xyz10", "xyz10.1", Ordering::Less),
+            ("xyz.4", "8", Ordering::Less),
+            ("xyz.4", "2", Ordering::Less),
+            ("5.5p2", "5.6p1", Ordering::Less),
+            ("5.6p1", "6.5p1", Ordering::Less),
+            ("6.0.rc1", "6.0", Ordering::Greater),
+            ("10b2", "10a1", Ordering::Greater),
+            ("1.0aa", "1.0a", Ordering::Greater),
+            ("10.0001", "10.1", Ordering::Equal),
+            ("10.0001", "10.0039", Ordering::Less),
+            ("4.999.9", "5.0", Ordering::Less),
+            ("20101121", "20101122", Ordering::Less),
+            ("2_0", "2_0", Ordering::Equal),
+            ("2.0", "2_0", Ordering::Equal),
+            ("a", "a", Ordering::Equal),
+            ("a+", "a_", Ordering::Equal),
+            ("+", "_", Ordering::Equal),
+            ("1.0~rc1", "1.0~rc1", Ordering::Equal),
+            ("1.0~rc1", "1.0", Ordering::Less),
+            ("1.0~rc1", "1.0~rc2", Ordering::Less),
+            ("1.0~rc1~git123", "1.0~rc1", Ordering::Less),
+            ("1.0^", "1.0^", Ordering::Equal),
+            ("1.0^", "1.0", Ordering::Greater),
+            ("1.0^git1", "1.0", Ordering::Greater),
+            ("1.0^git1", "1.01", Ordering::Less),
+            ("1.0^20160101", "1.0.1", Ordering::Less),
+            ("1.0^20160101^git1", "1.0^20160101", Ordering::Greater),
+            ("1.0^git1", "1.0^git2", Ordering::Less),
+            ("1.0~rc1^git1", "1.0~rc1", Ordering::Greater),
+            ("1.0^git1~pre", "1.0^git1", Ordering::Less),
+            ("a", "1", Ordering::Less),
+            ("1b.fc17", "1.fc17", Ordering::Less),
+            ("2a", "2.0", Ordering::Less),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(rpmvercmp(a, b), expected, "{} <=> {}", a, b);
+            assert_eq!(rpmvercmp(b, a), expected.reverse(), "{} <=> {}", b, a);
+        }
+    }
+
+    #[test]
+    fn epochs() {
+        assert_eq!(evrcmp((1, "1.0", "1"), (0, "2.0", "1")), Ordering::Greater);
+        assert_eq!(evrcmp((0, "1.0", "1.fc39"), (0, "1.0", "2.fc39")), Ordering::Less);
+        assert_eq!(evrcmp((0, "1.0", "1.fc39"), (0, "1.0", "1.fc39")), Ordering::Equal);
+    }
+}